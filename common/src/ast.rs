@@ -1,27 +1,652 @@
-use crate::types::DataType;
+use std::fmt::{Display, Formatter};
+use crate::datetime::{Date, Timestamp};
+use crate::types::{Collation, DataType, Decimal};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Statement {
-    Create { table_name: String, columns: Vec<Column> },
+    Create {
+        table_name: String,
+        columns: Vec<Column>,
+        if_not_exists: bool,
+        // 表级主键 PRIMARY KEY (a, b)
+        primary_key: Vec<String>,
+        comment: Option<String>,
+    },
     Insert {
         table_name: String,
         columns: Option<Vec<String>>,
         values: Vec<Vec<Expression>>,
+        // RETURNING projection, 返回写入的行
+        returning: Option<Projection>,
+    },
+    Select {
+        distinct: bool,
+        projection: Projection,
+        from: FromItem,
+        filter: Option<Expression>,
+        group_by: Vec<Expression>,
+        having: Option<Expression>,
+        // (排序表达式, 是否降序)
+        order_by: Vec<(Expression, bool)>,
+        limit: Option<u64>,
+        offset: Option<u64>,
+    },
+    Update {
+        table_name: String,
+        assignments: Vec<(String, Expression)>,
+        filter: Option<Expression>,
+        // 返回更新后的行
+        returning: Option<Projection>,
+    },
+    Delete {
+        table_name: String,
+        filter: Option<Expression>,
+        // 返回删除前的行
+        returning: Option<Projection>,
+    },
+    Drop { table_name: String, if_exists: bool },
+    // 清空表中的行, 保留表结构
+    Truncate { table_name: String },
+    AlterTable {
+        table_name: String,
+        op: AlterOperation,
+    },
+    // left UNION [ALL] right, 左结合, 两侧为 SELECT 或 UNION
+    Union {
+        left: Box<Statement>,
+        right: Box<Statement>,
+        all: bool,
+    },
+    // EXPLAIN statement
+    Explain(Box<Statement>),
+}
+
+// 还原为规范化的 SQL, 以分号结尾
+pub fn to_sql(stmt: &Statement) -> String {
+    format!("{};", stmt)
+}
+
+impl Statement {
+    // 语句中参与求值的表达式, 含派生表查询中的, 不含标量子查询内部的
+    pub fn expressions(&self) -> Vec<&Expression> {
+        match self {
+            Statement::Select { projection, from, filter, group_by, having, order_by, .. } => {
+                projection.expressions().into_iter()
+                    .chain(from.conditions())
+                    .chain(filter)
+                    .chain(group_by)
+                    .chain(having)
+                    .chain(order_by.iter().map(|(expr, _)| expr))
+                    .chain(from.subqueries().into_iter().flat_map(Statement::expressions))
+                    .collect()
+            }
+            Statement::Insert { values, returning, .. } => values.iter().flatten().chain(returning.iter().flat_map(Projection::expressions)).collect(),
+            Statement::Update { assignments, filter, returning, .. } => {
+                assignments.iter().map(|(_, expr)| expr).chain(filter).chain(returning.iter().flat_map(Projection::expressions)).collect()
+            }
+            Statement::Delete { filter, returning, .. } => filter.iter().chain(returning.iter().flat_map(Projection::expressions)).collect(),
+            Statement::Union { left, right, .. } => [left.expressions(), right.expressions()].concat(),
+            Statement::Explain(stmt) => stmt.expressions(),
+            _ => vec![],
+        }
+    }
+
+    pub fn expressions_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Statement::Select { projection, from, filter, group_by, having, order_by, .. } => {
+                let (conditions, subqueries) = from.conditions_and_subqueries_mut();
+
+                projection.expressions_mut().into_iter()
+                    .chain(conditions)
+                    .chain(filter)
+                    .chain(group_by)
+                    .chain(having)
+                    .chain(order_by.iter_mut().map(|(expr, _)| expr))
+                    .chain(subqueries.into_iter().flat_map(Statement::expressions_mut))
+                    .collect()
+            }
+            Statement::Insert { values, returning, .. } => values.iter_mut().flatten().chain(returning.iter_mut().flat_map(Projection::expressions_mut)).collect(),
+            Statement::Update { assignments, filter, returning, .. } => {
+                assignments.iter_mut().map(|(_, expr)| expr).chain(filter).chain(returning.iter_mut().flat_map(Projection::expressions_mut)).collect()
+            }
+            Statement::Delete { filter, returning, .. } => filter.iter_mut().chain(returning.iter_mut().flat_map(Projection::expressions_mut)).collect(),
+            Statement::Union { left, right, .. } => left.expressions_mut().into_iter().chain(right.expressions_mut()).collect(),
+            Statement::Explain(stmt) => stmt.expressions_mut(),
+            _ => vec![],
+        }
+    }
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Statement::Create { table_name, columns, if_not_exists, primary_key, comment } => {
+                write!(f, "CREATE TABLE {}{} ({}", if *if_not_exists { "IF NOT EXISTS " } else { "" }, Ident(table_name), List(columns))?;
+                if !primary_key.is_empty() {
+                    write!(f, ", PRIMARY KEY ({})", List(&primary_key.iter().map(|name| Ident(name)).collect::<Vec<_>>()))?;
+                }
+                write!(f, ")")?;
+                if let Some(comment) = comment {
+                    write!(f, " COMMENT {}", Quoted(comment))?;
+                }
+                Ok(())
+            }
+            Statement::Insert { table_name, columns, values, returning } => {
+                write!(f, "INSERT INTO {}", Ident(table_name))?;
+                if let Some(columns) = columns {
+                    write!(f, " ({})", List(&columns.iter().map(|name| Ident(name)).collect::<Vec<_>>()))?;
+                }
+                write!(f, " VALUES ")?;
+                for (i, row) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "({})", List(row))?;
+                }
+                Returning(returning).fmt(f)
+            }
+            Statement::Select { distinct, projection, from, filter, group_by, having, order_by, limit, offset } => {
+                write!(f, "SELECT {}{} FROM {}", if *distinct { "DISTINCT " } else { "" }, projection, from)?;
+                if let Some(filter) = filter {
+                    write!(f, " WHERE {}", filter)?;
+                }
+                if !group_by.is_empty() {
+                    write!(f, " GROUP BY {}", List(group_by))?;
+                }
+                if let Some(having) = having {
+                    write!(f, " HAVING {}", having)?;
+                }
+                if !order_by.is_empty() {
+                    let keys = order_by.iter()
+                        .map(|(expr, desc)| format!("{}{}", expr, if *desc { " DESC" } else { "" }))
+                        .collect::<Vec<_>>();
+                    write!(f, " ORDER BY {}", keys.join(", "))?;
+                }
+                if let Some(limit) = limit {
+                    write!(f, " LIMIT {}", limit)?;
+                }
+                if let Some(offset) = offset {
+                    write!(f, " OFFSET {}", offset)?;
+                }
+                Ok(())
+            }
+            Statement::Update { table_name, assignments, filter, returning } => {
+                let assignments = assignments.iter()
+                    .map(|(column, expr)| format!("{} = {}", Ident(column), expr))
+                    .collect::<Vec<_>>();
+                write!(f, "UPDATE {} SET {}", Ident(table_name), assignments.join(", "))?;
+                if let Some(filter) = filter {
+                    write!(f, " WHERE {}", filter)?;
+                }
+                Returning(returning).fmt(f)
+            }
+            Statement::Delete { table_name, filter, returning } => {
+                write!(f, "DELETE FROM {}", Ident(table_name))?;
+                if let Some(filter) = filter {
+                    write!(f, " WHERE {}", filter)?;
+                }
+                Returning(returning).fmt(f)
+            }
+            Statement::Drop { table_name, if_exists } => write!(f, "DROP TABLE {}{}", if *if_exists { "IF EXISTS " } else { "" }, Ident(table_name)),
+            Statement::Truncate { table_name } => write!(f, "TRUNCATE TABLE {}", Ident(table_name)),
+            Statement::AlterTable { table_name, op: AlterOperation::AddColumn(column) } => write!(f, "ALTER TABLE {} ADD COLUMN {}", Ident(table_name), column),
+            Statement::AlterTable { table_name, op: AlterOperation::DropColumn(name) } => write!(f, "ALTER TABLE {} DROP COLUMN {}", Ident(table_name), Ident(name)),
+            Statement::Union { left, right, all } => write!(f, "{} UNION {}{}", left, if *all { "ALL " } else { "" }, right),
+            Statement::Explain(stmt) => write!(f, "EXPLAIN {}", stmt),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlterOperation {
+    // ADD [COLUMN] column_def
+    AddColumn(Column),
+    // DROP [COLUMN] column_name
+    DropColumn(String),
+}
+
+// FROM 子句, 连接为左深树
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FromItem {
+    Table(String),
+    // (query) [AS] alias, 派生表以别名作为表名
+    Subquery {
+        query: Box<Statement>,
+        alias: String,
+    },
+    // left [INNER] JOIN right ON on, 交叉连接 left, right 无连接条件
+    Join {
+        left: Box<FromItem>,
+        right: Box<FromItem>,
+        on: Option<Expression>,
     },
-    Select { table_name: String },
 }
 
-#[derive(Debug, PartialEq)]
+impl FromItem {
+    // 连接条件
+    pub fn conditions(&self) -> Vec<&Expression> {
+        match self {
+            FromItem::Table(_) | FromItem::Subquery { .. } => vec![],
+            FromItem::Join { left, right, on } => [left.conditions(), right.conditions(), on.iter().collect()].concat(),
+        }
+    }
+
+    // 派生表的查询
+    pub fn subqueries(&self) -> Vec<&Statement> {
+        match self {
+            FromItem::Table(_) => vec![],
+            FromItem::Subquery { query, .. } => vec![query],
+            FromItem::Join { left, right, .. } => [left.subqueries(), right.subqueries()].concat(),
+        }
+    }
+
+    fn conditions_and_subqueries_mut(&mut self) -> (Vec<&mut Expression>, Vec<&mut Statement>) {
+        match self {
+            FromItem::Table(_) => (vec![], vec![]),
+            FromItem::Subquery { query, .. } => (vec![], vec![query]),
+            FromItem::Join { left, right, on } => {
+                let ((mut conditions, mut subqueries), (right_conditions, right_subqueries)) =
+                    (left.conditions_and_subqueries_mut(), right.conditions_and_subqueries_mut());
+                conditions.extend(right_conditions.into_iter().chain(on));
+                subqueries.extend(right_subqueries);
+
+                (conditions, subqueries)
+            }
+        }
+    }
+}
+
+impl Display for FromItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FromItem::Table(name) => write!(f, "{}", Ident(name)),
+            FromItem::Subquery { query, alias } => write!(f, "({}) AS {}", query, Ident(alias)),
+            FromItem::Join { left, right, on: Some(on) } => write!(f, "{} JOIN {} ON {}", left, right, on),
+            FromItem::Join { left, right, on: None } => write!(f, "{}, {}", left, right),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Projection {
+    // *
+    All,
+    // (表达式, 别名)
+    Columns(Vec<(Expression, Option<String>)>),
+}
+
+impl Projection {
+    pub fn expressions(&self) -> Vec<&Expression> {
+        match self {
+            Projection::All => vec![],
+            Projection::Columns(cols) => cols.iter().map(|(expr, _)| expr).collect(),
+        }
+    }
+
+    pub fn expressions_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Projection::All => vec![],
+            Projection::Columns(cols) => cols.iter_mut().map(|(expr, _)| expr).collect(),
+        }
+    }
+}
+
+impl Display for Projection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Projection::All => write!(f, "*"),
+            Projection::Columns(cols) => {
+                for (i, (expr, alias)) in cols.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", expr)?;
+                    if let Some(alias) = alias {
+                        write!(f, " AS {}", Ident(alias))?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// [ RETURNING projection]
+struct Returning<'a>(&'a Option<Projection>);
+
+impl Display for Returning<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(projection) => write!(f, " RETURNING {}", projection),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Column {
     pub name: String,
     pub data_type: DataType,
+    // VARCHAR(n) 的最大长度
+    pub max_length: Option<usize>,
     pub nullable: Option<bool>,
     pub default: Option<Expression>,
+    pub primary_key: bool,
+    pub unique: bool,
+    pub check: Option<Expression>,
+    pub auto_increment: bool,
+    // 未指定时使用数据库的默认规则
+    pub collation: Option<Collation>,
+    pub comment: Option<String>,
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ", Ident(&self.name))?;
+        match self.max_length {
+            Some(len) => write!(f, "VARCHAR({})", len)?,
+            None => write!(f, "{}", self.data_type)?,
+        }
+        match self.nullable {
+            Some(true) => write!(f, " NULL")?,
+            Some(false) => write!(f, " NOT NULL")?,
+            None => {}
+        }
+        // DEFAULT 之后只解析算术运算, 比较、逻辑运算及谓词须加括号
+        match &self.default {
+            Some(default @ Expression::BinaryOp { op: Operator::Equal | Operator::NotEqual | Operator::LessThan | Operator::LessThanOrEqual
+                | Operator::GreaterThan | Operator::GreaterThanOrEqual | Operator::And | Operator::Or, .. })
+            | Some(default @ (Expression::UnaryOp { op: UnaryOperator::Not, .. } | Expression::IsNull { .. } | Expression::Between { .. }
+                | Expression::InList { .. } | Expression::Like { .. })) => write!(f, " DEFAULT ({})", default)?,
+            Some(default) => write!(f, " DEFAULT {}", default)?,
+            None => {}
+        }
+        if self.primary_key {
+            write!(f, " PRIMARY KEY")?;
+        }
+        if self.unique {
+            write!(f, " UNIQUE")?;
+        }
+        if self.auto_increment {
+            write!(f, " AUTOINCREMENT")?;
+        }
+        if let Some(check) = &self.check {
+            write!(f, " CHECK ({})", check)?;
+        }
+        if let Some(collation) = &self.collation {
+            write!(f, " COLLATE {}", collation)?;
+        }
+        if let Some(comment) = &self.comment {
+            write!(f, " COMMENT {}", Quoted(comment))?;
+        }
+        Ok(())
+    }
+}
+
+// 逗号分隔输出
+struct List<'a, T>(&'a [T]);
+
+impl<T: Display> Display for List<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, item) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
+
+// 与 parser 的关键字一致, 同名标识符须加引号
+pub const KEYWORDS: &[&str] = &[
+    "create", "table", "int", "integer", "boolean", "bool", "string", "text", "varchar", "float", "double",
+    "select", "from", "insert", "into", "values", "true", "false", "default", "not", "null", "primary", "key",
+    "where", "and", "or", "update", "set", "delete", "drop", "if", "exists", "unique", "order", "by", "asc",
+    "desc", "limit", "offset", "is", "between", "in", "like", "as", "group", "having", "distinct", "join",
+    "inner", "on", "explain", "alter", "add", "column", "decimal", "numeric", "date", "timestamp", "check", "autoincrement",
+    "truncate", "case", "when", "then", "else", "end", "cast", "union", "all", "returning", "collate", "comment",
+];
+
+// 标识符, 含大写或特殊字符、与关键字同名时加引号, 保证重新解析得到同一名称
+struct Ident<'a>(&'a str);
+
+impl Display for Ident<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = self.0;
+        let plain = name.starts_with(|c: char| c.is_lowercase() || c == '_')
+            && name.chars().all(|c| c.is_lowercase() || c.is_numeric() || c == '_')
+            && !KEYWORDS.contains(&name);
+
+        match name {
+            _ if plain => write!(f, "{}", name),
+            _ if name.contains('"') => write!(f, "`{}`", name),
+            _ => write!(f, "\"{}\"", name),
+        }
+    }
+}
+
+// 字符串字面量, 其中的 ' 写为 ''
+struct Quoted<'a>(&'a str);
+
+impl Display for Quoted<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}'", self.0.replace('\'', "''"))
+    }
+}
+
+// 在输入中的位置, 行列均从 1 开始, 列按字符计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
 }
 
-#[derive(Debug, PartialEq)]
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expression {
-    Const(Const)
+    Const(Const),
+    // 列引用, 限定列名为 table.column
+    Column(String),
+    BinaryOp {
+        left: Box<Expression>,
+        op: Operator,
+        right: Box<Expression>,
+    },
+    UnaryOp {
+        op: UnaryOperator,
+        expr: Box<Expression>,
+    },
+    // expr IS [NOT] NULL
+    IsNull {
+        expr: Box<Expression>,
+        negated: bool,
+    },
+    // expr [NOT] BETWEEN low AND high, 包含边界
+    Between {
+        expr: Box<Expression>,
+        low: Box<Expression>,
+        high: Box<Expression>,
+        negated: bool,
+    },
+    // expr [NOT] IN (item, ...)
+    InList {
+        expr: Box<Expression>,
+        list: Vec<Expression>,
+        negated: bool,
+    },
+    // expr [NOT] LIKE pattern
+    Like {
+        expr: Box<Expression>,
+        pattern: Box<Expression>,
+        negated: bool,
+    },
+    // 聚合函数, arg 为空表示 COUNT(*)
+    Aggregate {
+        func: AggregateFunction,
+        arg: Option<Box<Expression>>,
+    },
+    // 标量函数, 逐行求值
+    Function {
+        func: ScalarFunction,
+        args: Vec<Expression>,
+    },
+    // VALUES 中的 DEFAULT, 取对应列的默认值
+    Default,
+    // CASE WHEN cond THEN result [...] [ELSE result] END, 取第一个条件为真的分支
+    Case {
+        branches: Vec<(Expression, Expression)>,
+        else_: Option<Box<Expression>>,
+    },
+    // CAST(expr AS type), 显式类型转换
+    Cast {
+        expr: Box<Expression>,
+        target: DataType,
+    },
+    // 参数占位符 ? 或 $n, 从 1 开始编号, 执行前由 Plan::bind 替换为常量
+    Placeholder(usize),
+    // (select ...) 标量子查询, 不引用外层的列, 执行前求值一次并替换为常量
+    Subquery(Box<Statement>),
+}
+
+impl Expression {
+    pub fn binary(left: Expression, op: Operator, right: Expression) -> Self {
+        Self::BinaryOp { left: Box::new(left), op, right: Box::new(right) }
+    }
+
+    pub fn unary(op: UnaryOperator, expr: Expression) -> Self {
+        Self::UnaryOp { op, expr: Box::new(expr) }
+    }
+
+    // 直接子表达式
+    pub fn children(&self) -> Vec<&Expression> {
+        match self {
+            Self::Const(_) | Self::Column(_) | Self::Default | Self::Placeholder(_) | Self::Subquery(_) => vec![],
+            Self::BinaryOp { left, right, .. } => vec![left, right],
+            Self::UnaryOp { expr, .. } | Self::IsNull { expr, .. } | Self::Cast { expr, .. } => vec![expr],
+            Self::Between { expr, low, high, .. } => vec![expr, low, high],
+            Self::InList { expr, list, .. } => std::iter::once(expr.as_ref()).chain(list).collect(),
+            Self::Like { expr, pattern, .. } => vec![expr, pattern],
+            Self::Aggregate { arg, .. } => arg.iter().map(AsRef::as_ref).collect(),
+            Self::Function { args, .. } => args.iter().collect(),
+            Self::Case { branches, else_ } => branches.iter().flat_map(|(cond, result)| [cond, result]).chain(else_.as_deref()).collect(),
+        }
+    }
+
+    pub fn children_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Self::Const(_) | Self::Column(_) | Self::Default | Self::Placeholder(_) | Self::Subquery(_) => vec![],
+            Self::BinaryOp { left, right, .. } => vec![left, right],
+            Self::UnaryOp { expr, .. } | Self::IsNull { expr, .. } | Self::Cast { expr, .. } => vec![expr],
+            Self::Between { expr, low, high, .. } => vec![expr, low, high],
+            Self::InList { expr, list, .. } => std::iter::once(expr.as_mut()).chain(list).collect(),
+            Self::Like { expr, pattern, .. } => vec![expr, pattern],
+            Self::Aggregate { arg, .. } => arg.iter_mut().map(AsMut::as_mut).collect(),
+            Self::Function { args, .. } => args.iter_mut().collect(),
+            Self::Case { branches, else_ } => branches.iter_mut().flat_map(|(cond, result)| [cond, result]).chain(else_.as_deref_mut()).collect(),
+        }
+    }
+
+    // 表达式中引用的所有列名
+    pub fn columns(&self) -> Vec<&String> {
+        match self {
+            Self::Column(name) => vec![name],
+            expr => expr.children().into_iter().flat_map(Self::columns).collect(),
+        }
+    }
+
+    // 表达式中占位符的编号, 含标量子查询内部的
+    pub fn placeholders(&self) -> Vec<usize> {
+        match self {
+            Self::Placeholder(n) => vec![*n],
+            Self::Subquery(query) => query.expressions().into_iter().flat_map(Self::placeholders).collect(),
+            expr => expr.children().into_iter().flat_map(Self::placeholders).collect(),
+        }
+    }
+
+    // 表达式中的标量子查询, 不含子查询内部嵌套的子查询
+    pub fn subqueries(&self) -> Vec<&Statement> {
+        match self {
+            Self::Subquery(query) => vec![query],
+            expr => expr.children().into_iter().flat_map(Self::subqueries).collect(),
+        }
+    }
+
+    pub fn contains_aggregate(&self) -> bool {
+        matches!(self, Self::Aggregate { .. }) || self.children().into_iter().any(Self::contains_aggregate)
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let not = |negated: &bool| if *negated { "NOT " } else { "" };
+
+        match self {
+            Self::Const(c) => write!(f, "{}", c),
+            // tbl.col
+            Self::Column(name) => match name.split_once('.') {
+                Some((table, column)) => write!(f, "{}.{}", Ident(table), Ident(column)),
+                None => write!(f, "{}", Ident(name)),
+            },
+            Self::BinaryOp { left, op, right } => {
+                let tight = !matches!(op, Operator::And | Operator::Or);
+                write!(f, "{} {} {}", Operand(left, tight), op, Operand(right, tight))
+            }
+            // - -a 及负数常量前的 - 会与之连成注释
+            Self::UnaryOp { op: UnaryOperator::Negate, expr } if expr.to_string().starts_with('-') => write!(f, "-({})", expr),
+            Self::UnaryOp { op: UnaryOperator::Negate, expr } => write!(f, "-{}", Operand(expr, true)),
+            Self::UnaryOp { op: UnaryOperator::Not, expr } => write!(f, "NOT {}", Operand(expr, false)),
+            Self::IsNull { expr, negated } => write!(f, "{} IS {}NULL", Operand(expr, true), not(negated)),
+            Self::Between { expr, low, high, negated } => {
+                write!(f, "{} {}BETWEEN {} AND {}", Operand(expr, true), not(negated), Operand(low, true), Operand(high, true))
+            }
+            Self::InList { expr, list, negated } => write!(f, "{} {}IN ({})", Operand(expr, true), not(negated), List(list)),
+            Self::Like { expr, pattern, negated } => write!(f, "{} {}LIKE {}", Operand(expr, true), not(negated), Operand(pattern, true)),
+            Self::Aggregate { func, arg: None } => write!(f, "{}(*)", func),
+            Self::Aggregate { func, arg: Some(arg) } => write!(f, "{}({})", func, arg),
+            Self::Function { func, args } => write!(f, "{}({})", func, List(args)),
+            Self::Default => write!(f, "DEFAULT"),
+            Self::Case { branches, else_ } => {
+                write!(f, "CASE")?;
+                for (cond, result) in branches {
+                    write!(f, " WHEN {} THEN {}", cond, result)?;
+                }
+                if let Some(else_) = else_ {
+                    write!(f, " ELSE {}", else_)?;
+                }
+                write!(f, " END")
+            }
+            Self::Cast { expr, target } => write!(f, "CAST({} AS {})", expr, target),
+            Self::Placeholder(n) => write!(f, "${}", n),
+            Self::Subquery(query) => write!(f, "({})", query),
+        }
+    }
+}
+
+// 二元运算作为操作数时加括号
+// 紧密结合处(比较、算术、后缀谓词的操作数)的前缀运算和后缀谓词也加括号, 如 (NOT a) = b
+struct Operand<'a>(&'a Expression, bool);
+
+impl Display for Operand<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            expr @ Expression::BinaryOp { .. } => write!(f, "({})", expr),
+            expr @ (Expression::UnaryOp { op: UnaryOperator::Not, .. } | Expression::IsNull { .. } | Expression::Between { .. }
+                | Expression::InList { .. } | Expression::Like { .. }) if self.1 => write!(f, "({})", expr),
+            expr => write!(f, "{}", expr),
+        }
+    }
 }
 
 impl From<Const> for Expression {
@@ -30,11 +655,162 @@ impl From<Const> for Expression {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Const {
     Null,
     Boolean(bool),
     Integer(i64),
-    Float(f64),
+    Float(#[cfg_attr(feature = "serde", serde(with = "crate::types::float_serde"))] f64),
     String(String),
-}
\ No newline at end of file
+    // 以下仅由求值结果产生, 不来自字面量
+    Decimal(Decimal),
+    Date(Date),
+    Timestamp(Timestamp),
+}
+
+impl Display for Const {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Const::Null => write!(f, "NULL"),
+            Const::Boolean(v) => write!(f, "{}", v),
+            Const::Integer(v) => write!(f, "{}", v),
+            // 不用指数形式, 整数值补 .0 以免解析为整数
+            Const::Float(v) if v.is_finite() && v.fract() == 0.0 => write!(f, "{}.0", v),
+            Const::Float(v) => write!(f, "{}", v),
+            Const::String(v) => write!(f, "{}", Quoted(v)),
+            Const::Decimal(v) => write!(f, "{}", v),
+            Const::Date(v) => write!(f, "DATE '{}'", v),
+            Const::Timestamp(v) => write!(f, "TIMESTAMP '{}'", v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    And,
+    Or,
+    Concat,
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Modulo => "%",
+            Operator::Equal => "=",
+            Operator::NotEqual => "<>",
+            Operator::LessThan => "<",
+            Operator::LessThanOrEqual => "<=",
+            Operator::GreaterThan => ">",
+            Operator::GreaterThanOrEqual => ">=",
+            Operator::And => "AND",
+            Operator::Or => "OR",
+            Operator::Concat => "||",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnaryOperator {
+    // -
+    Negate,
+    // NOT
+    Not,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl Display for AggregateFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AggregateFunction::Count => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScalarFunction {
+    // 第一个非 Null 的参数
+    Coalesce,
+    // 两参数相等时为 Null, 否则为第一个参数
+    NullIf,
+}
+
+impl ScalarFunction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "COALESCE" => Some(Self::Coalesce),
+            "NULLIF" => Some(Self::NullIf),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ScalarFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ScalarFunction::Coalesce => "COALESCE",
+            ScalarFunction::NullIf => "NULLIF",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expression_display() {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let int = |i| Expression::from(Const::Integer(i));
+
+        let expr = Expression::binary(
+            Expression::binary(col("a"), Operator::Equal, int(1)),
+            Operator::Or,
+            Expression::unary(UnaryOperator::Not, Expression::binary(col("b"), Operator::Add, Const::Float(2.0).into())),
+        );
+        assert_eq!(expr.to_string(), "(a = 1) OR NOT (b + 2.0)");
+
+        let expr = Expression::InList { expr: Box::new(col("t.a")), list: vec![int(1), Const::String("x".to_string()).into()], negated: true };
+        assert_eq!(expr.to_string(), "t.a NOT IN (1, 'x')");
+
+        let expr = Expression::Between { expr: Box::new(col("a")), low: Box::new(int(-1)), high: Box::new(Const::Null.into()), negated: false };
+        assert_eq!(expr.to_string(), "a BETWEEN -1 AND NULL");
+
+        assert_eq!(Expression::Aggregate { func: AggregateFunction::Count, arg: None }.to_string(), "COUNT(*)");
+        assert_eq!(Expression::IsNull { expr: Box::new(col("a")), negated: true }.to_string(), "a IS NOT NULL");
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use anyhow::{anyhow, Error, Result};
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_DAY: i64 = 86_400 * MICROS_PER_SECOND;
+
+// 日期, 自 1970-01-01 起的天数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Date(pub i32);
+
+// 时间戳, 自 1970-01-01 00:00:00 起的微秒数, 不带时区
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timestamp(pub i64);
+
+impl Date {
+    pub fn from_ymd(year: i64, month: u32, day: u32) -> Option<Date> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        // 以 3 月为一年之始, 闰日落在年末
+        let y = if month <= 2 { year - 1 } else { year };
+        let era = y.div_euclid(400);
+        let yoe = y.rem_euclid(400);
+        let doy = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+        i32::try_from(era * 146097 + doe - 719468).ok().map(Date)
+    }
+
+    pub fn ymd(self) -> (i64, u32, u32) {
+        let z = self.0 as i64 + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+
+        (yoe + era * 400 + (month <= 2) as i64, month, day)
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// 定长数字字段, 如年份 4 位、月份 2 位
+fn parse_digits(s: &str, len: usize) -> Option<u32> {
+    (s.len() == len && s.chars().all(|c| c.is_ascii_digit())).then(|| s.parse().ok()).flatten()
+}
+
+// YYYY-MM-DD
+impl FromStr for Date {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split('-');
+        let (Some(year), Some(month), Some(day), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(anyhow!("Invalid DATE: {}", s));
+        };
+
+        parse_digits(year, 4).zip(parse_digits(month, 2)).zip(parse_digits(day, 2))
+            .and_then(|((year, month), day)| Date::from_ymd(year as i64, month, day))
+            .ok_or(anyhow!("Invalid DATE: {}", s))
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = self.ymd();
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+impl From<Date> for Timestamp {
+    fn from(date: Date) -> Self {
+        Timestamp(date.0 as i64 * MICROS_PER_DAY)
+    }
+}
+
+impl Timestamp {
+    // 所在的日期, 舍去时间部分
+    pub fn date(self) -> Date {
+        Date(self.0.div_euclid(MICROS_PER_DAY) as i32)
+    }
+}
+
+// YYYY-MM-DD[( |T)HH:MM:SS[.ffffff]], 省略时间时为零点
+impl FromStr for Timestamp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid TIMESTAMP: {}", s);
+
+        let (date, time) = s.split_once([' ', 'T']).unwrap_or((s, "00:00:00"));
+        let date = date.parse::<Date>().map_err(|_| invalid())?;
+
+        let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+        let mut parts = time.split(':');
+        let (Some(hour), Some(minute), Some(second), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        let (hour, minute, second) = parse_digits(hour, 2).zip(parse_digits(minute, 2)).zip(parse_digits(second, 2))
+            .map(|((hour, minute), second)| (hour as i64, minute as i64, second as i64))
+            .filter(|&(hour, minute, second)| hour < 24 && minute < 60 && second < 60)
+            .ok_or_else(invalid)?;
+
+        // 小数部分最多到微秒
+        let micros = match fraction.len() {
+            0 => 0,
+            len @ 1..=6 => parse_digits(fraction, len).ok_or_else(invalid)? as i64 * 10i64.pow(6 - len as u32),
+            _ => return Err(invalid()),
+        };
+
+        Ok(Timestamp(Timestamp::from(date).0 + ((hour * 60 + minute) * 60 + second) * MICROS_PER_SECOND + micros))
+    }
+}
+
+// 微秒为零时省略小数部分, 否则去掉末尾的 0
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let date = self.date();
+        let micros = self.0.rem_euclid(MICROS_PER_DAY);
+        let seconds = micros / MICROS_PER_SECOND;
+
+        write!(f, "{} {:02}:{:02}:{:02}", date, seconds / 3600, seconds / 60 % 60, seconds % 60)?;
+        match micros % MICROS_PER_SECOND {
+            0 => Ok(()),
+            fraction => write!(f, ".{}", format!("{:06}", fraction).trim_end_matches('0')),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date() -> Result<()> {
+        assert_eq!("1970-01-01".parse::<Date>()?, Date(0));
+        assert_eq!("2024-01-02".parse::<Date>()?, Date(19724));
+        assert_eq!("1969-12-31".parse::<Date>()?, Date(-1));
+        assert_eq!("2024-02-29".parse::<Date>()?.to_string(), "2024-02-29");
+        assert_eq!("0001-01-01".parse::<Date>()?.to_string(), "0001-01-01");
+        assert_eq!("9999-12-31".parse::<Date>()?.to_string(), "9999-12-31");
+
+        for s in ["2023-02-29", "2024-13-01", "2024-00-10", "2024-1-02", "24-01-02", "2024-01-02-03", "2024/01/02", "abcd-01-02", ""] {
+            assert_eq!(s.parse::<Date>().unwrap_err().to_string(), format!("Invalid DATE: {}", s));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_timestamp() -> Result<()> {
+        assert_eq!("1970-01-01 00:00:01".parse::<Timestamp>()?, Timestamp(1_000_000));
+        assert_eq!("1970-01-02".parse::<Timestamp>()?, Timestamp(MICROS_PER_DAY));
+        assert_eq!("1969-12-31 23:59:59.5".parse::<Timestamp>()?, Timestamp(-500_000));
+
+        for (s, display) in [
+            ("2024-01-02 03:04:05", "2024-01-02 03:04:05"),
+            ("2024-01-02T03:04:05.120", "2024-01-02 03:04:05.12"),
+            ("1969-12-31 23:59:59.000001", "1969-12-31 23:59:59.000001"),
+            ("2024-01-02", "2024-01-02 00:00:00"),
+        ] {
+            assert_eq!(s.parse::<Timestamp>()?.to_string(), display);
+        }
+
+        for s in ["2024-01-02 24:00:00", "2024-01-02 03:04", "2024-01-02 03:04:05.1234567", "2024-01-02 3:04:05", "2024-02-30 00:00:00"] {
+            assert_eq!(s.parse::<Timestamp>().unwrap_err().to_string(), format!("Invalid TIMESTAMP: {}", s));
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 pub mod types;
 pub mod schema;
 pub mod ast;
+pub mod datetime;
+pub mod ops;
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right
@@ -0,0 +1,137 @@
+use std::cmp::Ordering;
+use anyhow::{anyhow, bail, Result};
+use crate::ast::{Operator, UnaryOperator};
+use crate::types::{Decimal, Value};
+
+// 值之间的运算, 执行时求值和规划时常量折叠共用
+pub fn eval_binary(op: &Operator, left: Value, right: Value) -> Result<Value> {
+    match op {
+        Operator::Add | Operator::Subtract | Operator::Multiply | Operator::Divide | Operator::Modulo =>
+            eval_arithmetic(op, left, right),
+        Operator::Equal | Operator::NotEqual | Operator::LessThan | Operator::LessThanOrEqual
+        | Operator::GreaterThan | Operator::GreaterThanOrEqual => eval_compare(op, left, right),
+        Operator::And | Operator::Or => eval_logic(op, left, right),
+        Operator::Concat => eval_concat(left, right),
+    }
+}
+
+// 仅拼接字符串, 任一侧为 Null 时结果为 Null
+pub fn eval_concat(left: Value, right: Value) -> Result<Value> {
+    Ok(match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => Value::Null,
+        (Value::String(a), Value::String(b)) => Value::String(a + &b),
+        (a, b) => bail!("Cannot apply {:?} to {:?} and {:?}", Operator::Concat, a, b),
+    })
+}
+
+pub fn eval_unary(op: &UnaryOperator, value: Value) -> Result<Value> {
+    Ok(match (op, value) {
+        (_, Value::Null) => Value::Null,
+        (UnaryOperator::Negate, Value::Integer(v)) => Value::Integer(v.checked_neg().ok_or(anyhow!("Integer overflow"))?),
+        (UnaryOperator::Negate, Value::Float(v)) => Value::Float(-v),
+        (UnaryOperator::Negate, Value::Decimal(v)) => Value::Decimal(-v),
+        (UnaryOperator::Not, Value::Boolean(v)) => Value::Boolean(!v),
+        (op, v) => bail!("Cannot apply {:?} to {:?}", op, v),
+    })
+}
+
+// 任一侧为 Null 时结果未知, 返回 Null
+pub fn eval_compare(op: &Operator, left: Value, right: Value) -> Result<Value> {
+    if left == Value::Null || right == Value::Null {
+        return Ok(Value::Null);
+    }
+
+    let ordering = left.compare(&right)?;
+    Ok(Value::Boolean(match op {
+        Operator::Equal => ordering == Ordering::Equal,
+        Operator::NotEqual => ordering != Ordering::Equal,
+        Operator::LessThan => ordering == Ordering::Less,
+        Operator::LessThanOrEqual => ordering != Ordering::Greater,
+        Operator::GreaterThan => ordering == Ordering::Greater,
+        _ => ordering != Ordering::Less,
+    }))
+}
+
+// 三值逻辑, Null 表示未知: false and null = false, true or null = true, 其余含 Null 的组合为 Null
+pub fn eval_logic(op: &Operator, left: Value, right: Value) -> Result<Value> {
+    let truth = |v: &Value| match v {
+        Value::Boolean(b) => Ok(Some(*b)),
+        Value::Null => Ok(None),
+        _ => bail!("Cannot apply {:?} to {:?} and {:?}", op, left, right),
+    };
+
+    Ok(match (op, truth(&left)?, truth(&right)?) {
+        (Operator::And, Some(false), _) | (Operator::And, _, Some(false)) => Value::Boolean(false),
+        (Operator::And, Some(true), Some(true)) => Value::Boolean(true),
+        (Operator::Or, Some(true), _) | (Operator::Or, _, Some(true)) => Value::Boolean(true),
+        (Operator::Or, Some(false), Some(false)) => Value::Boolean(false),
+        _ => Value::Null,
+    })
+}
+
+// 整数运算检查溢出, 整数与浮点混合时提升为浮点, 除数为零返回错误
+// 定点数与整数的加减乘结果为定点数, 其余与浮点混合或除法时转为浮点
+pub fn eval_arithmetic(op: &Operator, left: Value, right: Value) -> Result<Value> {
+    Ok(match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => Value::Null,
+        (Value::Integer(a), Value::Integer(b)) => Value::Integer(match op {
+            Operator::Add => a.checked_add(b),
+            Operator::Subtract => a.checked_sub(b),
+            Operator::Multiply => a.checked_mul(b),
+            Operator::Divide | Operator::Modulo if b == 0 => bail!("Division by zero"),
+            Operator::Divide => a.checked_div(b),
+            _ => a.checked_rem(b),
+        }.ok_or(anyhow!("Integer overflow"))?),
+        (Value::Integer(a), Value::Float(b)) => eval_float(op, a as f64, b)?,
+        (Value::Float(a), Value::Integer(b)) => eval_float(op, a, b as f64)?,
+        (Value::Float(a), Value::Float(b)) => eval_float(op, a, b)?,
+        (a @ (Value::Integer(_) | Value::Decimal(_)), b @ (Value::Integer(_) | Value::Decimal(_))) => {
+            eval_decimal(op, a.try_into()?, b.try_into()?)?
+        }
+        (Value::Decimal(a), Value::Float(b)) => eval_float(op, a.into(), b)?,
+        (Value::Float(a), Value::Decimal(b)) => eval_float(op, a, b.into())?,
+        (a, b) => bail!("Cannot apply {:?} to {:?} and {:?}", op, a, b),
+    })
+}
+
+fn eval_decimal(op: &Operator, a: Decimal, b: Decimal) -> Result<Value> {
+    let scale = a.scale.max(b.scale);
+    let align = |d: Decimal| 10i128.checked_pow(scale - d.scale).and_then(|factor| d.mantissa.checked_mul(factor));
+
+    let decimal = match op {
+        Operator::Add | Operator::Subtract => align(a).zip(align(b)).and_then(|(a, b)| match op {
+            Operator::Add => a.checked_add(b),
+            _ => a.checked_sub(b),
+        }).map(|mantissa| Decimal { mantissa, scale }),
+        // 小数位数之和超出上限时先去掉两侧末尾的 0, 乘积再四舍五入到上限
+        Operator::Multiply if a.scale + b.scale > Decimal::MAX_PRECISION => {
+            let trim = |mut d: Decimal| {
+                while d.scale > 0 && d.mantissa % 10 == 0 {
+                    d.mantissa /= 10;
+                    d.scale -= 1;
+                }
+                d
+            };
+            let (a, b) = (trim(a), trim(b));
+
+            a.mantissa.checked_mul(b.mantissa)
+                .and_then(|mantissa| Decimal { mantissa, scale: a.scale + b.scale }.rescale(Decimal::MAX_PRECISION, Decimal::MAX_PRECISION).ok())
+        }
+        Operator::Multiply => a.mantissa.checked_mul(b.mantissa)
+            .map(|mantissa| Decimal { mantissa, scale: a.scale + b.scale }),
+        _ => return eval_float(op, a.into(), b.into()),
+    };
+
+    Ok(Value::Decimal(decimal.ok_or(anyhow!("Decimal overflow"))?))
+}
+
+fn eval_float(op: &Operator, a: f64, b: f64) -> Result<Value> {
+    Ok(Value::Float(match op {
+        Operator::Add => a + b,
+        Operator::Subtract => a - b,
+        Operator::Multiply => a * b,
+        Operator::Divide | Operator::Modulo if b == 0.0 => bail!("Division by zero"),
+        Operator::Divide => a / b,
+        _ => a % b,
+    }))
+}
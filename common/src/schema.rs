@@ -1,33 +1,379 @@
-use crate::ast;
-use crate::types::{DataType, Value};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::LazyLock;
+use anyhow::{anyhow, bail, Error, Result};
+use crate::ast::{self, AggregateFunction, Expression, Operator, ScalarFunction, UnaryOperator};
+use crate::types::{Collation, DataType, Decimal, Value};
 
-#[derive(Debug,PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Table {
     pub name: String,
     pub columns: Vec<Column>,
+    pub comment: Option<String>,
 }
 
-#[derive(Debug,PartialEq)]
+impl Table {
+    pub fn get_column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|col| col.name == name)
+    }
+
+    // 列在表结构中的位置
+    // 连接结果的列名均限定为 table.column, 未限定的列名只能匹配其中一列
+    // 单表的列名不限定, 限定列名 table.column 须属于该表
+    pub fn resolve_column(&self, name: &str) -> Result<usize> {
+        let found = self.columns.iter().enumerate().filter(|(_, col)| {
+            match (name.split_once('.'), col.name.split_once('.')) {
+                (Some((table, column)), None) => table == self.name && column == col.name,
+                (None, Some((_, column))) => column == name,
+                _ => col.name == name,
+            }
+        }).map(|(i, _)| i).collect::<Vec<_>>();
+
+        match found[..] {
+            [i] => Ok(i),
+            [] => bail!("Column {} does not exist in table {}", name, self.name),
+            _ => bail!("Ambiguous column {}", name),
+        }
+    }
+
+    // 表达式在该表的行上求值所得的类型, 与求值规则一致
+    // 无类型的 NULL 按 VARCHAR 处理
+    pub fn expression_type(&self, expr: &Expression) -> Result<DataType> {
+        Ok(self.infer_type(expr)?.unwrap_or(DataType::String))
+    }
+
+    // 结果总为 Null 或类型未知时为 None
+    pub fn infer_type(&self, expr: &Expression) -> Result<Option<DataType>> {
+        // 多个候选结果取第一个有类型的
+        let first = |exprs: &mut dyn Iterator<Item = &Expression>| -> Result<Option<DataType>> {
+            for expr in exprs {
+                if let Some(data_type) = self.infer_type(expr)? {
+                    return Ok(Some(data_type));
+                }
+            }
+            Ok(None)
+        };
+
+        Ok(match expr {
+            Expression::Const(c) => Value::from(c.clone()).data_type(),
+            Expression::Column(name) => Some(self.columns[self.resolve_column(name)?].data_type.clone()),
+            Expression::BinaryOp { op: Operator::Concat, .. } => Some(DataType::String),
+            Expression::BinaryOp { op: op @ (Operator::Add | Operator::Subtract | Operator::Multiply | Operator::Divide | Operator::Modulo), left, right } => {
+                let scale = |data_type: &DataType| match data_type {
+                    DataType::Decimal { scale, .. } => *scale,
+                    _ => 0,
+                };
+
+                match (self.infer_type(left)?, self.infer_type(right)?) {
+                    (None, _) | (_, None) => None,
+                    (Some(DataType::Integer), Some(DataType::Integer)) => Some(DataType::Integer),
+                    // 定点数的加减取较大的小数位数, 乘法为两者之和但不超过上限, 除法转为浮点
+                    (Some(a @ (DataType::Integer | DataType::Decimal { .. })), Some(b @ (DataType::Integer | DataType::Decimal { .. }))) => Some(match op {
+                        Operator::Add | Operator::Subtract => DataType::Decimal { precision: Decimal::MAX_PRECISION, scale: scale(&a).max(scale(&b)) },
+                        Operator::Multiply => DataType::Decimal { precision: Decimal::MAX_PRECISION, scale: (scale(&a) + scale(&b)).min(Decimal::MAX_PRECISION) },
+                        _ => DataType::Float,
+                    }),
+                    (Some(DataType::Integer | DataType::Float | DataType::Decimal { .. }), Some(DataType::Integer | DataType::Float | DataType::Decimal { .. })) => {
+                        Some(DataType::Float)
+                    }
+                    (Some(a), Some(b)) => bail!("Cannot apply {:?} to {} and {}", op, a, b),
+                }
+            }
+            Expression::BinaryOp { .. } | Expression::IsNull { .. } | Expression::Between { .. } | Expression::InList { .. }
+            | Expression::Like { .. } | Expression::UnaryOp { op: UnaryOperator::Not, .. } => Some(DataType::Boolean),
+            Expression::UnaryOp { op: UnaryOperator::Negate, expr } => self.infer_type(expr)?,
+            Expression::Aggregate { func: AggregateFunction::Count, .. } => Some(DataType::Integer),
+            Expression::Aggregate { func: AggregateFunction::Avg, .. } => Some(DataType::Float),
+            Expression::Aggregate { arg, .. } => match arg {
+                Some(arg) => self.infer_type(arg)?,
+                None => None,
+            },
+            Expression::Function { func: ScalarFunction::Coalesce, args } => first(&mut args.iter())?,
+            Expression::Function { func: ScalarFunction::NullIf, args } => first(&mut args.iter().take(1))?,
+            Expression::Case { branches, else_ } => first(&mut branches.iter().map(|(_, result)| result).chain(else_.as_deref()))?,
+            Expression::Cast { target, .. } => Some(target.clone()),
+            // 规划时不求值子查询, 类型未知; 执行前已替换为常量
+            Expression::Subquery(_) => None,
+            Expression::Default | Expression::Placeholder(_) => bail!("Cannot infer the type of {}", expr),
+        })
+    }
+}
+
+// 表目录, 供规划时校验表和列
+pub trait Catalog: Debug {
+    fn get_table(&self, name: &str) -> Option<&Table>;
+
+    // 所有用户表, 不含系统表
+    fn tables(&self) -> Vec<&Table>;
+}
+
+impl Catalog for HashMap<String, Table> {
+    fn get_table(&self, name: &str) -> Option<&Table> {
+        self.get(name)
+    }
+
+    fn tables(&self) -> Vec<&Table> {
+        self.values().collect()
+    }
+}
+
+pub const TABLES_TABLE: &str = "__tables__";
+pub const COLUMNS_TABLE: &str = "__columns__";
+
+// 只读的系统表, 查询时由执行器根据目录生成行
+static SYSTEM_TABLES: LazyLock<[Table; 2]> = LazyLock::new(|| {
+    let column = |name: &str, data_type| Column {
+        name: name.to_string(),
+        data_type,
+        max_length: None,
+        nullable: false,
+        default: None,
+        primary_key: false,
+        unique: false,
+        check: None,
+        auto_increment: false,
+        collation: Collation::Binary,
+        comment: None,
+    };
+    let comment = || Column { nullable: true, ..column("comment", DataType::String) };
+
+    [
+        Table {
+            name: TABLES_TABLE.to_string(),
+            columns: vec![column("table_name", DataType::String), comment()],
+            comment: None,
+        },
+        Table {
+            name: COLUMNS_TABLE.to_string(),
+            columns: vec![
+                column("table_name", DataType::String),
+                column("column_name", DataType::String),
+                column("data_type", DataType::String),
+                column("nullable", DataType::Boolean),
+                comment(),
+            ],
+            comment: None,
+        },
+    ]
+});
+
+pub fn system_table(name: &str) -> Option<&'static Table> {
+    SYSTEM_TABLES.iter().find(|table| table.name == name)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Column {
     pub name: String,
     pub data_type: DataType,
+    pub max_length: Option<usize>,
     pub nullable: bool,
-    pub default: Option<Value>,
+    pub default: Option<ColumnDefault>,
+    pub primary_key: bool,
+    pub unique: bool,
+    // CHECK (expr), 为 false 的行不能写入
+    pub check: Option<Expression>,
+    // 插入时省略或为 Null 则取下一个序号
+    pub auto_increment: bool,
+    // 与该列比较时的字符串规则
+    pub collation: Collation,
+    pub comment: Option<String>,
 }
 
-impl From<ast::Column> for Column {
-    fn from(value: ast::Column) -> Self {
-        let nullable = value.nullable.unwrap_or(false);
+// 列的默认值, 常量在建表时求出, 含子查询的表达式在每次插入时求值
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColumnDefault {
+    Const(Value),
+    Dynamic(Expression),
+}
+
+impl ColumnDefault {
+    // 插入时代入省略的列
+    pub fn to_expression(&self) -> Expression {
+        match self {
+            ColumnDefault::Const(value) => Expression::Const(value.clone().into()),
+            ColumnDefault::Dynamic(expr) => expr.clone(),
+        }
+    }
+}
+
+impl Column {
+    // 查询结果中的列: 可为空, 无默认值及约束
+    pub fn output(name: String, data_type: DataType) -> Self {
         Self {
-            name: value.name,
-            data_type: value.data_type,
-            nullable,
+            name,
+            data_type,
+            max_length: None,
+            nullable: true,
+            default: None,
+            primary_key: false,
+            unique: false,
+            check: None,
+            auto_increment: false,
+            collation: Collation::Binary,
+            comment: None,
+        }
+    }
+}
+
+impl TryFrom<ast::Column> for Column {
+    type Error = Error;
+
+    fn try_from(value: ast::Column) -> Result<Self> {
+        // 主键隐含 not null
+        let nullable = !value.primary_key && value.nullable.unwrap_or(false);
+        Ok(Self {
             default: match value.default {
-                Some(expr) => Some(expr.into()),
+                // 动态默认值的类型及可空性在插入时校验
+                Some(expr) if !expr.subqueries().is_empty() => Some(ColumnDefault::Dynamic(expr)),
+                // 默认值须可转换为列类型
+                Some(expr) => Some(ColumnDefault::Const(match Value::try_from(expr)? {
+                    Value::Null if !nullable => bail!("Invalid default value for column {}: cannot be null", value.name),
+                    v => v.coerce_to(&value.data_type).map_err(|e| anyhow!("Invalid default value for column {}: {}", value.name, e))?,
+                })),
                 // 允许为空时,默认值可为空
-                None if nullable => Some(Value::Null),
+                None if nullable => Some(ColumnDefault::Const(Value::Null)),
                 None => None,
             },
-        }
+            name: value.name,
+            data_type: value.data_type,
+            max_length: value.max_length,
+            nullable,
+            primary_key: value.primary_key,
+            unique: value.unique,
+            check: value.check,
+            auto_increment: value.auto_increment,
+            collation: value.collation.unwrap_or_default(),
+            comment: value.comment,
+        })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Const;
+
+    #[test]
+    fn test_column_from_ast() -> Result<()> {
+        let col: Column = ast::Column {
+            name: "a".to_string(),
+            data_type: DataType::Integer,
+            max_length: None,
+            nullable: Some(true),
+            default: None,
+            primary_key: false,
+            unique: false,
+            check: None,
+            auto_increment: false,
+            collation: None,
+            comment: None,
+        }.try_into()?;
+
+        assert_eq!(col, Column {
+            name: "a".to_string(),
+            data_type: DataType::Integer,
+            max_length: None,
+            nullable: true,
+            default: Some(ColumnDefault::Const(Value::Null)),
+            primary_key: false,
+            unique: false,
+            check: None,
+            auto_increment: false,
+            collation: Collation::Binary,
+            comment: None,
+        });
+
+        let col: Column = ast::Column {
+            name: "id".to_string(),
+            data_type: DataType::Integer,
+            max_length: None,
+            nullable: None,
+            default: Some(Const::Integer(1).into()),
+            primary_key: true,
+            unique: true,
+            check: None,
+            auto_increment: false,
+            collation: None,
+            comment: None,
+        }.try_into()?;
+
+        assert_eq!(col, Column {
+            name: "id".to_string(),
+            data_type: DataType::Integer,
+            max_length: None,
+            nullable: false,
+            default: Some(ColumnDefault::Const(Value::Integer(1))),
+            primary_key: true,
+            unique: true,
+            check: None,
+            auto_increment: false,
+            collation: Collation::Binary,
+            comment: None,
+        });
+
+        // 主键即使声明 null 也不可为空
+        let col: Column = ast::Column {
+            name: "id".to_string(),
+            data_type: DataType::Integer,
+            max_length: None,
+            nullable: Some(true),
+            default: None,
+            primary_key: true,
+            unique: false,
+            check: None,
+            auto_increment: false,
+            collation: None,
+            comment: None,
+        }.try_into()?;
+
+        assert!(!col.nullable);
+        assert_eq!(col.default, None);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_table_serde() -> Result<()> {
+        let table = Table {
+            name: "t".to_string(),
+            columns: vec![
+                Column {
+                    name: "id".to_string(),
+                    data_type: DataType::Integer,
+                    max_length: None,
+                    nullable: false,
+                    default: None,
+                    primary_key: true,
+                    unique: true,
+                    check: None,
+                    auto_increment: false,
+                    collation: Collation::Binary,
+                    comment: None,
+                },
+                Column {
+                    name: "score".to_string(),
+                    data_type: DataType::Float,
+                    max_length: None,
+                    nullable: true,
+                    default: Some(ColumnDefault::Const(Value::Null)),
+                    primary_key: false,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: Collation::Binary,
+                    comment: None,
+                },
+            ],
+            comment: None,
+        };
+
+        let json = serde_json::to_string(&table)?;
+        assert_eq!(serde_json::from_str::<Table>(&json)?, table);
+
+        Ok(())
+    }
+}
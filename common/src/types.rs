@@ -1,30 +1,736 @@
-use crate::ast::{Const, Expression};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use anyhow::{anyhow, bail, Error, Result};
+use crate::ast::{Const, Expression, UnaryOperator};
+use crate::datetime::{Date, Timestamp};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataType {
     Integer,
     Float,
     String,
     Boolean,
+    // 总位数及小数位数
+    Decimal { precision: u32, scale: u32 },
+    Date,
+    Timestamp,
 }
 
-#[derive(Debug, PartialEq)]
+impl Display for DataType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataType::Integer => write!(f, "INTEGER"),
+            DataType::Float => write!(f, "FLOAT"),
+            DataType::String => write!(f, "VARCHAR"),
+            DataType::Boolean => write!(f, "BOOLEAN"),
+            DataType::Decimal { precision, scale } => write!(f, "DECIMAL({}, {})", precision, scale),
+            DataType::Date => write!(f, "DATE"),
+            DataType::Timestamp => write!(f, "TIMESTAMP"),
+        }
+    }
+}
+
+impl DataType {
+    // 两列合并 (如 UNION) 后的类型: 整数与定点数合并为定点数, 小数位数取较大者, 与浮点合并为浮点,
+    // 日期与时间戳合并为时间戳, 其余须类型相同
+    pub fn common_type(&self, other: &DataType) -> Option<DataType> {
+        Some(match (self, other) {
+            (a, b) if a == b => a.clone(),
+            (DataType::Integer | DataType::Decimal { .. }, DataType::Integer | DataType::Decimal { .. }) => {
+                let scale = |data_type: &DataType| match data_type {
+                    DataType::Decimal { scale, .. } => *scale,
+                    _ => 0,
+                };
+
+                DataType::Decimal { precision: Decimal::MAX_PRECISION, scale: scale(self).max(scale(other)) }
+            }
+            (DataType::Integer | DataType::Float | DataType::Decimal { .. }, DataType::Integer | DataType::Float | DataType::Decimal { .. }) => DataType::Float,
+            (DataType::Date | DataType::Timestamp, DataType::Date | DataType::Timestamp) => DataType::Timestamp,
+            _ => return None,
+        })
+    }
+}
+
+// 字符串的比较规则, BINARY 区分大小写, NOCASE 比较前转为小写
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Collation {
+    #[default]
+    Binary,
+    NoCase,
+}
+
+impl Collation {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "BINARY" => Some(Self::Binary),
+            "NOCASE" => Some(Self::NoCase),
+            _ => None,
+        }
+    }
+
+    // 按规则转换参与比较的字符串, 其余值不变
+    pub fn normalize(self, value: Value) -> Value {
+        match (self, value) {
+            (Collation::NoCase, Value::String(s)) => Value::String(s.to_lowercase()),
+            (_, value) => value,
+        }
+    }
+}
+
+impl Display for Collation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Collation::Binary => write!(f, "BINARY"),
+            Collation::NoCase => write!(f, "NOCASE"),
+        }
+    }
+}
+
+// 浮点按规范化后的位比较与哈希, 以满足 Eq/Hash: 0.0 与 -0.0 相等, 所有 NaN 相等且等于自身
+// 整数与浮点不相等, 数值语义上的比较使用 compare
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Value {
     Null,
     Boolean(bool),
     Integer(i64),
-    Float(f64),
+    Float(#[cfg_attr(feature = "serde", serde(with = "float_serde"))] f64),
     String(String),
+    Decimal(Decimal),
+    Date(Date),
+    Timestamp(Timestamp),
+}
+
+impl Value {
+    // Null 没有类型
+    pub fn data_type(&self) -> Option<DataType> {
+        match self {
+            Value::Null => None,
+            Value::Boolean(_) => Some(DataType::Boolean),
+            Value::Integer(_) => Some(DataType::Integer),
+            Value::Float(_) => Some(DataType::Float),
+            Value::String(_) => Some(DataType::String),
+            // 值本身不记录精度, 取最大精度
+            Value::Decimal(v) => Some(DataType::Decimal { precision: Decimal::MAX_PRECISION, scale: v.scale }),
+            Value::Date(_) => Some(DataType::Date),
+            Value::Timestamp(_) => Some(DataType::Timestamp),
+        }
+    }
+
+    // 转换为列类型: 整数提升为浮点, 数值舍入为定点数, 字符串解析为日期和时间戳, Null 原样返回
+    // 布尔与数值之间不互相转换
+    pub fn coerce_to(self, data_type: &DataType) -> Result<Value> {
+        Ok(match (self, data_type) {
+            (Value::Null, _) => Value::Null,
+            (Value::Integer(v), DataType::Float) => Value::Float(v as f64),
+            (v @ (Value::Integer(_) | Value::Float(_) | Value::Decimal(_)), DataType::Decimal { precision, scale }) => {
+                Value::Decimal(Decimal::try_from(v)?.rescale(*precision, *scale)?)
+            }
+            (Value::String(v), DataType::Date) => Value::Date(v.parse()?),
+            (Value::String(v), DataType::Timestamp) => Value::Timestamp(v.parse()?),
+            (Value::Date(v), DataType::Timestamp) => Value::Timestamp(v.into()),
+            (v, data_type) if v.data_type().as_ref() == Some(data_type) => v,
+            (v, data_type) => bail!("Type mismatch: expected {:?}, got {:?}", data_type, v),
+        })
+    }
+
+    // 显式类型转换, 比 coerce_to 宽松: 字符串解析为任意类型, 任意类型格式化为字符串,
+    // 浮点和定点数向零截断为整数, 数值与布尔互转, 时间戳截断为日期
+    pub fn cast_to(self, data_type: &DataType) -> Result<Value> {
+        let invalid = |v: &Value| anyhow!("Cannot cast {:?} to {}", v, data_type);
+
+        Ok(match (self, data_type) {
+            (Value::Null, _) => Value::Null,
+            (v, DataType::String) => Value::String(v.to_string()),
+            (Value::String(s), DataType::Integer) => Value::Integer(s.trim().parse().map_err(|_| invalid(&Value::String(s.clone())))?),
+            (Value::String(s), DataType::Float) => Value::Float(s.trim().parse().map_err(|_| invalid(&Value::String(s.clone())))?),
+            (Value::String(s), DataType::Boolean) => match s.trim().to_lowercase().as_str() {
+                "true" | "1" => Value::Boolean(true),
+                "false" | "0" => Value::Boolean(false),
+                _ => return Err(invalid(&Value::String(s))),
+            },
+            (Value::String(s), DataType::Decimal { precision, scale }) => {
+                let v = s.trim().parse::<Decimal>().map_err(|_| invalid(&Value::String(s.clone())))?;
+                Value::Decimal(v.rescale(*precision, *scale)?)
+            }
+            (Value::Float(v), DataType::Integer) => match v.trunc() {
+                // i64::MAX 转为浮点后进位为 2^63, 不在范围内
+                t if t >= i64::MIN as f64 && t < i64::MAX as f64 => Value::Integer(t as i64),
+                _ => return Err(invalid(&Value::Float(v))),
+            },
+            (Value::Decimal(v), DataType::Integer) => {
+                let t = 10i128.checked_pow(v.scale).map(|factor| v.mantissa / factor);
+                Value::Integer(t.and_then(|t| i64::try_from(t).ok()).ok_or_else(|| invalid(&Value::Decimal(v)))?)
+            }
+            (Value::Decimal(v), DataType::Float) => Value::Float(v.into()),
+            (Value::Integer(v), DataType::Boolean) => Value::Boolean(v != 0),
+            (Value::Float(v), DataType::Boolean) => Value::Boolean(v != 0.0),
+            (Value::Boolean(v), DataType::Integer) => Value::Integer(v as i64),
+            (Value::Boolean(v), DataType::Float) => Value::Float(v as i64 as f64),
+            (Value::Timestamp(v), DataType::Date) => Value::Date(v.date()),
+            // 其余与隐式转换相同
+            (v @ (Value::Integer(_) | Value::Float(_) | Value::Decimal(_)), DataType::Decimal { .. })
+            | (v @ Value::Integer(_), DataType::Float)
+            | (v @ Value::String(_), DataType::Date | DataType::Timestamp)
+            | (v @ Value::Date(_), DataType::Timestamp) => v.coerce_to(data_type)?,
+            (v, data_type) if v.data_type().as_ref() == Some(data_type) => v,
+            (v, _) => return Err(invalid(&v)),
+        })
+    }
+
+    // 同类型比较, 整数与浮点互相比较时提升为浮点
+    // Null 小于任何值, NaN 大于任何数且等于 NaN, 其余不同类型返回错误
+    pub fn compare(&self, other: &Value) -> Result<Ordering> {
+        Ok(match (self, other) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Null, _) => Ordering::Less,
+            (_, Value::Null) => Ordering::Greater,
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::Integer(a), Value::Float(b)) => compare_float(*a as f64, *b),
+            (Value::Float(a), Value::Integer(b)) => compare_float(*a, *b as f64),
+            (Value::Float(a), Value::Float(b)) => compare_float(*a, *b),
+            (Value::Decimal(a), Value::Decimal(b)) => a.cmp(b),
+            (Value::Decimal(a), Value::Integer(b)) => a.cmp(&Decimal::from(*b)),
+            (Value::Integer(a), Value::Decimal(b)) => Decimal::from(*a).cmp(b),
+            (Value::Decimal(a), Value::Float(b)) => compare_float(f64::from(*a), *b),
+            (Value::Float(a), Value::Decimal(b)) => compare_float(*a, f64::from(*b)),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Date(a), Value::Date(b)) => a.cmp(b),
+            (Value::Timestamp(a), Value::Timestamp(b)) => a.cmp(b),
+            (Value::Date(a), Value::Timestamp(b)) => Timestamp::from(*a).cmp(b),
+            (Value::Timestamp(a), Value::Date(b)) => a.cmp(&Timestamp::from(*b)),
+            // 字符串按日期格式解析后比较
+            (Value::Date(a), Value::String(b)) => a.cmp(&b.parse()?),
+            (Value::String(a), Value::Date(b)) => a.parse::<Date>()?.cmp(b),
+            (Value::Timestamp(a), Value::String(b)) => a.cmp(&b.parse()?),
+            (Value::String(a), Value::Timestamp(b)) => a.parse::<Timestamp>()?.cmp(b),
+            (a, b) => bail!("Cannot compare {:?} with {:?}", a, b),
+        })
+    }
+
+    // 浮点保留 precision 位小数, 用于报表等须对齐的输出, 其余值与 to_string 相同
+    pub fn display_with(&self, precision: usize) -> String {
+        format!("{:.*}", precision, self)
+    }
 }
 
-impl From<Expression> for Value {
-    fn from(expr: Expression) -> Self {
-        match expr {
-            Expression::Const(Const::Null) => Value::Null,
-            Expression::Const(Const::Boolean(v)) => Value::Boolean(v),
-            Expression::Const(Const::Integer(v)) => Value::Integer(v),
-            Expression::Const(Const::Float(v)) => Value::Float(v),
-            Expression::Const(Const::String(v)) => Value::String(v),
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Integer(a), Value::Integer(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => float_bits(*a) == float_bits(*b),
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Decimal(a), Value::Decimal(b)) => a == b,
+            (Value::Date(a), Value::Date(b)) => a == b,
+            (Value::Timestamp(a), Value::Timestamp(b)) => a == b,
+            _ => false,
         }
     }
-}
\ No newline at end of file
+}
+
+impl Eq for Value {}
+
+// -0.0 归为 0.0, 不同载荷的 NaN 归为同一个
+fn float_bits(v: f64) -> u64 {
+    match v {
+        v if v.is_nan() => f64::NAN.to_bits(),
+        0.0 => 0,
+        v => v.to_bits(),
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Null => {}
+            Value::Boolean(v) => v.hash(state),
+            Value::Integer(v) => v.hash(state),
+            Value::Float(v) => float_bits(*v).hash(state),
+            Value::String(v) => v.hash(state),
+            Value::Decimal(v) => v.hash(state),
+            Value::Date(v) => v.hash(state),
+            Value::Timestamp(v) => v.hash(state),
+        }
+    }
+}
+
+fn compare_float(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b).unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+}
+
+impl From<Const> for Value {
+    fn from(c: Const) -> Self {
+        match c {
+            Const::Null => Value::Null,
+            Const::Boolean(v) => Value::Boolean(v),
+            Const::Integer(v) => Value::Integer(v),
+            Const::Float(v) => Value::Float(v),
+            Const::String(v) => Value::String(v),
+            Const::Decimal(v) => Value::Decimal(v),
+            Const::Date(v) => Value::Date(v),
+            Const::Timestamp(v) => Value::Timestamp(v),
+        }
+    }
+}
+
+impl From<Value> for Const {
+    fn from(v: Value) -> Self {
+        match v {
+            Value::Null => Const::Null,
+            Value::Boolean(v) => Const::Boolean(v),
+            Value::Integer(v) => Const::Integer(v),
+            Value::Float(v) => Const::Float(v),
+            Value::String(v) => Const::String(v),
+            Value::Decimal(v) => Const::Decimal(v),
+            Value::Date(v) => Const::Date(v),
+            Value::Timestamp(v) => Const::Timestamp(v),
+        }
+    }
+}
+
+// 只接受常量表达式
+impl TryFrom<Expression> for Value {
+    type Error = Error;
+
+    fn try_from(expr: Expression) -> Result<Self> {
+        Ok(match expr {
+            Expression::Const(c) => c.into(),
+            // -1 -2.5
+            Expression::UnaryOp { op: UnaryOperator::Negate, expr } => match Value::try_from(*expr)? {
+                Value::Integer(v) => Value::Integer(-v),
+                Value::Float(v) => Value::Float(-v),
+                Value::Decimal(v) => Value::Decimal(-v),
+                v => bail!("Cannot negate value: {:?}", v),
+            },
+            expr => bail!("Not a constant expression: {:?}", expr),
+        })
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Boolean(v) => write!(f, "{}", v),
+            Value::Integer(v) => write!(f, "{}", v),
+            // 指定精度时保留固定的小数位数, {:.2} -> 2.30
+            // 否则 Debug 输出最短的可还原表示, 且整数值保留 .0
+            Value::Float(v) => match f.precision() {
+                Some(precision) => write!(f, "{:.*}", precision, v),
+                None => write!(f, "{:?}", v),
+            },
+            Value::String(v) => write!(f, "{}", v),
+            Value::Decimal(v) => write!(f, "{}", v),
+            Value::Date(v) => write!(f, "{}", v),
+            Value::Timestamp(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+// 定点数, 值为 mantissa * 10^-scale, 比较按数值, 相等与哈希按表示: 1.5 与 1.50 不等
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Decimal {
+    pub mantissa: i128,
+    pub scale: u32,
+}
+
+impl Decimal {
+    // i128 可容纳的十进制位数
+    pub const MAX_PRECISION: u32 = 38;
+
+    // 按 scale 四舍五入 (远离零), 整数部分超出 precision - scale 位时返回错误
+    pub fn rescale(self, precision: u32, scale: u32) -> Result<Decimal> {
+        let overflow = || anyhow!("Value {} out of range for DECIMAL({}, {})", self, precision, scale);
+
+        let mantissa = if scale >= self.scale {
+            10i128.checked_pow(scale - self.scale)
+                .and_then(|factor| self.mantissa.checked_mul(factor))
+                .ok_or_else(overflow)?
+        } else {
+            let factor = 10i128.pow((self.scale - scale).min(Self::MAX_PRECISION));
+            let (quotient, remainder) = (self.mantissa / factor, self.mantissa % factor);
+            match remainder.unsigned_abs() * 2 >= factor.unsigned_abs() {
+                true => quotient + remainder.signum(),
+                false => quotient,
+            }
+        };
+
+        if mantissa.unsigned_abs() >= 10u128.pow(precision) {
+            return Err(overflow());
+        }
+
+        Ok(Decimal { mantissa, scale })
+    }
+}
+
+// 整数与小数按数值比较, 对齐小数位溢出时退化为浮点比较
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        let align = |d: &Decimal| 10i128.checked_pow(scale - d.scale).and_then(|factor| d.mantissa.checked_mul(factor));
+
+        match (align(self), align(other)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => compare_float(f64::from(*self), f64::from(*other)),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::ops::Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal { mantissa: -self.mantissa, scale: self.scale }
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(v: i64) -> Self {
+        Decimal { mantissa: v as i128, scale: 0 }
+    }
+}
+
+impl From<Decimal> for f64 {
+    fn from(v: Decimal) -> Self {
+        v.mantissa as f64 / 10f64.powi(v.scale as i32)
+    }
+}
+
+// 整数、浮点及定点数可转换为定点数
+impl TryFrom<Value> for Decimal {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Self> {
+        match v {
+            Value::Integer(v) => Ok(v.into()),
+            Value::Float(v) => v.try_into(),
+            Value::Decimal(v) => Ok(v),
+            v => bail!("Cannot convert {:?} to DECIMAL", v),
+        }
+    }
+}
+
+// 浮点按最短的可还原十进制表示转换, 1.1 -> 1.1 而非 1.100000000000000088...
+impl TryFrom<f64> for Decimal {
+    type Error = Error;
+
+    fn try_from(v: f64) -> Result<Self> {
+        if !v.is_finite() {
+            bail!("Cannot convert {} to DECIMAL", v);
+        }
+
+        v.to_string().parse()
+    }
+}
+
+// 12.34 -12 .5
+impl FromStr for Decimal {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid DECIMAL: {}", s);
+
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        if (int.is_empty() && frac.is_empty()) || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let mut mantissa = 0i128;
+        for c in int.chars().chain(frac.chars()) {
+            mantissa = mantissa.checked_mul(10)
+                .and_then(|m| m.checked_add(c.to_digit(10)? as i128))
+                .ok_or_else(invalid)?;
+        }
+
+        Ok(Decimal {
+            mantissa: if negative { -mantissa } else { mantissa },
+            scale: frac.len() as u32,
+        })
+    }
+}
+
+// 保留 scale 位小数: 1.50 -0.05
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let digits = format!("{:0>width$}", self.mantissa.unsigned_abs(), width = self.scale as usize + 1);
+        let (int, frac) = digits.split_at(digits.len() - self.scale as usize);
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+
+        match frac.is_empty() {
+            true => write!(f, "{}{}", sign, int),
+            false => write!(f, "{}{}.{}", sign, int, frac),
+        }
+    }
+}
+
+// JSON 无法表示 NaN 与无穷, 可读格式中以字符串保存, 其余浮点原样保存
+#[cfg(feature = "serde")]
+pub(crate) mod float_serde {
+    use std::fmt::Formatter;
+    use serde::de::{Error, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        match v {
+            v if !serializer.is_human_readable() || v.is_finite() => v.serialize(serializer),
+            v if v.is_nan() => serializer.serialize_str("NaN"),
+            v if *v > 0.0 => serializer.serialize_str("inf"),
+            _ => serializer.serialize_str("-inf"),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        if !deserializer.is_human_readable() {
+            return f64::deserialize(deserializer);
+        }
+
+        struct FloatVisitor;
+
+        impl Visitor<'_> for FloatVisitor {
+            type Value = f64;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                write!(f, "a float, NaN, inf or -inf")
+            }
+
+            fn visit_f64<E: Error>(self, v: f64) -> Result<f64, E> {
+                Ok(v)
+            }
+
+            fn visit_i64<E: Error>(self, v: i64) -> Result<f64, E> {
+                Ok(v as f64)
+            }
+
+            fn visit_u64<E: Error>(self, v: u64) -> Result<f64, E> {
+                Ok(v as f64)
+            }
+
+            fn visit_str<E: Error>(self, v: &str) -> Result<f64, E> {
+                match v {
+                    "NaN" => Ok(f64::NAN),
+                    "inf" => Ok(f64::INFINITY),
+                    "-inf" => Ok(f64::NEG_INFINITY),
+                    v => Err(E::custom(format!("invalid float: {}", v))),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(FloatVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_value_display() {
+        assert_eq!(Value::Null.to_string(), "NULL");
+        assert_eq!(Value::Boolean(true).to_string(), "true");
+        assert_eq!(Value::Boolean(false).to_string(), "false");
+        assert_eq!(Value::Integer(-42).to_string(), "-42");
+        assert_eq!(Value::Float(2.3).to_string(), "2.3");
+        assert_eq!(Value::Float(0.1 + 0.2).to_string(), "0.30000000000000004");
+        assert_eq!(Value::Float(1.0).to_string(), "1.0");
+        assert_eq!(Value::String("abc".to_string()).to_string(), "abc");
+    }
+
+    #[test]
+    fn test_value_display_with() {
+        assert_eq!(Value::Float(2.3).display_with(2), "2.30");
+        assert_eq!(Value::Float(1.0).display_with(0), "1");
+        assert_eq!(Value::Float(-0.125).display_with(2), "-0.12");
+        assert_eq!(Value::Float(1234.5678).display_with(3), "1234.568");
+        assert_eq!(format!("{:.1}", Value::Float(0.1 + 0.2)), "0.3");
+        assert_eq!(Value::Float(f64::INFINITY).display_with(2), "inf");
+
+        // 只影响浮点
+        assert_eq!(Value::Integer(7).display_with(2), "7");
+        assert_eq!(Value::String("abcdef".to_string()).display_with(2), "abcdef");
+        assert_eq!(Value::Null.display_with(2), "NULL");
+    }
+
+    #[test]
+    fn test_value_eq_hash() {
+        let values = HashSet::from([
+            Value::Null,
+            Value::Integer(1),
+            Value::Float(1.0),
+            Value::Float(f64::NAN),
+            Value::Float(0.0),
+            Value::Float(-0.0),
+            Value::String("1".to_string()),
+        ]);
+        assert_eq!(values.len(), 6);
+
+        assert_eq!(Value::Float(f64::NAN), Value::Float(f64::NAN));
+        assert_eq!(Value::Float(f64::NAN), Value::Float(-f64::NAN));
+        assert_eq!(Value::Float(0.0), Value::Float(-0.0));
+        assert_ne!(Value::Integer(1), Value::Float(1.0));
+        assert_ne!(Value::Float(1.0), Value::Float(1.0 + f64::EPSILON));
+        assert!(values.contains(&Value::Float(f64::from_bits(f64::NAN.to_bits() | 1))));
+        assert!(values.contains(&Value::Null));
+
+        // 相等的值哈希相同
+        let hash = |v: &Value| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            v.hash(&mut hasher);
+            hasher.finish()
+        };
+        for (a, b) in [
+            (Value::Float(0.0), Value::Float(-0.0)),
+            (Value::Float(f64::NAN), Value::Float(-f64::NAN)),
+            (Value::String("a".to_string()), Value::String("a".to_string())),
+            (Value::Integer(-1), Value::Integer(-1)),
+        ] {
+            assert_eq!(a, b);
+            assert_eq!(hash(&a), hash(&b));
+        }
+
+        let deduped = HashSet::<Value>::from_iter([Value::Integer(1), Value::Float(0.0), Value::Integer(1), Value::Float(-0.0), Value::Null, Value::Null]);
+        assert_eq!(deduped, HashSet::from([Value::Integer(1), Value::Float(0.0), Value::Null]));
+    }
+
+    #[test]
+    fn test_value_compare() -> Result<()> {
+        assert_eq!(Value::Integer(1).compare(&Value::Integer(2))?, Ordering::Less);
+        assert_eq!(Value::Integer(2).compare(&Value::Integer(2))?, Ordering::Equal);
+        assert_eq!(Value::Float(2.5).compare(&Value::Float(1.5))?, Ordering::Greater);
+
+        assert_eq!(Value::Integer(2).compare(&Value::Float(2.0))?, Ordering::Equal);
+        assert_eq!(Value::Integer(2).compare(&Value::Float(2.5))?, Ordering::Less);
+        assert_eq!(Value::Float(3.5).compare(&Value::Integer(3))?, Ordering::Greater);
+
+        assert_eq!(Value::String("abc".to_string()).compare(&Value::String("abd".to_string()))?, Ordering::Less);
+        assert_eq!(Value::String("b".to_string()).compare(&Value::String("abc".to_string()))?, Ordering::Greater);
+        assert_eq!(Value::Boolean(false).compare(&Value::Boolean(true))?, Ordering::Less);
+
+        assert_eq!(Value::Null.compare(&Value::Null)?, Ordering::Equal);
+        assert_eq!(Value::Null.compare(&Value::Integer(i64::MIN))?, Ordering::Less);
+        assert_eq!(Value::String("".to_string()).compare(&Value::Null)?, Ordering::Greater);
+
+        assert_eq!(Value::Float(f64::NAN).compare(&Value::Float(f64::NAN))?, Ordering::Equal);
+        assert_eq!(Value::Float(f64::NAN).compare(&Value::Float(f64::INFINITY))?, Ordering::Greater);
+        assert_eq!(Value::Integer(1).compare(&Value::Float(f64::NAN))?, Ordering::Less);
+        assert_eq!(Value::Float(-0.0).compare(&Value::Float(0.0))?, Ordering::Equal);
+
+        assert_eq!(Value::Integer(1).compare(&Value::String("1".to_string())).unwrap_err().to_string(), r#"Cannot compare Integer(1) with String("1")"#);
+        assert!(Value::Boolean(true).compare(&Value::Integer(1)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_value_coerce_to() -> Result<()> {
+        assert_eq!(Value::Integer(1).coerce_to(&DataType::Float)?, Value::Float(1.0));
+        assert_eq!(Value::Integer(1).coerce_to(&DataType::Decimal { precision: 5, scale: 2 })?, Value::Decimal("1.00".parse()?));
+        assert_eq!(Value::Float(1.005).coerce_to(&DataType::Decimal { precision: 5, scale: 2 })?, Value::Decimal("1.01".parse()?));
+        assert_eq!(Value::Boolean(true).coerce_to(&DataType::Boolean)?, Value::Boolean(true));
+        assert_eq!(Value::Null.coerce_to(&DataType::Integer)?, Value::Null);
+
+        assert_eq!(Value::Boolean(true).coerce_to(&DataType::Integer).unwrap_err().to_string(), "Type mismatch: expected Integer, got Boolean(true)");
+        assert_eq!(Value::Integer(1).coerce_to(&DataType::Boolean).unwrap_err().to_string(), "Type mismatch: expected Boolean, got Integer(1)");
+        assert_eq!(Value::Float(1.5).coerce_to(&DataType::Integer).unwrap_err().to_string(), "Type mismatch: expected Integer, got Float(1.5)");
+        assert_eq!(Value::String("1".to_string()).coerce_to(&DataType::Integer).unwrap_err().to_string(), r#"Type mismatch: expected Integer, got String("1")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_common_type() {
+        assert_eq!(DataType::Integer.common_type(&DataType::Float), Some(DataType::Float));
+        assert_eq!(DataType::Decimal { precision: 5, scale: 2 }.common_type(&DataType::Integer), Some(DataType::Decimal { precision: 38, scale: 2 }));
+        assert_eq!(DataType::Decimal { precision: 5, scale: 2 }.common_type(&DataType::Float), Some(DataType::Float));
+        assert_eq!(DataType::Date.common_type(&DataType::Timestamp), Some(DataType::Timestamp));
+        assert_eq!(DataType::Integer.common_type(&DataType::String), None);
+    }
+
+    #[test]
+    fn test_decimal() -> Result<()> {
+        let d = |s: &str| s.parse::<Decimal>();
+
+        assert_eq!(d("12.34")?, Decimal { mantissa: 1234, scale: 2 });
+        assert_eq!(d("-.5")?, Decimal { mantissa: -5, scale: 1 });
+        assert_eq!(d("7")?, Decimal { mantissa: 7, scale: 0 });
+        assert_eq!(d("1.2.3").unwrap_err().to_string(), "Invalid DECIMAL: 1.2.3");
+        assert_eq!(d("-").unwrap_err().to_string(), "Invalid DECIMAL: -");
+
+        for s in ["12.34", "-0.05", "0.50", "7", "-123"] {
+            assert_eq!(d(s)?.to_string(), s);
+        }
+
+        // 四舍五入远离零
+        assert_eq!(d("12.345")?.rescale(10, 2)?, d("12.35")?);
+        assert_eq!(d("-12.345")?.rescale(10, 2)?, d("-12.35")?);
+        assert_eq!(d("12.344")?.rescale(10, 2)?, d("12.34")?);
+        assert_eq!(d("1.5")?.rescale(5, 3)?, d("1.500")?);
+        assert_eq!(d("99.995")?.rescale(4, 2).unwrap_err().to_string(), "Value 99.995 out of range for DECIMAL(4, 2)");
+        assert_eq!(d("99.994")?.rescale(4, 2)?, d("99.99")?);
+
+        assert_eq!(Decimal::try_from(1.1)?, d("1.1")?);
+        assert!(Decimal::try_from(f64::NAN).is_err());
+
+        // 小数位数超出 i128 范围时不能转换
+        assert_eq!(Value::Decimal(d("-3.99")?).cast_to(&DataType::Integer)?, Value::Integer(-3));
+        assert_eq!(
+            Value::Decimal(Decimal { mantissa: 1, scale: 76 }).cast_to(&DataType::Integer).unwrap_err().to_string(),
+            "Cannot cast Decimal(Decimal { mantissa: 1, scale: 76 }) to INTEGER",
+        );
+
+        assert_eq!(Value::Decimal(d("1.5")?).compare(&Value::Decimal(d("1.50")?))?, Ordering::Equal);
+        assert_ne!(Value::Decimal(d("1.5")?), Value::Decimal(d("1.50")?));
+        assert_eq!(Value::Decimal(d("1.5")?).compare(&Value::Integer(2))?, Ordering::Less);
+        assert_eq!(Value::Float(1.6).compare(&Value::Decimal(d("1.5")?))?, Ordering::Greater);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_value_serde() -> Result<()> {
+        let values = vec![
+            Value::Null,
+            Value::Boolean(true),
+            Value::Integer(i64::MIN),
+            Value::Float(0.1),
+            Value::Float(-0.0),
+            Value::Float(f64::MAX),
+            Value::Float(f64::NAN),
+            Value::Float(f64::NEG_INFINITY),
+            Value::String("a\"b".to_string()),
+            Value::Decimal("-12.30".parse()?),
+            Value::Date("2024-01-02".parse()?),
+            Value::Timestamp("1969-12-31 23:59:59.5".parse()?),
+        ];
+
+        // 浮点按位还原
+        let json = serde_json::to_string(&values)?;
+        assert_eq!(serde_json::from_str::<Vec<Value>>(&json)?, values);
+        assert_eq!(serde_json::to_string(&Value::Float(f64::INFINITY))?, r#"{"Float":"inf"}"#);
+
+        let types = vec![DataType::Integer, DataType::Float, DataType::String, DataType::Boolean, DataType::Decimal { precision: 10, scale: 2 }, DataType::Date, DataType::Timestamp];
+        assert_eq!(serde_json::from_str::<Vec<DataType>>(&serde_json::to_string(&types)?)?, types);
+
+        Ok(())
+    }
+}
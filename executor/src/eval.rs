@@ -0,0 +1,686 @@
+use std::cmp::Ordering;
+use anyhow::{anyhow, bail, Result};
+use common::ast::{AggregateFunction, Expression, Operator, ScalarFunction};
+use common::ops::{eval_arithmetic, eval_binary, eval_compare, eval_logic, eval_unary};
+use common::schema::Table;
+use common::types::{Collation, Value};
+
+// 对一行数据求表达式的值, 列按表结构中的位置取值
+pub fn eval(expr: &Expression, row: &[Value], schema: &Table) -> Result<Value> {
+    Ok(match expr {
+        Expression::Const(c) => c.clone().into(),
+        Expression::Column(name) => {
+            let i = schema.resolve_column(name)?;
+
+            row.get(i).cloned().ok_or(anyhow!("Row has no value for column {}", name))?
+        }
+        // 左侧已能确定结果时不再求值右侧
+        Expression::BinaryOp { left, op: op @ (Operator::And | Operator::Or), right } => match (op, eval(left, row, schema)?) {
+            (Operator::And, Value::Boolean(false)) => Value::Boolean(false),
+            (Operator::Or, Value::Boolean(true)) => Value::Boolean(true),
+            (op, left) => eval_logic(op, left, eval(right, row, schema)?)?,
+        },
+        Expression::BinaryOp { left, op, right } => {
+            let collation = match op {
+                Operator::Equal | Operator::NotEqual | Operator::LessThan | Operator::LessThanOrEqual
+                | Operator::GreaterThan | Operator::GreaterThanOrEqual => collation(&[left, right], schema),
+                _ => Collation::Binary,
+            };
+            let left = collation.normalize(eval(left, row, schema)?);
+            let right = collation.normalize(eval(right, row, schema)?);
+
+            eval_binary(op, left, right)?
+        }
+        Expression::UnaryOp { op, expr } => eval_unary(op, eval(expr, row, schema)?)?,
+        // 与 = NULL 不同, 结果总是布尔值
+        Expression::IsNull { expr, negated } => Value::Boolean((eval(expr, row, schema)? == Value::Null) != *negated),
+        // 等价于 low <= expr and expr <= high
+        Expression::Between { expr, low, high, negated } => {
+            let collation = collation(&[expr, low, high], schema);
+            let value = collation.normalize(eval(expr, row, schema)?);
+            let result = eval_logic(
+                &Operator::And,
+                eval_compare(&Operator::GreaterThanOrEqual, value.clone(), collation.normalize(eval(low, row, schema)?))?,
+                eval_compare(&Operator::LessThanOrEqual, value, collation.normalize(eval(high, row, schema)?))?,
+            )?;
+
+            match result {
+                Value::Boolean(b) => Value::Boolean(b != *negated),
+                result => result,
+            }
+        }
+        Expression::Like { expr, pattern, negated } => {
+            let collation = collation(&[expr, pattern], schema);
+            match (collation.normalize(eval(expr, row, schema)?), collation.normalize(eval(pattern, row, schema)?)) {
+                (Value::Null, _) | (_, Value::Null) => Value::Null,
+                (Value::String(value), Value::String(pattern)) => Value::Boolean(like(&value, &pattern) != *negated),
+                (value, pattern) => bail!("Cannot apply LIKE to {:?} and {:?}", value, pattern),
+            }
+        }
+        Expression::Aggregate { func, .. } => bail!("Aggregate function {:?} is not allowed here", func),
+        Expression::Function { func, args } => eval_function(func, args, row, schema)?,
+        Expression::Default => bail!("DEFAULT is not allowed here"),
+        // 只求值命中的分支, 条件为 false 或 Null 时看下一个分支
+        Expression::Case { branches, else_ } => {
+            for (cond, result) in branches {
+                match eval(cond, row, schema)? {
+                    Value::Boolean(true) => return eval(result, row, schema),
+                    Value::Boolean(false) | Value::Null => {}
+                    value => bail!("CASE condition must be a boolean, got {:?}", value),
+                }
+            }
+
+            match else_ {
+                Some(else_) => eval(else_, row, schema)?,
+                None => Value::Null,
+            }
+        }
+        Expression::Cast { expr, target } => eval(expr, row, schema)?.cast_to(target)?,
+        Expression::Placeholder(n) => bail!("Placeholder ${} is not bound", n),
+        Expression::Subquery(query) => bail!("Subquery is not resolved: {}", query),
+        // 等价于 expr = item1 or expr = item2 ..., 无匹配且含 Null 时为 Null
+        // 两侧任一列为 NOCASE 时整体忽略大小写
+        Expression::InList { expr, list, negated } => {
+            let collation = collation(&std::iter::once(&**expr).chain(list).collect::<Vec<_>>(), schema);
+            let value = collation.normalize(eval(expr, row, schema)?);
+
+            let mut result = Value::Boolean(false);
+            for item in list {
+                let item = collation.normalize(eval(item, row, schema)?);
+                result = eval_logic(&Operator::Or, result, eval_compare(&Operator::Equal, value.clone(), item)?)?;
+            }
+
+            match result {
+                Value::Boolean(b) => Value::Boolean(b != *negated),
+                result => result,
+            }
+        }
+    })
+}
+
+// 参与比较的列中有 NOCASE 时忽略大小写, 否则按字节比较
+pub(crate) fn collation(exprs: &[&Expression], schema: &Table) -> Collation {
+    exprs.iter()
+        .filter_map(|expr| match expr {
+            Expression::Column(name) => schema.resolve_column(name).ok().map(|i| schema.columns[i].collation),
+            _ => None,
+        })
+        .find(|collation| *collation != Collation::Binary)
+        .unwrap_or_default()
+}
+
+// 谓词为 true 才保留, false 和 Null 均过滤掉
+pub(crate) fn matches(predicate: &Expression, row: &[Value], table: &Table) -> Result<bool> {
+    match eval(predicate, row, table)? {
+        Value::Boolean(v) => Ok(v),
+        Value::Null => Ok(false),
+        value => bail!("Filter predicate must be a boolean, got {:?}", value),
+    }
+}
+
+// 将表达式中的聚合函数替换为其在一组行上的结果
+pub fn resolve_aggregates(expr: &Expression, rows: &[Vec<Value>], schema: &Table) -> Result<Expression> {
+    let mut expr = expr.clone();
+    resolve_aggregates_mut(&mut expr, rows, schema)?;
+
+    Ok(expr)
+}
+
+fn resolve_aggregates_mut(expr: &mut Expression, rows: &[Vec<Value>], schema: &Table) -> Result<()> {
+    if let Expression::Aggregate { func, arg } = expr {
+        let value = eval_aggregate(func, arg.as_deref(), rows, schema)?;
+        *expr = Expression::Const(value.into());
+        return Ok(());
+    }
+
+    for child in expr.children_mut() {
+        resolve_aggregates_mut(child, rows, schema)?;
+    }
+
+    Ok(())
+}
+
+// COUNT(*) 计数所有行, 其余忽略 Null; 没有非空值时 SUM/AVG/MIN/MAX 为 Null
+fn eval_function(func: &ScalarFunction, args: &[Expression], row: &[Value], schema: &Table) -> Result<Value> {
+    match func {
+        // 遇到非 Null 即返回, 其后的参数不求值
+        ScalarFunction::Coalesce => {
+            for arg in args {
+                match eval(arg, row, schema)? {
+                    Value::Null => {}
+                    value => return Ok(value),
+                }
+            }
+
+            Ok(Value::Null)
+        }
+        ScalarFunction::NullIf => {
+            let [left, right] = args else { bail!("NULLIF expects 2 arguments, got {}", args.len()) };
+            let left = eval(left, row, schema)?;
+
+            Ok(match eval_compare(&Operator::Equal, left.clone(), eval(right, row, schema)?)? {
+                Value::Boolean(true) => Value::Null,
+                _ => left,
+            })
+        }
+    }
+}
+
+fn eval_aggregate(func: &AggregateFunction, arg: Option<&Expression>, rows: &[Vec<Value>], schema: &Table) -> Result<Value> {
+    let Some(arg) = arg else { return Ok(Value::Integer(rows.len() as i64)) };
+
+    let values = rows.iter()
+        .map(|row| eval(arg, row, schema))
+        .filter(|value| !matches!(value, Ok(Value::Null)))
+        .collect::<Result<Vec<_>>>()?;
+
+    if *func == AggregateFunction::Count {
+        return Ok(Value::Integer(values.len() as i64));
+    }
+
+    let count = values.len();
+    let mut values = values.into_iter();
+    let Some(first) = values.next() else { return Ok(Value::Null) };
+
+    Ok(match func {
+        AggregateFunction::Sum | AggregateFunction::Avg => {
+            let sum = values.try_fold(first, |sum, value| eval_arithmetic(&Operator::Add, sum, value))?;
+
+            match (func, sum) {
+                (AggregateFunction::Sum, sum @ (Value::Integer(_) | Value::Float(_) | Value::Decimal(_))) => sum,
+                (_, Value::Integer(v)) => Value::Float(v as f64 / count as f64),
+                (_, Value::Float(v)) => Value::Float(v / count as f64),
+                (_, Value::Decimal(v)) => Value::Float(f64::from(v) / count as f64),
+                (func, sum) => bail!("Cannot apply {:?} to {:?}", func, sum),
+            }
+        }
+        _ => values.try_fold(first, |acc, value| {
+            let ordering = value.compare(&acc)?;
+            let replace = match func {
+                AggregateFunction::Min => ordering == Ordering::Less,
+                _ => ordering == Ordering::Greater,
+            };
+
+            Ok::<_, anyhow::Error>(if replace { value } else { acc })
+        })?,
+    })
+}
+
+// % 匹配任意个字符, _ 匹配单个字符, \ 转义其后的字符
+fn like(value: &str, pattern: &str) -> bool {
+    enum Token {
+        Any,
+        One,
+        Char(char),
+    }
+
+    let mut tokens = vec![];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '%' => Token::Any,
+            '_' => Token::One,
+            '\\' => Token::Char(chars.next().unwrap_or('\\')),
+            c => Token::Char(c),
+        });
+    }
+
+    // matched[j]: value 的前 j 个字符能否匹配已处理的模式
+    let value = value.chars().collect::<Vec<_>>();
+    let mut matched = vec![false; value.len() + 1];
+    matched[0] = true;
+
+    for token in tokens {
+        let mut next = vec![false; value.len() + 1];
+
+        match token {
+            Token::Any => {
+                let mut any = false;
+                for j in 0..=value.len() {
+                    any |= matched[j];
+                    next[j] = any;
+                }
+            }
+            Token::One => next[1..].copy_from_slice(&matched[..value.len()]),
+            Token::Char(c) => for j in 1..=value.len() {
+                next[j] = matched[j - 1] && value[j - 1] == c;
+            },
+        }
+
+        matched = next;
+    }
+
+    matched[value.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::ast::Const;
+    use common::schema::{Column, ColumnDefault};
+    use common::types::DataType;
+
+    fn schema() -> Table {
+        let column = |name: &str, data_type| Column {
+            name: name.to_string(),
+            data_type,
+            max_length: None,
+            nullable: true,
+            default: Some(ColumnDefault::Const(Value::Null)),
+            primary_key: false,
+            unique: false,
+            check: None,
+            auto_increment: false,
+            collation: Collation::Binary,
+            comment: None,
+        };
+
+        Table {
+            name: "t".to_string(),
+            columns: vec![column("a", DataType::Integer), column("b", DataType::Float), column("c", DataType::String)],
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_eval_collation() -> Result<()> {
+        let mut schema = schema();
+        schema.columns.push(Column { name: "d".to_string(), collation: Collation::NoCase, ..schema.columns[2].clone() });
+        let row = [Value::Null, Value::Null, Value::String("Alice".to_string()), Value::String("Alice".to_string())];
+
+        // BINARY 列区分大小写, NOCASE 列比较时忽略大小写
+        let cases = [
+            ("c = 'alice'", false),
+            ("d = 'alice'", true),
+            ("'ALICE' = d", true),
+            ("d <> 'aLiCe'", false),
+            ("c < 'a'", true),
+            ("d < 'a'", false),
+            ("d > 'ALI'", true),
+            ("d in ('bob', 'ALICE')", true),
+            ("c in ('bob', 'ALICE')", false),
+            // 列表一侧的 NOCASE 列同样生效
+            ("'ALICE' in ('bob', d)", true),
+            ("'ALICE' in ('bob', c)", false),
+            ("'ALICE' not in (d)", false),
+            ("d like 'al%'", true),
+            ("c like 'al%'", false),
+            ("'ALICE' like d", true),
+            ("d not like '%CE'", false),
+            ("d between 'a' and 'b'", true),
+            ("c = d", true),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(eval(&parse(sql)?, &row, &schema)?, Value::Boolean(expected), "{}", sql);
+        }
+
+        // 比较之外的运算不受影响
+        assert_eq!(eval(&parse("d || '!'")?, &row, &schema)?, Value::String("Alice!".to_string()));
+
+        Ok(())
+    }
+
+    fn parse(sql: &str) -> Result<Expression> {
+        match parser::Parser::new(&format!(" select * from t where {};", sql)).parse()? {
+            common::ast::Statement::Select { filter: Some(expr), .. } => Ok(expr),
+            stmt => bail!("Unexpected statement: {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_eval_const() -> Result<()> {
+        let schema = schema();
+
+        assert_eq!(eval(&Const::Integer(1).into(), &[], &schema)?, Value::Integer(1));
+        assert_eq!(eval(&Const::String("x".to_string()).into(), &[], &schema)?, Value::String("x".to_string()));
+        assert_eq!(eval(&parse("1 + 2 * 3")?, &[], &schema)?, Value::Integer(7));
+        assert_eq!(eval(&parse("-(1 - 3.5)")?, &[], &schema)?, Value::Float(2.5));
+        assert_eq!(eval(&parse("7 % 3")?, &[], &schema)?, Value::Integer(1));
+        assert_eq!(eval(&parse("7 / 2")?, &[], &schema)?, Value::Integer(3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_column() -> Result<()> {
+        let schema = schema();
+        let row = [Value::Integer(2), Value::Float(1.5), Value::String("x".to_string())];
+
+        assert_eq!(eval(&parse("a")?, &row, &schema)?, Value::Integer(2));
+        assert_eq!(eval(&parse("c")?, &row, &schema)?, Value::String("x".to_string()));
+        assert_eq!(eval(&parse("a * b + 1")?, &row, &schema)?, Value::Float(4.0));
+        assert_eq!(eval(&parse("t.a + 1")?, &row, &schema)?, Value::Integer(3));
+        assert_eq!(eval(&parse("d")?, &row, &schema).unwrap_err().to_string(), "Column d does not exist in table t");
+        assert_eq!(eval(&parse("u.a")?, &row, &schema).unwrap_err().to_string(), "Column u.a does not exist in table t");
+
+        // 连接结果的列名已限定
+        let mut joined = schema.clone();
+        joined.name = "t, u".to_string();
+        for (col, name) in joined.columns.iter_mut().zip(["t.a", "t.b", "u.a"]) {
+            col.name = name.to_string();
+        }
+        assert_eq!(eval(&parse("b")?, &row, &joined)?, Value::Float(1.5));
+        assert_eq!(eval(&parse("u.a")?, &row, &joined)?, Value::String("x".to_string()));
+        assert_eq!(eval(&parse("a")?, &row, &joined).unwrap_err().to_string(), "Ambiguous column a");
+        assert_eq!(eval(&parse("u.b")?, &row, &joined).unwrap_err().to_string(), "Column u.b does not exist in table t, u");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_compare() -> Result<()> {
+        let schema = schema();
+        let row = [Value::Integer(2), Value::Float(2.0), Value::String("x".to_string())];
+
+        assert_eq!(eval(&parse("a = b")?, &row, &schema)?, Value::Boolean(true));
+        assert_eq!(eval(&parse("a + 1 > b")?, &row, &schema)?, Value::Boolean(true));
+        assert_eq!(eval(&parse("c <> 'x'")?, &row, &schema)?, Value::Boolean(false));
+        assert_eq!(eval(&parse("a >= 2 and not c < 'a'")?, &row, &schema)?, Value::Boolean(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_short_circuit() -> Result<()> {
+        let schema = schema();
+        let row = [Value::Integer(2), Value::Float(2.0), Value::String("x".to_string())];
+
+        // 右侧求值会出错, 左侧已确定结果时不求值
+        assert_eq!(eval(&parse("a = 2 or 1 / 0 = 1")?, &row, &schema)?, Value::Boolean(true));
+        assert_eq!(eval(&parse("a = 1 and missing = 1")?, &row, &schema)?, Value::Boolean(false));
+        assert_eq!(eval(&parse("true or (false and 1 % 0 = 0)")?, &row, &schema)?, Value::Boolean(true));
+
+        // 左侧为 false/Null 时仍须求值右侧
+        assert_eq!(eval(&parse("a = 1 or 1 / 0 = 1")?, &row, &schema).unwrap_err().to_string(), "Division by zero");
+        assert_eq!(eval(&parse("null and 1 / 0 = 1")?, &row, &schema).unwrap_err().to_string(), "Division by zero");
+        assert_eq!(eval(&parse("a = 2 and missing = 1")?, &row, &schema).unwrap_err().to_string(), "Column missing does not exist in table t");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_null_logic() -> Result<()> {
+        let schema = schema();
+
+        let cases = [
+            ("true and true", Value::Boolean(true)),
+            ("true and false", Value::Boolean(false)),
+            ("true and null", Value::Null),
+            ("false and true", Value::Boolean(false)),
+            ("false and false", Value::Boolean(false)),
+            ("false and null", Value::Boolean(false)),
+            ("null and true", Value::Null),
+            ("null and false", Value::Boolean(false)),
+            ("null and null", Value::Null),
+            ("true or true", Value::Boolean(true)),
+            ("true or false", Value::Boolean(true)),
+            ("true or null", Value::Boolean(true)),
+            ("false or true", Value::Boolean(true)),
+            ("false or false", Value::Boolean(false)),
+            ("false or null", Value::Null),
+            ("null or true", Value::Boolean(true)),
+            ("null or false", Value::Null),
+            ("null or null", Value::Null),
+            ("1 = 1", Value::Boolean(true)),
+            ("1 = 2", Value::Boolean(false)),
+            ("1 = null", Value::Null),
+            ("null = 1", Value::Null),
+            ("null = null", Value::Null),
+            ("null <> null", Value::Null),
+            ("not null", Value::Null),
+            ("null + 1", Value::Null),
+            ("null is null", Value::Boolean(true)),
+            ("null is not null", Value::Boolean(false)),
+            ("1 is null", Value::Boolean(false)),
+            ("1 + null is not null", Value::Boolean(false)),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(eval(&parse(sql)?, &[], &schema)?, expected, "{}", sql);
+        }
+
+        // 列值为 Null 时同样传播
+        let row = [Value::Null, Value::Float(1.0), Value::String("x".to_string())];
+        assert_eq!(eval(&parse("a > 1 or b = 1")?, &row, &schema)?, Value::Boolean(true));
+        assert_eq!(eval(&parse("a > 1 and b = 1")?, &row, &schema)?, Value::Null);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_between() -> Result<()> {
+        let schema = schema();
+
+        let cases = [
+            ("1 between 1 and 3", Value::Boolean(true)),
+            ("3 between 1 and 3", Value::Boolean(true)),
+            ("2.5 between 1 and 3", Value::Boolean(true)),
+            ("0 between 1 and 3", Value::Boolean(false)),
+            ("4 between 1 and 3", Value::Boolean(false)),
+            ("2 between 3 and 1", Value::Boolean(false)),
+            ("0 not between 1 and 3", Value::Boolean(true)),
+            ("3 not between 1 and 3", Value::Boolean(false)),
+            ("'b' between 'a' and 'c'", Value::Boolean(true)),
+            ("null between 1 and 3", Value::Null),
+            ("2 between null and 3", Value::Null),
+            ("4 between null and 3", Value::Boolean(false)),
+            ("4 not between null and 3", Value::Boolean(true)),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(eval(&parse(sql)?, &[], &schema)?, expected, "{}", sql);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_in_list() -> Result<()> {
+        let schema = schema();
+
+        let cases = [
+            ("1 in (1, 2, 3)", Value::Boolean(true)),
+            ("3 in (1, 2, 3)", Value::Boolean(true)),
+            ("4 in (1, 2, 3)", Value::Boolean(false)),
+            ("2 in (1.5, 2.0)", Value::Boolean(true)),
+            ("'b' in ('a', 'b')", Value::Boolean(true)),
+            ("4 not in (1, 2, 3)", Value::Boolean(true)),
+            ("1 not in (1, 2, 3)", Value::Boolean(false)),
+            ("null in (1, 2)", Value::Null),
+            ("null not in (1, 2)", Value::Null),
+            ("1 in (null, 1)", Value::Boolean(true)),
+            ("3 in (1, null)", Value::Null),
+            ("3 not in (1, null)", Value::Null),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(eval(&parse(sql)?, &[], &schema)?, expected, "{}", sql);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_like() -> Result<()> {
+        let schema = schema();
+
+        let cases = [
+            // 前缀
+            ("'abc' like 'a%'", Value::Boolean(true)),
+            ("'bac' like 'a%'", Value::Boolean(false)),
+            // 后缀
+            ("'abc' like '%c'", Value::Boolean(true)),
+            ("'abcd' like '%c'", Value::Boolean(false)),
+            // 包含
+            ("'abc' like '%b%'", Value::Boolean(true)),
+            ("'b' like '%b%'", Value::Boolean(true)),
+            ("'ac' like '%b%'", Value::Boolean(false)),
+            // 单个字符
+            ("'abc' like 'a_c'", Value::Boolean(true)),
+            ("'ac' like 'a_c'", Value::Boolean(false)),
+            ("'abbc' like 'a_c'", Value::Boolean(false)),
+            ("'abc' like '___'", Value::Boolean(true)),
+            // 转义
+            (r"'50%' like '50\%'", Value::Boolean(true)),
+            (r"'500' like '50\%'", Value::Boolean(false)),
+            (r"'a_b' like 'a\_b'", Value::Boolean(true)),
+            (r"'axb' like 'a\_b'", Value::Boolean(false)),
+            ("'abc' like 'abc'", Value::Boolean(true)),
+            ("'' like '%'", Value::Boolean(true)),
+            ("'abc' not like 'a%'", Value::Boolean(false)),
+            ("'abc' not like 'b%'", Value::Boolean(true)),
+            ("null like 'a%'", Value::Null),
+            ("'abc' like null", Value::Null),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(eval(&parse(sql)?, &[], &schema)?, expected, "{}", sql);
+        }
+
+        assert_eq!(eval(&parse("1 like '1'")?, &[], &schema).unwrap_err().to_string(), r#"Cannot apply LIKE to Integer(1) and String("1")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_case() -> Result<()> {
+        let schema = schema();
+        let sql = "case when a > 10 then 'big' when a > 0 then 'small' else 'none' end";
+
+        for (a, expected) in [(20, "big"), (5, "small"), (-1, "none")] {
+            assert_eq!(eval(&parse(sql)?, &[Value::Integer(a), Value::Null, Value::Null], &schema)?, Value::String(expected.to_string()));
+        }
+        // Null 条件不命中
+        assert_eq!(eval(&parse(sql)?, &[Value::Null, Value::Null, Value::Null], &schema)?, Value::String("none".to_string()));
+        assert_eq!(eval(&parse("case when a > 0 then 1 end")?, &[Value::Integer(0), Value::Null, Value::Null], &schema)?, Value::Null);
+
+        // 未命中的分支不求值
+        assert_eq!(eval(&parse("case when true then 1 else 1 / 0 end")?, &[], &schema)?, Value::Integer(1));
+        assert_eq!(eval(&parse("case when 1 then 1 end")?, &[], &schema).unwrap_err().to_string(), "CASE condition must be a boolean, got Integer(1)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_function() -> Result<()> {
+        let schema = schema();
+        let row = [Value::Null, Value::Float(2.0), Value::String("".to_string())];
+
+        assert_eq!(eval(&parse("coalesce(a, b, 3)")?, &row, &schema)?, Value::Float(2.0));
+        assert_eq!(eval(&parse("coalesce(a, null)")?, &row, &schema)?, Value::Null);
+        assert_eq!(eval(&parse("coalesce(null)")?, &row, &schema)?, Value::Null);
+        // 其后的参数不求值
+        assert_eq!(eval(&parse("coalesce(1, 1 / 0)")?, &row, &schema)?, Value::Integer(1));
+
+        assert_eq!(eval(&parse("nullif(c, '')")?, &row, &schema)?, Value::Null);
+        assert_eq!(eval(&parse("nullif(b, 1)")?, &row, &schema)?, Value::Float(2.0));
+        assert_eq!(eval(&parse("nullif(2, b)")?, &row, &schema)?, Value::Null);
+        assert_eq!(eval(&parse("nullif(1, a)")?, &row, &schema)?, Value::Integer(1));
+        assert_eq!(eval(&parse("nullif(a, 1)")?, &row, &schema)?, Value::Null);
+        assert_eq!(eval(&parse("coalesce(nullif(c, ''), 'empty')")?, &row, &schema)?, Value::String("empty".to_string()));
+
+        assert_eq!(eval(&parse("nullif(c, 1)")?, &row, &schema).unwrap_err().to_string(), r#"Cannot compare String("") with Integer(1)"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_cast() -> Result<()> {
+        let schema = schema();
+        let row = [Value::Integer(7), Value::Float(-2.9), Value::String(" 42 ".to_string())];
+        let cast = |sql: &str| eval(&parse(sql)?, &row, &schema);
+
+        assert_eq!(cast("cast(c as int)")?, Value::Integer(42));
+        assert_eq!(cast("cast(b as integer)")?, Value::Integer(-2));
+        assert_eq!(cast("cast(a as float) / 2")?, Value::Float(3.5));
+        assert_eq!(cast("cast(a as varchar) || '!'")?, Value::String("7!".to_string()));
+        assert_eq!(cast("cast(b as text)")?, Value::String("-2.9".to_string()));
+        assert_eq!(cast("cast(a as bool)")?, Value::Boolean(true));
+        assert_eq!(cast("cast(0 as boolean)")?, Value::Boolean(false));
+        assert_eq!(cast("cast(true as int)")?, Value::Integer(1));
+        assert_eq!(cast("cast('FALSE' as boolean)")?, Value::Boolean(false));
+        assert_eq!(cast("cast('1.255' as decimal(5, 2))")?.to_string(), "1.26");
+        assert_eq!(cast("cast(cast('-3.99' as decimal(5, 2)) as int)")?, Value::Integer(-3));
+        assert_eq!(cast("cast(cast('2024-01-02 03:04:05' as timestamp) as date)")?.to_string(), "2024-01-02");
+        assert_eq!(cast("cast(null as int)")?, Value::Null);
+
+        assert_eq!(cast("cast('abc' as int)").unwrap_err().to_string(), r#"Cannot cast String("abc") to INTEGER"#);
+        assert_eq!(cast("cast('yes' as boolean)").unwrap_err().to_string(), r#"Cannot cast String("yes") to BOOLEAN"#);
+        assert_eq!(cast("cast(10000000000000000000.0 as int)").unwrap_err().to_string(), "Cannot cast Float(1e19) to INTEGER");
+        assert_eq!(cast("cast(true as date)").unwrap_err().to_string(), "Cannot cast Boolean(true) to DATE");
+        assert_eq!(cast("cast('2023-02-30' as date)").unwrap_err().to_string(), "Invalid DATE: 2023-02-30");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_concat() -> Result<()> {
+        let schema = schema();
+        let row = [Value::Integer(1), Value::Float(2.0), Value::String("c".to_string())];
+
+        assert_eq!(eval(&parse("'a' || 'b'")?, &row, &schema)?, Value::String("ab".to_string()));
+        assert_eq!(eval(&parse("c || '-' || c")?, &row, &schema)?, Value::String("c-c".to_string()));
+        assert_eq!(eval(&parse("'' || ''")?, &row, &schema)?, Value::String("".to_string()));
+        assert_eq!(eval(&parse("'a' || null")?, &row, &schema)?, Value::Null);
+        assert_eq!(eval(&parse("c || 'x' = 'cx'")?, &row, &schema)?, Value::Boolean(true));
+
+        assert_eq!(eval(&parse("'a' || a")?, &row, &schema).unwrap_err().to_string(), r#"Cannot apply Concat to String("a") and Integer(1)"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_aggregate() -> Result<()> {
+        let schema = schema();
+        let rows = [
+            vec![Value::Integer(1), Value::Float(1.5), Value::String("b".to_string())],
+            vec![Value::Integer(2), Value::Null, Value::String("a".to_string())],
+            vec![Value::Null, Value::Float(2.5), Value::Null],
+            vec![Value::Integer(6), Value::Float(-1.0), Value::String("c".to_string())],
+        ];
+
+        let cases = [
+            ("count(*)", Value::Integer(4)),
+            ("count(a)", Value::Integer(3)),
+            ("sum(a)", Value::Integer(9)),
+            ("sum(b)", Value::Float(3.0)),
+            ("sum(a + b)", Value::Float(7.5)),
+            ("avg(a)", Value::Float(3.0)),
+            ("min(a)", Value::Integer(1)),
+            ("max(b)", Value::Float(2.5)),
+            ("min(c)", Value::String("a".to_string())),
+            ("max(c)", Value::String("c".to_string())),
+            ("count(*) * 2 + sum(a)", Value::Integer(17)),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(eval(&resolve_aggregates(&parse(sql)?, &rows, &schema)?, &[], &schema)?, expected, "{}", sql);
+        }
+
+        // 空集上 COUNT 为 0, 其余为 Null
+        for (sql, expected) in [("count(*)", Value::Integer(0)), ("count(a)", Value::Integer(0)), ("sum(a)", Value::Null), ("avg(a)", Value::Null), ("max(a)", Value::Null)] {
+            assert_eq!(eval(&resolve_aggregates(&parse(sql)?, &[], &schema)?, &[], &schema)?, expected, "{}", sql);
+        }
+
+        assert_eq!(resolve_aggregates(&parse("sum(c)")?, &rows, &schema).unwrap_err().to_string(), r#"Cannot apply Add to String("b") and String("a")"#);
+        assert_eq!(eval(&parse("count(*) > 1")?, &[], &schema).unwrap_err().to_string(), "Aggregate function Count is not allowed here");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eval_error() -> Result<()> {
+        let schema = schema();
+        let row = [Value::Integer(2), Value::Float(0.0), Value::String("x".to_string())];
+
+        assert_eq!(eval(&parse("a / 0")?, &row, &schema).unwrap_err().to_string(), "Division by zero");
+        assert_eq!(eval(&parse("a % b")?, &row, &schema).unwrap_err().to_string(), "Division by zero");
+        assert_eq!(eval(&parse("9223372036854775807 + a")?, &row, &schema).unwrap_err().to_string(), "Integer overflow");
+        assert_eq!(eval(&parse("a + c")?, &row, &schema).unwrap_err().to_string(), r#"Cannot apply Add to Integer(2) and String("x")"#);
+        assert_eq!(eval(&parse("a = c")?, &row, &schema).unwrap_err().to_string(), r#"Cannot compare Integer(2) with String("x")"#);
+        assert_eq!(eval(&parse("not a")?, &row, &schema).unwrap_err().to_string(), "Cannot apply Not to Integer(2)");
+        assert_eq!(eval(&parse("null and a")?, &row, &schema).unwrap_err().to_string(), "Cannot apply And to Null and Integer(2)");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,471 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use anyhow::{anyhow, bail, Result};
+use derive_new::new;
+use common::ast::{Expression, Operator, Statement};
+use common::schema::{system_table, Column, Table, COLUMNS_TABLE, TABLES_TABLE};
+use common::types::{Collation, DataType, Value};
+use planner::{AlterOperation, Node, Plan, Planner};
+use crate::eval::{eval, matches, resolve_aggregates};
+use crate::iterator::{BoxedRowIterator, CastIterator, ChainIterator, DistinctIterator, FilterIterator, LimitIterator, NestedLoopJoinIterator, ProjectionIterator, ScanIterator, SortIterator, VecIterator};
+use crate::{ExecResult, ResultSet};
+use crate::storage::Storage;
+
+#[derive(Debug, new)]
+pub struct Executor<'a> {
+    storage: &'a mut dyn Storage,
+}
+
+impl Executor<'_> {
+    // 修改数据及表结构的语句返回影响的行数, DDL 为 0, 带 RETURNING 的及其余语句返回结果集
+    pub fn execute(&mut self, plan: Plan) -> Result<ExecResult> {
+        let plan = self.resolve_subqueries(self.fill_defaults(plan)?)?;
+
+        Ok(match plan.0 {
+            Node::Create { schema, if_not_exists } => {
+                if !(if_not_exists && self.storage.get_table(&schema.name).is_some()) {
+                    self.storage.create_table(schema)?;
+                }
+
+                ExecResult::Affected(0)
+            }
+            Node::Insert { table_name, values, returning, .. } => {
+                let values = values.iter()
+                    .map(|row| row.iter().map(Planner::fold_constant).collect::<Result<Vec<_>>>())
+                    .collect::<Result<Vec<_>>>()?;
+                let (count, returned) = self.append(&table_name, values, returning)?;
+
+                returned.map_or(ExecResult::Affected(count), ExecResult::Rows)
+            }
+            Node::Update { table_name, assignments, filter, returning } => {
+                let table = self.get_table(&table_name)?;
+
+                let assignments = assignments.into_iter().map(|(column, expr)| {
+                    let i = table.columns.iter()
+                        .position(|col| col.name == column)
+                        .ok_or(anyhow!("Column {} does not exist in table {}", column, table_name))?;
+                    Ok((i, expr))
+                }).collect::<Result<Vec<_>>>()?;
+
+                // 赋值表达式基于更新前的行求值
+                let mut indexes = vec![];
+                let mut rows = vec![];
+                for (i, row) in self.storage.scan(&table_name)?.enumerate() {
+                    if filter.as_ref().map_or(Ok(true), |filter| matches(filter, row, table))? {
+                        let mut new_row = row.clone();
+                        for (col, expr) in &assignments {
+                            new_row[*col] = eval(expr, row, table)?;
+                        }
+                        indexes.push(i);
+                        rows.push(new_row);
+                    }
+                }
+                let rows = Planner::check_types(table, rows)?;
+                if let Some(i) = table.columns.iter().position(|col| col.auto_increment && !col.nullable) {
+                    if let Some(j) = rows.iter().position(|row| row[i] == Value::Null) {
+                        bail!("Column {} in row {} cannot be null", table.columns[i].name, j);
+                    }
+                }
+                Self::check_constraints(table, &rows)?;
+                let returned = returning.map(|exprs| Self::returning(table, exprs, &rows)).transpose()?;
+                // 与插入相同, 写入的更大序号使之后的序号从其后开始
+                let max_id = table.columns.iter().position(|col| col.auto_increment)
+                    .and_then(|i| rows.iter().filter_map(|row| match row[i] {
+                        Value::Integer(v) => Some(v),
+                        _ => None,
+                    }).max());
+
+                let count = rows.len();
+                self.storage.update(&table_name, indexes.into_iter().zip(rows).collect())?;
+                if let Some(max_id) = max_id {
+                    if max_id > self.storage.sequence(&table_name)? {
+                        self.storage.set_sequence(&table_name, max_id)?;
+                    }
+                }
+
+                returned.map_or(ExecResult::Affected(count), ExecResult::Rows)
+            }
+            Node::Delete { table_name, filter, returning } => {
+                let table = self.get_table(&table_name)?;
+
+                // 只在需要返回时保留被删除的行
+                let mut indexes = vec![];
+                let mut rows = vec![];
+                for (i, row) in self.storage.scan(&table_name)?.enumerate() {
+                    if filter.as_ref().map_or(Ok(true), |filter| matches(filter, row, table))? {
+                        indexes.push(i);
+                        if returning.is_some() {
+                            rows.push(row.clone());
+                        }
+                    }
+                }
+                let returned = returning.map(|exprs| Self::returning(table, exprs, &rows)).transpose()?;
+
+                self.storage.delete(&table_name, &indexes)?;
+
+                returned.map_or(ExecResult::Affected(indexes.len()), ExecResult::Rows)
+            }
+            Node::Drop { table_name, if_exists } => {
+                if !(if_exists && self.storage.get_table(&table_name).is_none()) {
+                    self.storage.drop_table(&table_name)?;
+                }
+
+                ExecResult::Affected(0)
+            }
+            Node::Truncate { table_name } => {
+                let count = self.storage.scan(&table_name)?.count();
+                self.storage.delete(&table_name, &(0..count).collect::<Vec<_>>())?;
+                self.storage.set_sequence(&table_name, 0)?;
+
+                ExecResult::Affected(count)
+            }
+            Node::AlterTable { table_name, op } => {
+                match op {
+                    AlterOperation::AddColumn(column) => self.storage.add_column(&table_name, column)?,
+                    AlterOperation::DropColumn(column) => self.storage.drop_column(&table_name, &column)?,
+                }
+
+                ExecResult::Affected(0)
+            }
+            node => ExecResult::Rows(self.execute_node(node)?),
+        })
+    }
+
+    // 不经解析和规划直接追加行, 每行按表的列顺序给出值, 校验与 INSERT 相同
+    // 任一行校验失败时不写入任何行, 返回写入的行数
+    pub fn insert_rows(&mut self, table_name: &str, rows: impl IntoIterator<Item=Vec<Value>>) -> Result<usize> {
+        self.append(table_name, rows.into_iter().collect(), None).map(|(count, _)| count)
+    }
+
+    // 校验类型及约束、分配自增序号后写入, 同时返回写入的行数及 RETURNING 的结果
+    fn append(&mut self, table_name: &str, values: Vec<Vec<Value>>, returning: Option<Vec<(Expression, String)>>) -> Result<(usize, Option<ResultSet>)> {
+        let table = self.get_table(table_name)?;
+
+        let mut values = Planner::check_types(table, values)?;
+        let sequence = Self::assign_ids(table, &mut values, self.storage.sequence(table_name)?)?;
+        Self::check_constraints(table, &values)?;
+        let returned = returning.map(|exprs| Self::returning(table, exprs, &values)).transpose()?;
+
+        let count = values.len();
+        self.storage.insert(table_name, values)?;
+        if let Some(sequence) = sequence {
+            self.storage.set_sequence(table_name, sequence)?;
+        }
+
+        Ok((count, returned))
+    }
+
+    // 未经目录规划的插入, 先按表的列顺序补全默认值, 其中的子查询随后一并求值
+    fn fill_defaults(&self, plan: Plan) -> Result<Plan> {
+        Ok(match plan.0 {
+            Node::Insert { table_name, columns, values, returning } if !columns.is_empty() => {
+                let values = Planner::fill_defaults(self.get_table(&table_name)?, &columns, values)?;
+                Plan(Node::Insert { table_name, columns: vec![], values, returning })
+            }
+            node => Plan(node),
+        })
+    }
+
+    fn resolve_subqueries(&self, plan: Plan) -> Result<Plan> {
+        plan.resolve_subqueries(&mut |query| self.eval_subquery(query))
+    }
+
+    // 标量子查询须恰好返回一列, 至多一行, 没有行时为 Null
+    fn eval_subquery(&self, query: &Statement) -> Result<Value> {
+        let plan = self.resolve_subqueries(Plan::build_with_catalog(query.clone(), &*self.storage)?)?;
+        let (table, mut iter) = self.build_iterator(plan.0)?;
+        if table.columns.len() != 1 {
+            bail!("Subquery must return exactly one column, got {}", table.columns.len());
+        }
+
+        let value = iter.next()?.map_or(Value::Null, |mut row| row.remove(0));
+        if iter.next()?.is_some() {
+            bail!("Subquery returned more than one row: {}", query);
+        }
+
+        Ok(value)
+    }
+
+    // 查询节点, 拉取根节点的迭代器直到结束
+    fn execute_node(&self, node: Node) -> Result<ResultSet> {
+        let (table, mut iter) = self.build_iterator(node)?;
+        let (columns, column_types) = table.columns.into_iter().map(|col| (col.name, col.data_type)).unzip();
+
+        let mut rows = vec![];
+        while let Some(row) = iter.next()? {
+            rows.push(row);
+        }
+
+        Ok(ResultSet { columns, column_types, rows })
+    }
+
+    // 构建节点的行迭代器, 子节点的行按需拉取
+    // 同时返回输出行的表结构, 供上层节点按列名求值
+    fn build_iterator(&self, node: Node) -> Result<(Table, BoxedRowIterator<'_>)> {
+        // 计算列的结果, 表名沿用来源表以便解析限定列名
+        let output = |table: &Table, exprs: &[(Expression, String)]| -> Result<Table> {
+            Ok(Table {
+                name: table.name.clone(),
+                columns: exprs.iter().map(|(expr, name)| Ok(Column::output(name.clone(), table.expression_type(expr)?))).collect::<Result<_>>()?,
+                comment: None,
+            })
+        };
+
+        Ok(match node {
+            Node::Scan { table_name } if self.storage.get_table(&table_name).is_none() && system_table(&table_name).is_some() => (
+                self.source_table(&table_name)?.clone(),
+                Box::new(VecIterator::new(self.system_rows(&table_name))),
+            ),
+            Node::Scan { table_name } => (
+                self.get_table(&table_name)?.clone(),
+                Box::new(ScanIterator::new(self.storage.scan(&table_name)?)),
+            ),
+            Node::NestedLoopJoin { left, right, predicate } => {
+                let (left_table, left) = self.build_iterator(*left)?;
+                let (right_table, right) = self.build_iterator(*right)?;
+                let table = Self::join_schema(&left_table, &right_table);
+
+                (table.clone(), Box::new(NestedLoopJoinIterator::new(left, right, predicate, Cow::Owned(table))?))
+            }
+            // 派生表的列名去掉限定, 以别名为表名
+            Node::Derived { source, alias } => {
+                let (table, source) = self.build_iterator(*source)?;
+                let columns = table.columns.into_iter().map(|col| match col.name.rsplit_once('.') {
+                    Some((_, name)) => Column { name: name.to_string(), ..col },
+                    None => col,
+                }).collect();
+
+                (Table { name: alias, columns , comment: None }, source)
+            }
+            Node::Filter { source, predicate } => {
+                let lookup = match &*source {
+                    Node::Scan { table_name } => self.index_lookup(table_name, &predicate)?,
+                    _ => None,
+                };
+                let (table, source) = match lookup {
+                    Some(lookup) => lookup,
+                    None => self.build_iterator(*source)?,
+                };
+
+                (table.clone(), Box::new(FilterIterator::new(source, predicate, Cow::Owned(table))))
+            }
+            Node::Projection { source, exprs } => {
+                let (table, source) = self.build_iterator(*source)?;
+                let output = output(&table, &exprs)?;
+                let exprs = exprs.into_iter().map(|(expr, _)| expr).collect();
+
+                (output, Box::new(ProjectionIterator::new(source, exprs, Cow::Owned(table))))
+            }
+            Node::Aggregate { source, group_keys, aggregates, having } => {
+                let (table, mut source) = self.build_iterator(*source)?;
+                let output = output(&table, &aggregates)?;
+                let table = &table;
+
+                // 按分组键的值分组, 保持首次出现的顺序; 无分组键时所有行为一组
+                let mut groups: Vec<Vec<Vec<Value>>> = vec![];
+                let mut indexes = HashMap::new();
+                if group_keys.is_empty() {
+                    groups.push(vec![]);
+                    indexes.insert(vec![], 0);
+                }
+                while let Some(row) = source.next()? {
+                    let key = group_keys.iter().map(|expr| eval(expr, &row, table)).collect::<Result<Vec<_>>>()?;
+
+                    let i = *indexes.entry(key).or_insert_with(|| {
+                        groups.push(vec![]);
+                        groups.len() - 1
+                    });
+                    groups[i].push(row);
+                }
+
+                let mut rows = vec![];
+                for group in groups {
+                    // 聚合之外只引用分组键, 组内任一行的取值相同
+                    let null_row = vec![Value::Null; table.columns.len()];
+                    let row = group.first().unwrap_or(&null_row);
+
+                    if let Some(having) = &having {
+                        if eval(&resolve_aggregates(having, &group, table)?, row, table)? != Value::Boolean(true) {
+                            continue;
+                        }
+                    }
+
+                    rows.push(aggregates.iter()
+                        .map(|(expr, _)| eval(&resolve_aggregates(expr, &group, table)?, row, table))
+                        .collect::<Result<_>>()?);
+                }
+
+                (output, Box::new(VecIterator::new(rows)))
+            }
+            Node::Union { left, right, all } => {
+                let (mut table, left) = self.build_iterator(*left)?;
+                let (right_table, right) = self.build_iterator(*right)?;
+
+                // 两侧的行先转换为合并后的类型再拼接, 去重时 1 与 1.0 视为相同
+                let types = table.columns.iter().zip(&right_table.columns)
+                    .map(|(a, b)| a.data_type.common_type(&b.data_type).unwrap_or_else(|| a.data_type.clone()))
+                    .collect::<Vec<_>>();
+                let casts = |table: &Table| table.columns.iter().zip(&types).map(|(col, data_type)| Some(data_type.clone()).filter(|t| *t != col.data_type)).collect();
+                let rows = Box::new(ChainIterator::new(
+                    Box::new(CastIterator::new(left, casts(&table))),
+                    Box::new(CastIterator::new(right, casts(&right_table))),
+                ));
+                for (col, data_type) in table.columns.iter_mut().zip(types) {
+                    col.data_type = data_type;
+                }
+
+                match all {
+                    true => (table, rows),
+                    false => (table, Box::new(DistinctIterator::new(rows))),
+                }
+            }
+            Node::Distinct { source } => {
+                let (table, source) = self.build_iterator(*source)?;
+
+                (table, Box::new(DistinctIterator::new(source)))
+            }
+            Node::Sort { source, keys } => {
+                let (table, source) = self.build_iterator(*source)?;
+                let rows = SortIterator::new(source, &keys, &table)?;
+
+                (table, Box::new(rows))
+            }
+            Node::Limit { source, limit, offset } => {
+                let (table, source) = self.build_iterator(*source)?;
+
+                (table, Box::new(LimitIterator::new(source, limit, offset)))
+            }
+            // 每行一个计划节点
+            Node::Explain { source } => (
+                Table { name: String::new(), columns: vec![Column::output("plan".to_string(), DataType::String)] , comment: None },
+                Box::new(VecIterator::new(source.to_string().lines().map(|line| vec![Value::String(line.to_string())]).collect())),
+            ),
+            node => bail!("Unsupported node: {:?}", node),
+        })
+    }
+
+    // RETURNING 对每行按表的列求值, 为空时返回整行
+    fn returning(table: &Table, exprs: Vec<(Expression, String)>, rows: &[Vec<Value>]) -> Result<ResultSet> {
+        if exprs.is_empty() {
+            let (columns, column_types) = table.columns.iter().map(|col| (col.name.clone(), col.data_type.clone())).unzip();
+            return Ok(ResultSet { columns, column_types, rows: rows.to_vec() });
+        }
+
+        let column_types = exprs.iter().map(|(expr, _)| table.expression_type(expr)).collect::<Result<_>>()?;
+        let rows = rows.iter()
+            .map(|row| exprs.iter().map(|(expr, _)| eval(expr, row, table)).collect::<Result<_>>())
+            .collect::<Result<_>>()?;
+
+        Ok(ResultSet { columns: exprs.into_iter().map(|(_, name)| name).collect(), column_types, rows })
+    }
+
+    // 自增列为 Null 的行依次取下一个序号, 显式给出的更大值使之后的序号从其后开始
+    // 返回分配后的最大序号, 表中无自增列时为 None
+    fn assign_ids(table: &Table, rows: &mut [Vec<Value>], mut sequence: i64) -> Result<Option<i64>> {
+        let Some(i) = table.columns.iter().position(|col| col.auto_increment) else { return Ok(None) };
+
+        for row in rows {
+            match row[i] {
+                Value::Null => {
+                    sequence = sequence.checked_add(1).ok_or(anyhow!("Sequence of table {} is exhausted", table.name))?;
+                    row[i] = Value::Integer(sequence);
+                }
+                Value::Integer(v) => sequence = sequence.max(v),
+                _ => {}
+            }
+        }
+
+        Ok(Some(sequence))
+    }
+
+    // CHECK 为 false 时拒绝写入, 为 Null 时视为满足
+    fn check_constraints(table: &Table, rows: &[Vec<Value>]) -> Result<()> {
+        for col in &table.columns {
+            let Some(check) = &col.check else { continue };
+
+            for (i, row) in rows.iter().enumerate() {
+                match eval(check, row, table)? {
+                    Value::Boolean(true) | Value::Null => {}
+                    Value::Boolean(false) => bail!("Row {} violates CHECK constraint on column {}: {}", i, col.name, check),
+                    value => bail!("CHECK constraint on column {} must be a boolean, got {:?}", col.name, value),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // 过滤条件中有 主键列 = 常量 的合取项且存储支持时按索引查找, 否则返回 None 照常全表扫描
+    // 命中的行仍须经过完整的过滤条件
+    fn index_lookup(&self, table_name: &str, predicate: &Expression) -> Result<Option<(Table, BoxedRowIterator<'_>)>> {
+        let Some(table) = self.storage.get_table(table_name) else { return Ok(None) };
+        let Some((column, value)) = Self::index_key(table, predicate) else { return Ok(None) };
+
+        Ok(self.storage.index_lookup(table_name, column, &value)?
+            .map(|rows| (table.clone(), Box::new(ScanIterator::new(rows)) as BoxedRowIterator<'_>)))
+    }
+
+    // 可按索引查找的合取项: 主键列 = 与列类型相同的非空常量, 且该列按字节比较
+    fn index_key<'t>(table: &'t Table, predicate: &Expression) -> Option<(&'t str, Value)> {
+        match predicate {
+            Expression::BinaryOp { left, op: Operator::And, right } => Self::index_key(table, left).or_else(|| Self::index_key(table, right)),
+            Expression::BinaryOp { left, op: Operator::Equal, right } => {
+                let ((Expression::Column(name), Expression::Const(c)) | (Expression::Const(c), Expression::Column(name))) = (&**left, &**right) else {
+                    return None;
+                };
+                let col = &table.columns[table.resolve_column(name).ok()?];
+                let value = Value::from(c.clone());
+
+                (col.primary_key && col.collation == Collation::Binary && value.data_type().as_ref() == Some(&col.data_type))
+                    .then_some((col.name.as_str(), value))
+            }
+            _ => None,
+        }
+    }
+
+    // 左右两侧的列依次拼接, 列名限定为 table.column 以区分同名列
+    fn join_schema(left: &Table, right: &Table) -> Table {
+        let qualify = |table: &Table| table.columns.iter().map(|col| match col.name.contains('.') {
+            // 已是连接结果
+            true => col.clone(),
+            false => Column { name: format!("{}.{}", table.name, col.name), ..col.clone() },
+        }).collect::<Vec<_>>();
+
+        Table {
+            name: format!("{}, {}", left.name, right.name),
+            columns: [qualify(left), qualify(right)].concat(),
+            comment: None,
+        }
+    }
+
+    fn get_table(&self, table_name: &str) -> Result<&Table> {
+        self.storage.get_table(table_name).ok_or(anyhow!("Table {} does not exist", table_name))
+    }
+
+    // 查询的来源表, 含系统表
+    fn source_table(&self, table_name: &str) -> Result<&Table> {
+        self.storage.get_table(table_name).or(system_table(table_name)).ok_or(anyhow!("Table {} does not exist", table_name))
+    }
+
+    // 系统表的行按表名排序, 列按定义顺序
+    fn system_rows(&self, table_name: &str) -> Vec<Vec<Value>> {
+        let mut tables = self.storage.tables();
+        tables.sort_by(|a, b| a.name.cmp(&b.name));
+        let comment = |comment: &Option<String>| comment.clone().map_or(Value::Null, Value::String);
+
+        match table_name {
+            TABLES_TABLE => tables.iter().map(|table| vec![Value::String(table.name.clone()), comment(&table.comment)]).collect(),
+            COLUMNS_TABLE => tables.iter().flat_map(|table| table.columns.iter().map(|col| vec![
+                Value::String(table.name.clone()),
+                Value::String(col.name.clone()),
+                Value::String(match (&col.data_type, col.max_length) {
+                    (DataType::String, Some(n)) => format!("VARCHAR({})", n),
+                    (data_type, _) => data_type.to_string(),
+                }),
+                Value::Boolean(col.nullable),
+                comment(&col.comment),
+            ])).collect(),
+            _ => vec![],
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use anyhow::Result;
+use common::ast::Expression;
+use common::schema::Table;
+use common::types::{DataType, Value};
+use crate::eval::{collation, eval, matches};
+use crate::storage::Rows;
+
+// 拉取式的行迭代器, 每次调用产出一行, 返回 None 表示结束
+pub trait RowIterator {
+    fn next(&mut self) -> Result<Option<Vec<Value>>>;
+}
+
+pub type BoxedRowIterator<'a> = Box<dyn RowIterator + 'a>;
+
+// 逐行读取存储中的表
+pub struct ScanIterator<'a> {
+    rows: Rows<'a>,
+}
+
+impl<'a> ScanIterator<'a> {
+    pub fn new(rows: Rows<'a>) -> Self {
+        Self { rows }
+    }
+}
+
+impl RowIterator for ScanIterator<'_> {
+    fn next(&mut self) -> Result<Option<Vec<Value>>> {
+        Ok(self.rows.next().cloned())
+    }
+}
+
+// 已物化的行, 用于聚合等须读完输入才能产出的节点
+pub struct VecIterator {
+    rows: std::vec::IntoIter<Vec<Value>>,
+}
+
+impl VecIterator {
+    pub fn new(rows: Vec<Vec<Value>>) -> Self {
+        Self { rows: rows.into_iter() }
+    }
+}
+
+impl RowIterator for VecIterator {
+    fn next(&mut self) -> Result<Option<Vec<Value>>> {
+        Ok(self.rows.next())
+    }
+}
+
+pub struct FilterIterator<'a> {
+    source: BoxedRowIterator<'a>,
+    predicate: Expression,
+    table: Cow<'a, Table>,
+}
+
+impl<'a> FilterIterator<'a> {
+    pub fn new(source: BoxedRowIterator<'a>, predicate: Expression, table: Cow<'a, Table>) -> Self {
+        Self { source, predicate, table }
+    }
+}
+
+impl RowIterator for FilterIterator<'_> {
+    fn next(&mut self) -> Result<Option<Vec<Value>>> {
+        while let Some(row) = self.source.next()? {
+            if matches(&self.predicate, &row, &self.table)? {
+                return Ok(Some(row));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+pub struct ProjectionIterator<'a> {
+    source: BoxedRowIterator<'a>,
+    exprs: Vec<Expression>,
+    table: Cow<'a, Table>,
+}
+
+impl<'a> ProjectionIterator<'a> {
+    pub fn new(source: BoxedRowIterator<'a>, exprs: Vec<Expression>, table: Cow<'a, Table>) -> Self {
+        Self { source, exprs, table }
+    }
+}
+
+impl RowIterator for ProjectionIterator<'_> {
+    fn next(&mut self) -> Result<Option<Vec<Value>>> {
+        let Some(row) = self.source.next()? else { return Ok(None) };
+
+        self.exprs.iter().map(|expr| eval(expr, &row, &self.table)).collect::<Result<_>>().map(Some)
+    }
+}
+
+// 按列转换为目标类型, 目标为 None 的列保持不变
+pub struct CastIterator<'a> {
+    source: BoxedRowIterator<'a>,
+    types: Vec<Option<DataType>>,
+}
+
+impl<'a> CastIterator<'a> {
+    pub fn new(source: BoxedRowIterator<'a>, types: Vec<Option<DataType>>) -> Self {
+        Self { source, types }
+    }
+}
+
+impl RowIterator for CastIterator<'_> {
+    fn next(&mut self) -> Result<Option<Vec<Value>>> {
+        let Some(row) = self.source.next()? else { return Ok(None) };
+
+        row.into_iter().zip(&self.types).map(|(value, data_type)| match data_type {
+            Some(data_type) => value.cast_to(data_type),
+            None => Ok(value),
+        }).collect::<Result<_>>().map(Some)
+    }
+}
+
+// 左侧每行与右侧每行拼接后求连接条件, 右侧的行预先读入
+pub struct NestedLoopJoinIterator<'a> {
+    left: BoxedRowIterator<'a>,
+    right: Vec<Vec<Value>>,
+    predicate: Expression,
+    // 左右两侧的列依次拼接
+    table: Cow<'a, Table>,
+    // 当前左侧行及下一个要拼接的右侧行
+    current: Option<(Vec<Value>, usize)>,
+}
+
+impl<'a> NestedLoopJoinIterator<'a> {
+    pub fn new(left: BoxedRowIterator<'a>, mut right: BoxedRowIterator<'a>, predicate: Expression, table: Cow<'a, Table>) -> Result<Self> {
+        let mut rows = vec![];
+        while let Some(row) = right.next()? {
+            rows.push(row);
+        }
+
+        Ok(Self { left, right: rows, predicate, table, current: None })
+    }
+}
+
+impl RowIterator for NestedLoopJoinIterator<'_> {
+    fn next(&mut self) -> Result<Option<Vec<Value>>> {
+        loop {
+            let (left, i) = match &mut self.current {
+                Some(current) => current,
+                None => match self.left.next()? {
+                    Some(row) => self.current.insert((row, 0)),
+                    None => return Ok(None),
+                },
+            };
+
+            while let Some(right) = self.right.get(*i) {
+                *i += 1;
+
+                let row = [left.as_slice(), right].concat();
+                if matches(&self.predicate, &row, &self.table)? {
+                    return Ok(Some(row));
+                }
+            }
+
+            self.current = None;
+        }
+    }
+}
+
+// 先取完左侧再取右侧
+pub struct ChainIterator<'a> {
+    left: BoxedRowIterator<'a>,
+    right: BoxedRowIterator<'a>,
+}
+
+impl<'a> ChainIterator<'a> {
+    pub fn new(left: BoxedRowIterator<'a>, right: BoxedRowIterator<'a>) -> Self {
+        Self { left, right }
+    }
+}
+
+impl RowIterator for ChainIterator<'_> {
+    fn next(&mut self) -> Result<Option<Vec<Value>>> {
+        match self.left.next()? {
+            Some(row) => Ok(Some(row)),
+            None => self.right.next(),
+        }
+    }
+}
+
+// 读完输入后按排序键排序, 键相同时保持输入顺序
+// 键值按所引用列的比较规则比较, Null 小于任何值: 升序时排在最前, 降序时排在最后
+pub struct SortIterator {
+    rows: std::vec::IntoIter<Vec<Value>>,
+}
+
+impl SortIterator {
+    pub fn new(mut source: BoxedRowIterator<'_>, keys: &[(Expression, bool)], table: &Table) -> Result<Self> {
+        let collations = keys.iter().map(|(expr, _)| collation(&[expr], table)).collect::<Vec<_>>();
+
+        let mut rows = vec![];
+        while let Some(row) = source.next()? {
+            let key = keys.iter().zip(&collations)
+                .map(|((expr, _), collation)| Ok(collation.normalize(eval(expr, &row, table)?)))
+                .collect::<Result<Vec<_>>>()?;
+            rows.push((key, row));
+        }
+
+        // 排序中途无法返回错误, 记下第一个
+        let mut error = None;
+        rows.sort_by(|(a, _), (b, _)| {
+            for ((a, b), (_, desc)) in a.iter().zip(b).zip(keys) {
+                match a.compare(b) {
+                    Ok(Ordering::Equal) => {}
+                    Ok(ordering) if *desc => return ordering.reverse(),
+                    Ok(ordering) => return ordering,
+                    Err(e) => {
+                        error.get_or_insert(e);
+                        return Ordering::Equal;
+                    }
+                }
+            }
+            Ordering::Equal
+        });
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        Ok(Self { rows: rows.into_iter().map(|(_, row)| row).collect::<Vec<_>>().into_iter() })
+    }
+}
+
+impl RowIterator for SortIterator {
+    fn next(&mut self) -> Result<Option<Vec<Value>>> {
+        Ok(self.rows.next())
+    }
+}
+
+// 保留首次出现的行
+pub struct DistinctIterator<'a> {
+    source: BoxedRowIterator<'a>,
+    seen: HashSet<Vec<Value>>,
+}
+
+impl<'a> DistinctIterator<'a> {
+    pub fn new(source: BoxedRowIterator<'a>) -> Self {
+        Self { source, seen: HashSet::new() }
+    }
+}
+
+impl RowIterator for DistinctIterator<'_> {
+    fn next(&mut self) -> Result<Option<Vec<Value>>> {
+        while let Some(row) = self.source.next()? {
+            if self.seen.insert(row.clone()) {
+                return Ok(Some(row));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+// 取够 limit 行后不再拉取输入
+pub struct LimitIterator<'a> {
+    source: BoxedRowIterator<'a>,
+    offset: usize,
+    remaining: usize,
+}
+
+impl<'a> LimitIterator<'a> {
+    pub fn new(source: BoxedRowIterator<'a>, limit: Option<u64>, offset: Option<u64>) -> Self {
+        Self {
+            source,
+            offset: offset.unwrap_or(0) as usize,
+            remaining: limit.map_or(usize::MAX, |limit| limit as usize),
+        }
+    }
+}
+
+impl RowIterator for LimitIterator<'_> {
+    fn next(&mut self) -> Result<Option<Vec<Value>>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        while self.offset > 0 {
+            if self.source.next()?.is_none() {
+                return Ok(None);
+            }
+            self.offset -= 1;
+        }
+
+        self.remaining -= 1;
+
+        self.source.next()
+    }
+}
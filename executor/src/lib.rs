@@ -0,0 +1,986 @@
+mod eval;
+mod executor;
+mod iterator;
+mod storage;
+
+use common::types::{DataType, Value};
+pub use crate::eval::eval;
+pub use crate::executor::Executor;
+pub use crate::iterator::RowIterator;
+pub use crate::storage::{FileStorage, MemoryStorage, Rows, Storage};
+
+// 执行结果
+#[derive(Debug, Default, PartialEq)]
+pub struct ResultSet {
+    pub columns: Vec<String>,
+    // 各列的类型, 与 columns 一一对应
+    pub column_types: Vec<DataType>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+// 语句的执行结果
+#[derive(Debug, PartialEq)]
+pub enum ExecResult {
+    Rows(ResultSet),
+    // INSERT/UPDATE/DELETE 影响的行数
+    Affected(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use parser::Parser;
+    use std::cell::Cell;
+    use common::schema::{Catalog, Column, Table};
+    use planner::Plan;
+
+    fn execute(storage: &mut MemoryStorage, sql: &str) -> Result<ExecResult> {
+        let plan = Plan::build_with_catalog(Parser::new(sql).parse()?, storage)?;
+        Executor::new(storage).execute(plan)
+    }
+
+    fn query(storage: &mut MemoryStorage, sql: &str) -> Result<ResultSet> {
+        match execute(storage, sql)? {
+            ExecResult::Rows(result) => Ok(result),
+            result => panic!("not a query result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_execute_create_insert_select() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table users (id int not null, name varchar null, score float default 0);")?;
+        execute(&mut storage, " insert into users values (1, 'a', 1.5), (2, null, 2);")?;
+        execute(&mut storage, " insert into users (name, id) values ('c', 3);")?;
+
+        assert_eq!(query(&mut storage, " select * from users;")?, ResultSet {
+            columns: vec!["id".to_string(), "name".to_string(), "score".to_string()],
+            column_types: vec![DataType::Integer, DataType::String, DataType::Float],
+            rows: vec![
+                vec![Value::Integer(1), Value::String("a".to_string()), Value::Float(1.5)],
+                vec![Value::Integer(2), Value::Null, Value::Float(2.0)],
+                vec![Value::Integer(3), Value::String("c".to_string()), Value::Float(0.0)],
+            ],
+        });
+
+        assert_eq!(query(&mut storage, " select name, id from users limit 2 offset 1;")?, ResultSet {
+            columns: vec!["name".to_string(), "id".to_string()],
+            column_types: vec![DataType::String, DataType::Integer],
+            rows: vec![
+                vec![Value::Null, Value::Integer(2)],
+                vec![Value::String("c".to_string()), Value::Integer(3)],
+            ],
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_affected() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        assert_eq!(execute(&mut storage, " create table t (a int, b int null);")?, ExecResult::Affected(0));
+        assert_eq!(execute(&mut storage, " insert into t values (1, 1), (2, null), (3, 3), (4, 4);")?, ExecResult::Affected(4));
+        assert_eq!(execute(&mut storage, " insert into t (a) values (5);")?, ExecResult::Affected(1));
+
+        // Null 参与比较的行不算匹配
+        assert_eq!(execute(&mut storage, " update t set b = b + 1 where b > 1;")?, ExecResult::Affected(2));
+        assert_eq!(execute(&mut storage, " delete from t where a >= 3 or b is null;")?, ExecResult::Affected(4));
+        assert_eq!(execute(&mut storage, " delete from t where a > 10;")?, ExecResult::Affected(0));
+        assert_eq!(query(&mut storage, " select * from t;")?.rows, vec![vec![Value::Integer(1), Value::Integer(1)]]);
+
+        assert!(matches!(execute(&mut storage, " explain delete from t;")?, ExecResult::Rows(_)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_update() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (id int, a int, b int null);")?;
+        execute(&mut storage, " insert into t values (1, 10, 100), (2, 20, 200), (3, 30, null);")?;
+
+        // 右侧的表达式取更新前的值, 可交换两列
+        assert_eq!(execute(&mut storage, " update t set a = b, b = a where id >= 2 and b is not null;")?, ExecResult::Affected(1));
+        assert_eq!(execute(&mut storage, " update t set a = a + id * 100 where id <> 2;")?, ExecResult::Affected(2));
+        assert_eq!(query(&mut storage, " select * from t;")?.rows, vec![
+            vec![Value::Integer(1), Value::Integer(110), Value::Integer(100)],
+            vec![Value::Integer(2), Value::Integer(200), Value::Integer(20)],
+            vec![Value::Integer(3), Value::Integer(330), Value::Null],
+        ]);
+
+        assert_eq!(execute(&mut storage, " update t set a = 1.5 where id = 1;").unwrap_err().to_string(), "Column a in row 0: Type mismatch: expected Integer, got Float(1.5)");
+        assert_eq!(execute(&mut storage, " update t set a = null;").unwrap_err().to_string(), "Column a in row 0 cannot be null");
+        assert_eq!(execute(&mut storage, " update t set a = c;").unwrap_err().to_string(), "Column c does not exist in table t");
+        assert_eq!(execute(&mut storage, " update t set a = 1 where c = 1;").unwrap_err().to_string(), "Column c does not exist in table t");
+        assert_eq!(execute(&mut storage, " update t set a = 1, a = 2;").unwrap_err().to_string(), "Column a assigned more than once at position 1");
+
+        // 只删除条件为真的行
+        assert_eq!(execute(&mut storage, " delete from t where b < 50 or b is null;")?, ExecResult::Affected(2));
+        assert_eq!(query(&mut storage, " select id from t;")?.rows, vec![vec![Value::Integer(1)]]);
+        assert_eq!(execute(&mut storage, " delete from t where c = 1;").unwrap_err().to_string(), "Column c does not exist in table t");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_alias() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (a int, b int);")?;
+        execute(&mut storage, " insert into t values (1, 10), (2, 20);")?;
+
+        assert_eq!(query(&mut storage, " select a as x, b + 1 as total, b, a * b from t;")?, ResultSet {
+            columns: vec!["x".to_string(), "total".to_string(), "b".to_string(), "expr3".to_string()],
+            column_types: vec![DataType::Integer, DataType::Integer, DataType::Integer, DataType::Integer],
+            rows: vec![
+                vec![Value::Integer(1), Value::Integer(11), Value::Integer(10), Value::Integer(10)],
+                vec![Value::Integer(2), Value::Integer(21), Value::Integer(20), Value::Integer(40)],
+            ],
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_aggregate() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (a int null, b float null);")?;
+        assert_eq!(query(&mut storage, " select count(*), sum(a) from t;")?.rows, vec![vec![Value::Integer(0), Value::Null]]);
+
+        execute(&mut storage, " insert into t values (1, 1.5), (2, null), (null, 2.5);")?;
+
+        assert_eq!(query(&mut storage, " select count(*) from t;")?, ResultSet {
+            columns: vec!["expr0".to_string()],
+            column_types: vec![DataType::Integer],
+            rows: vec![vec![Value::Integer(3)]],
+        });
+        assert_eq!(query(&mut storage, " select count(a) as n, avg(a), max(b) from t where a is not null;")?, ResultSet {
+            columns: vec!["n".to_string(), "expr1".to_string(), "expr2".to_string()],
+            column_types: vec![DataType::Integer, DataType::Float, DataType::Float],
+            rows: vec![vec![Value::Integer(2), Value::Float(1.5), Value::Float(1.5)]],
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_group_by() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (k varchar null, v int);")?;
+        execute(&mut storage, " insert into t values ('a', 1), ('b', 2), ('a', 3), (null, 4), ('b', 5), ('a', 6);")?;
+
+        assert_eq!(query(&mut storage, " select k, count(*) as n, sum(v) from t group by k;")?, ResultSet {
+            columns: vec!["k".to_string(), "n".to_string(), "expr2".to_string()],
+            column_types: vec![DataType::String, DataType::Integer, DataType::Integer],
+            rows: vec![
+                vec![Value::String("a".to_string()), Value::Integer(3), Value::Integer(10)],
+                vec![Value::String("b".to_string()), Value::Integer(2), Value::Integer(7)],
+                vec![Value::Null, Value::Integer(1), Value::Integer(4)],
+            ],
+        });
+
+        // 过滤后 b 先出现
+        assert_eq!(query(&mut storage, " select k from t where v > 1 group by k having count(*) >= 2;")?.rows, vec![
+            vec![Value::String("b".to_string())],
+            vec![Value::String("a".to_string())],
+        ]);
+
+        assert_eq!(query(&mut storage, " select k, count(*) from t where v > 10 group by k;")?.rows, Vec::<Vec<Value>>::new());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_distinct() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (a int null, b float);")?;
+        execute(&mut storage, " insert into t values (1, 1.5), (2, 1.5), (1, 1.5), (null, 2), (null, 2), (1, 2);")?;
+
+        assert_eq!(query(&mut storage, " select distinct a, b from t;")?.rows, vec![
+            vec![Value::Integer(1), Value::Float(1.5)],
+            vec![Value::Integer(2), Value::Float(1.5)],
+            vec![Value::Null, Value::Float(2.0)],
+            vec![Value::Integer(1), Value::Float(2.0)],
+        ]);
+
+        assert_eq!(query(&mut storage, " select distinct b from t limit 5;")?.rows, vec![vec![Value::Float(1.5)], vec![Value::Float(2.0)]]);
+        assert_eq!(query(&mut storage, " select distinct * from t where a = 1;")?.rows.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_sort() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (a int null, b varchar collate nocase);")?;
+        execute(&mut storage, " insert into t values (2, 'b'), (null, 'C'), (1, 'a'), (3, 'B'), (1, 'A');")?;
+
+        let ints = |values: &[Option<i64>]| values.iter().map(|v| v.map_or(Value::Null, Value::Integer)).collect::<Vec<_>>();
+        let strings = |values: &[&str]| values.iter().map(|v| Value::String(v.to_string())).collect::<Vec<_>>();
+
+        // 按聚合结果排序
+        assert_eq!(query(&mut storage, " select a, count(*) as n from t group by a order by n desc, a;")?.rows, vec![
+            ints(&[Some(1), Some(2)]),
+            ints(&[None, Some(1)]),
+            ints(&[Some(2), Some(1)]),
+            ints(&[Some(3), Some(1)]),
+        ]);
+
+        assert_eq!(query(&mut storage, " select * from t order by b + 1;").unwrap_err().to_string(), "Cannot apply Add to String(\"b\") and Integer(1)");
+
+        let mut column = |sql: &str| -> Result<Vec<Value>> { Ok(query(&mut storage, sql)?.rows.into_iter().map(|mut row| row.remove(0)).collect()) };
+
+        // Null 升序时在前, 降序时在后
+        assert_eq!(column(" select a from t order by a;")?, ints(&[None, Some(1), Some(1), Some(2), Some(3)]));
+        assert_eq!(column(" select a from t order by a desc;")?, ints(&[Some(3), Some(2), Some(1), Some(1), None]));
+        assert_eq!(column(" select a from t order by a desc limit 2;")?, ints(&[Some(3), Some(2)]));
+
+        // 按列的比较规则忽略大小写, 相等时保持原顺序
+        assert_eq!(column(" select b from t order by b;")?, strings(&["a", "A", "b", "B", "C"]));
+        assert_eq!(column(" select b from t order by a desc, b desc;")?, strings(&["B", "b", "a", "A", "C"]));
+
+        // 按未投影的列、别名及聚合表达式排序
+        assert_eq!(column(" select b from t where a is not null order by a, b;")?, strings(&["a", "A", "b", "B"]));
+        assert_eq!(column(" select a * 10 as x from t where a is not null order by x desc;")?, ints(&[Some(30), Some(20), Some(10), Some(10)]));
+        assert_eq!(column(" select a, count(*) from t group by a order by count(*) desc, a desc;")?, ints(&[Some(1), Some(3), Some(2), None]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_filter() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (a int null, b int null);")?;
+        execute(&mut storage, " insert into t values (1, 1), (2, null), (null, 3), (null, null);")?;
+
+        // Null 参与的比较结果为 Null, 行不会被保留
+        assert_eq!(query(&mut storage, " select a from t where b > 0;")?.rows, vec![vec![Value::Integer(1)], vec![Value::Null]]);
+        assert_eq!(query(&mut storage, " select a from t where not b > 0;")?.rows, Vec::<Vec<Value>>::new());
+        assert_eq!(query(&mut storage, " select b from t where a = 1 or b = 3;")?.rows, vec![vec![Value::Integer(1)], vec![Value::Integer(3)]]);
+        assert_eq!(query(&mut storage, " select b from t where a = null;")?.rows, Vec::<Vec<Value>>::new());
+        assert_eq!(query(&mut storage, " select b from t where a is null;")?.rows, vec![vec![Value::Integer(3)], vec![Value::Null]]);
+        assert_eq!(query(&mut storage, " select a from t where b is not null and a is not null;")?.rows, vec![vec![Value::Integer(1)]]);
+
+        assert_eq!(query(&mut storage, " select * from t where a + 1;").unwrap_err().to_string(), "Filter predicate must be a boolean, got Integer(2)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_union() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table a (id int, name varchar);")?;
+        execute(&mut storage, " create table b (id float, name varchar null);")?;
+        execute(&mut storage, " insert into a values (1, 'x'), (2, 'y'), (1, 'x');")?;
+        execute(&mut storage, " insert into b values (2.5, 'z'), (1.0, null);")?;
+
+        assert_eq!(query(&mut storage, " select id, name from a union all select id, name from b;")?, ResultSet {
+            columns: vec!["id".to_string(), "name".to_string()],
+            column_types: vec![DataType::Float, DataType::String],
+            rows: vec![
+                vec![Value::Float(1.0), Value::String("x".to_string())],
+                vec![Value::Float(2.0), Value::String("y".to_string())],
+                vec![Value::Float(1.0), Value::String("x".to_string())],
+                vec![Value::Float(2.5), Value::String("z".to_string())],
+                vec![Value::Float(1.0), Value::Null],
+            ],
+        });
+        // 两侧先转换为合并后的类型再去重
+        assert_eq!(query(&mut storage, " select id from a union select id from b;")?.rows, vec![
+            vec![Value::Float(1.0)],
+            vec![Value::Float(2.0)],
+            vec![Value::Float(2.5)],
+        ]);
+        execute(&mut storage, " create table c (v decimal(5, 2));")?;
+        execute(&mut storage, " insert into c values (1.5);")?;
+        assert_eq!(query(&mut storage, " select id from a where id = 1 union select v from c;")?, ResultSet {
+            columns: vec!["id".to_string()],
+            column_types: vec![DataType::Decimal { precision: 38, scale: 2 }],
+            rows: vec![
+                vec![Value::Decimal("1.00".parse()?)],
+                vec![Value::Decimal("1.50".parse()?)],
+            ],
+        });
+
+        // 去重作用于两侧合并后的所有行
+        assert_eq!(query(&mut storage, " select name from a union select name from a where id = 2 union select 'z' from b;")?.rows, vec![
+            vec![Value::String("x".to_string())],
+            vec![Value::String("y".to_string())],
+            vec![Value::String("z".to_string())],
+        ]);
+        assert_eq!(query(&mut storage, " select count(*) from a union all select count(*) from b;")?.rows, vec![
+            vec![Value::Integer(3)],
+            vec![Value::Integer(2)],
+        ]);
+
+        assert_eq!(query(&mut storage, " select id from a union select name from b;").unwrap_err().to_string(), "UNION column 1 has incompatible types INTEGER and VARCHAR");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_subquery() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (a int, b varchar);")?;
+        execute(&mut storage, " insert into t values (1, 'x'), (3, 'y'), (2, 'z');")?;
+
+        assert_eq!(query(&mut storage, " select * from t where a = (select max(a) from t);")?.rows, vec![
+            vec![Value::Integer(3), Value::String("y".to_string())],
+        ]);
+        assert_eq!(query(&mut storage, " select a, a - (select min(a) from t where b <> 'x') as d from t where a < 3;")?, ResultSet {
+            columns: vec!["a".to_string(), "d".to_string()],
+            column_types: vec![DataType::Integer, DataType::Integer],
+            rows: vec![
+                vec![Value::Integer(1), Value::Integer(-1)],
+                vec![Value::Integer(2), Value::Integer(0)],
+            ],
+        });
+
+        // 嵌套子查询, 没有行时为 Null
+        assert_eq!(query(&mut storage, " select b from t where a = (select a from t where a > (select min(a) from t) limit 1);")?.rows, vec![
+            vec![Value::String("y".to_string())],
+        ]);
+        assert_eq!(query(&mut storage, " select count(*) from t where a = (select a from t where a > 5);")?.rows, vec![vec![Value::Integer(0)]]);
+
+        assert_eq!(execute(&mut storage, " update t set b = 'max' where a = (select max(a) from t);")?, ExecResult::Affected(1));
+        assert_eq!(execute(&mut storage, " delete from t where a < (select max(a) from t);")?, ExecResult::Affected(2));
+        assert_eq!(query(&mut storage, " select b from t;")?.rows, vec![vec![Value::String("max".to_string())]]);
+
+        execute(&mut storage, " insert into t values (4, 'w');")?;
+        assert_eq!(
+            query(&mut storage, " select * from t where a = (select a from t);").unwrap_err().to_string(),
+            "Subquery returned more than one row: SELECT a FROM t",
+        );
+        assert_eq!(
+            query(&mut storage, " select * from t where a = (select a, b from t);").unwrap_err().to_string(),
+            "Subquery must return exactly one column, got 2",
+        );
+        assert_eq!(query(&mut storage, " select (select c from t) from t;").unwrap_err().to_string(), "Column c does not exist in table t");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_derived_table() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (id int, k varchar, v int);")?;
+        execute(&mut storage, " insert into t values (1, 'a', 10), (2, 'b', 20), (3, 'a', 30);")?;
+
+        assert_eq!(query(&mut storage, " select * from (select id, v * 2 as w from t where id > 1) as sub where w < 60;")?, ResultSet {
+            columns: vec!["id".to_string(), "w".to_string()],
+            column_types: vec![DataType::Integer, DataType::Integer],
+            rows: vec![vec![Value::Integer(2), Value::Integer(40)]],
+        });
+
+        // 派生表上的聚合及与原表的连接
+        assert_eq!(query(&mut storage, " select k, sum(total) from (select k, v as total from t) as s group by k;")?.rows, vec![
+            vec![Value::String("a".to_string()), Value::Integer(40)],
+            vec![Value::String("b".to_string()), Value::Integer(20)],
+        ]);
+        assert_eq!(query(&mut storage, " select t.id, s.n from t join (select k, count(*) as n from t group by k) as s on t.k = s.k where s.n > 1;")?.rows, vec![
+            vec![Value::Integer(1), Value::Integer(2)],
+            vec![Value::Integer(3), Value::Integer(2)],
+        ]);
+        assert_eq!(query(&mut storage, " select s.id from (select t.id from t limit 1) s;")?.rows, vec![vec![Value::Integer(1)]]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_join() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table a (id int, name varchar);")?;
+        execute(&mut storage, " create table b (id int, a_id int, score int);")?;
+        execute(&mut storage, " insert into a values (1, 'x'), (2, 'y');")?;
+        execute(&mut storage, " insert into b values (10, 1, 5), (11, 1, 6), (12, 3, 7);")?;
+
+        // 只有一侧有的列无需限定
+        assert_eq!(query(&mut storage, " select name, score from a join b on a.id = a_id;")?, ResultSet {
+            columns: vec!["name".to_string(), "score".to_string()],
+            column_types: vec![DataType::String, DataType::Integer],
+            rows: vec![
+                vec![Value::String("x".to_string()), Value::Integer(5)],
+                vec![Value::String("x".to_string()), Value::Integer(6)],
+            ],
+        });
+
+        assert_eq!(query(&mut storage, " select name from a join b on a.id = a_id where id > 10;").unwrap_err().to_string(), "Ambiguous column id");
+        assert_eq!(query(&mut storage, " select id from a, b;").unwrap_err().to_string(), "Ambiguous column id");
+
+        // 限定列名消除歧义
+        assert_eq!(query(&mut storage, " select a.id, b.id from a join b on a.id = b.a_id where b.id > 10;")?.rows, vec![
+            vec![Value::Integer(1), Value::Integer(11)],
+        ]);
+
+        let result = query(&mut storage, " select * from a, b where score = 7;")?;
+        assert_eq!(result.columns, vec!["a.id", "a.name", "b.id", "b.a_id", "b.score"]);
+        assert_eq!(result.rows.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_add_column() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (a int);")?;
+        execute(&mut storage, " insert into t values (1), (2);")?;
+
+        execute(&mut storage, " alter table t add column b int default 0;")?;
+        execute(&mut storage, " alter table t add c varchar null;")?;
+        execute(&mut storage, " insert into t values (3, 30, 'x');")?;
+
+        assert_eq!(query(&mut storage, " select * from t;")?, ResultSet {
+            columns: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            column_types: vec![DataType::Integer, DataType::Integer, DataType::String],
+            rows: vec![
+                vec![Value::Integer(1), Value::Integer(0), Value::Null],
+                vec![Value::Integer(2), Value::Integer(0), Value::Null],
+                vec![Value::Integer(3), Value::Integer(30), Value::String("x".to_string())],
+            ],
+        });
+
+        assert_eq!(execute(&mut storage, " alter table t add d int;").unwrap_err().to_string(), "Column d is not nullable and has no default value");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_drop_column() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (id int primary key, a int, b varchar);")?;
+        execute(&mut storage, " insert into t values (1, 10, 'x'), (2, 20, 'y');")?;
+
+        execute(&mut storage, " alter table t drop column a;")?;
+
+        assert_eq!(query(&mut storage, " select * from t;")?, ResultSet {
+            columns: vec!["id".to_string(), "b".to_string()],
+            column_types: vec![DataType::Integer, DataType::String],
+            rows: vec![
+                vec![Value::Integer(1), Value::String("x".to_string())],
+                vec![Value::Integer(2), Value::String("y".to_string())],
+            ],
+        });
+        assert_eq!(query(&mut storage, " select a from t;").unwrap_err().to_string(), "Column a does not exist in table t");
+
+        // 无目录规划时由存储校验
+        let plan = Plan::build(Parser::new(" alter table t drop column id;").parse()?)?;
+        assert_eq!(Executor::new(&mut storage).execute(plan).unwrap_err().to_string(), "Cannot drop primary key column id");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_explain() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (a int);")?;
+
+        assert_eq!(query(&mut storage, " explain select * from t where a = 1;")?, ResultSet {
+            columns: vec!["plan".to_string()],
+            column_types: vec![DataType::String],
+            rows: vec![
+                vec![Value::String("Filter: a = 1".to_string())],
+                vec![Value::String("  Scan: t".to_string())],
+            ],
+        });
+
+        // 不会真正执行
+        execute(&mut storage, " explain insert into t values (1);")?;
+        assert_eq!(query(&mut storage, " select * from t;")?.rows, Vec::<Vec<Value>>::new());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_check() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (id int, age int null check (age >= 0 and age < 150) default 0);")?;
+        execute(&mut storage, " insert into t values (1, 20), (2, null);")?;
+        execute(&mut storage, " insert into t (id) values (3);")?;
+
+        assert_eq!(
+            execute(&mut storage, " insert into t values (4, 30), (5, -1);").unwrap_err().to_string(),
+            "Row 1 violates CHECK constraint on column age: (age >= 0) AND (age < 150)",
+        );
+        assert_eq!(
+            execute(&mut storage, " update t set age = age + 200 where id = 1;").unwrap_err().to_string(),
+            "Row 0 violates CHECK constraint on column age: (age >= 0) AND (age < 150)",
+        );
+
+        // 违反约束时不写入任何行
+        assert_eq!(query(&mut storage, " select age from t;")?.rows, vec![vec![Value::Integer(20)], vec![Value::Null], vec![Value::Integer(0)]]);
+
+        assert_eq!(
+            execute(&mut storage, " create table u (a int check (b > 0));").unwrap_err().to_string(),
+            "Column b does not exist in table u",
+        );
+        assert_eq!(
+            execute(&mut storage, " create table u (a int check (count(*) > 0));").unwrap_err().to_string(),
+            "Aggregate functions are not allowed in CHECK constraints",
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_autoincrement() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (id int primary key autoincrement, name varchar);")?;
+        execute(&mut storage, " insert into t (name) values ('a');")?;
+        execute(&mut storage, " insert into t values (null, 'b');")?;
+        assert_eq!(query(&mut storage, " select id from t;")?.rows, vec![vec![Value::Integer(1)], vec![Value::Integer(2)]]);
+
+        // 显式给出的序号之后继续递增
+        execute(&mut storage, " insert into t values (10, 'c'), (null, 'd');")?;
+        assert_eq!(query(&mut storage, " select id from t where name = 'd';")?.rows, vec![vec![Value::Integer(11)]]);
+
+        // 更新写入的序号同样推进, 之后插入的不会重复
+        execute(&mut storage, " update t set id = 20 where name = 'a';")?;
+        execute(&mut storage, " update t set id = 3 where name = 'b';")?;
+        execute(&mut storage, " insert into t (name) values ('e');")?;
+        assert_eq!(query(&mut storage, " select id from t;")?.rows, vec![
+            vec![Value::Integer(20)], vec![Value::Integer(3)], vec![Value::Integer(10)], vec![Value::Integer(11)], vec![Value::Integer(21)],
+        ]);
+
+        assert_eq!(execute(&mut storage, " update t set id = null;").unwrap_err().to_string(), "Column id in row 0 cannot be null");
+        assert_eq!(
+            execute(&mut storage, " create table u (id varchar autoincrement);").unwrap_err().to_string(),
+            "AUTOINCREMENT column id must be INTEGER",
+        );
+        assert_eq!(
+            execute(&mut storage, " create table u (a int autoincrement, b int autoincrement);").unwrap_err().to_string(),
+            "Table u has more than one AUTOINCREMENT column",
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_rows() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (id int autoincrement, name varchar null, score float check (score >= 0));")?;
+
+        // 整数按列类型转换为浮点数, 自增列为 Null 时分配序号
+        let rows = (0..100).map(|i| vec![Value::Null, Value::String(format!("n{}", i)), Value::Integer(i)]);
+        assert_eq!(Executor::new(&mut storage).insert_rows("t", rows)?, 100);
+        assert_eq!(query(&mut storage, " select count(*), sum(score), max(id) from t;")?.rows, vec![
+            vec![Value::Integer(100), Value::Float(4950.0), Value::Integer(100)],
+        ]);
+        assert_eq!(query(&mut storage, " select * from t where id = 100;")?.rows, vec![
+            vec![Value::Integer(100), Value::String("n99".to_string()), Value::Float(99.0)],
+        ]);
+
+        // 任一行出错时整批不写入
+        let rows = vec![
+            vec![Value::Null, Value::Null, Value::Float(1.0)],
+            vec![Value::Null, Value::Integer(1), Value::Float(1.0)],
+        ];
+        assert_eq!(
+            Executor::new(&mut storage).insert_rows("t", rows).unwrap_err().to_string(),
+            "Column name in row 1: Type mismatch: expected String, got Integer(1)",
+        );
+        assert_eq!(
+            Executor::new(&mut storage).insert_rows("t", vec![vec![Value::Null, Value::Null]]).unwrap_err().to_string(),
+            "Insert row 0 has 2 values, expected 3",
+        );
+        assert_eq!(
+            Executor::new(&mut storage).insert_rows("t", vec![vec![Value::Null, Value::Null, Value::Float(-1.0)]]).unwrap_err().to_string(),
+            "Row 0 violates CHECK constraint on column score: score >= 0",
+        );
+        assert_eq!(
+            Executor::new(&mut storage).insert_rows("missing", vec![]).unwrap_err().to_string(),
+            "Table missing does not exist",
+        );
+        assert_eq!(query(&mut storage, " select count(*) from t;")?.rows, vec![vec![Value::Integer(100)]]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_dynamic_default() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (id int, seq int default (select count(*) from t), tag varchar default 'x');")?;
+
+        // 动态默认值在每次插入时求值, 同一语句中的各行看到插入前的表; 常量默认值不变
+        execute(&mut storage, " insert into t (id) values (1);")?;
+        execute(&mut storage, " insert into t (id) values (2), (3);")?;
+        execute(&mut storage, " insert into t values (4, default, default);")?;
+        assert_eq!(query(&mut storage, " select id, seq, tag from t;")?.rows, vec![
+            vec![Value::Integer(1), Value::Integer(0), Value::String("x".to_string())],
+            vec![Value::Integer(2), Value::Integer(1), Value::String("x".to_string())],
+            vec![Value::Integer(3), Value::Integer(1), Value::String("x".to_string())],
+            vec![Value::Integer(4), Value::Integer(3), Value::String("x".to_string())],
+        ]);
+
+        // 类型及可空性在插入时校验
+        execute(&mut storage, " create table u (a int, b int default (select max(a) from u));")?;
+        assert_eq!(execute(&mut storage, " insert into u (a) values (1);").unwrap_err().to_string(), "Column b in row 0 cannot be null");
+        execute(&mut storage, " insert into u values (1, 0);")?;
+        execute(&mut storage, " insert into u (a) values (2);")?;
+        assert_eq!(query(&mut storage, " select b from u where a = 2;")?.rows, vec![vec![Value::Integer(1)]]);
+
+        assert_eq!(
+            execute(&mut storage, " alter table u add column c int default (select count(*) from u);").unwrap_err().to_string(),
+            "Column c with a dynamic default cannot be added to a non-empty table",
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_returning() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (id int primary key autoincrement, name varchar, score float default 0);")?;
+
+        // 返回分配的序号及默认值
+        assert_eq!(execute(&mut storage, " insert into t (name) values ('a'), ('b') returning id, name, score * 2 as twice;")?, ExecResult::Rows(ResultSet {
+            columns: vec!["id".to_string(), "name".to_string(), "twice".to_string()],
+            column_types: vec![DataType::Integer, DataType::String, DataType::Float],
+            rows: vec![
+                vec![Value::Integer(1), Value::String("a".to_string()), Value::Float(0.0)],
+                vec![Value::Integer(2), Value::String("b".to_string()), Value::Float(0.0)],
+            ],
+        }));
+        assert_eq!(query(&mut storage, " select count(*) from t;")?.rows, vec![vec![Value::Integer(2)]]);
+
+        assert_eq!(query(&mut storage, " update t set score = score + 1.5 where id = 2 returning *;")?, ResultSet {
+            columns: vec!["id".to_string(), "name".to_string(), "score".to_string()],
+            column_types: vec![DataType::Integer, DataType::String, DataType::Float],
+            rows: vec![vec![Value::Integer(2), Value::String("b".to_string()), Value::Float(1.5)]],
+        });
+        assert_eq!(query(&mut storage, " delete from t where score < 1 returning name;")?.rows, vec![vec![Value::String("a".to_string())]]);
+        assert_eq!(query(&mut storage, " delete from t where id > 5 returning name;")?.rows, Vec::<Vec<Value>>::new());
+        assert_eq!(query(&mut storage, " select id from t;")?.rows, vec![vec![Value::Integer(2)]]);
+
+        // 求值失败时不写入
+        assert_eq!(execute(&mut storage, " insert into t (name) values ('c') returning id / 0;").unwrap_err().to_string(), "Division by zero");
+        assert_eq!(query(&mut storage, " select count(*) from t;")?.rows, vec![vec![Value::Integer(1)]]);
+
+        assert_eq!(execute(&mut storage, " delete from t returning age;").unwrap_err().to_string(), "Column age does not exist in table t");
+        assert_eq!(execute(&mut storage, " delete from t returning count(*);").unwrap_err().to_string(), "Aggregate functions are not allowed in RETURNING");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_ddl() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (a int);")?;
+        execute(&mut storage, " create table if not exists t (b int);")?;
+        assert_eq!(execute(&mut storage, " create table t (a int);").unwrap_err().to_string(), "Table t already exists");
+
+        execute(&mut storage, " drop table t;")?;
+        execute(&mut storage, " drop table if exists t;")?;
+        assert_eq!(query(&mut storage, " select * from t;").unwrap_err().to_string(), "Table t does not exist");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_bind() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        execute(&mut storage, " create table t (id int, name varchar, score float default 0);")?;
+
+        for (id, name) in [(1, "a"), (2, "it's")] {
+            let plan = Plan::build_with_catalog(Parser::new(" insert into t (id, name) values (?, ?);").parse()?, &storage)?;
+            Executor::new(&mut storage).execute(plan.bind(&[Value::Integer(id), Value::String(name.to_string())])?)?;
+        }
+
+        let plan = Plan::build_with_catalog(Parser::new(" select name, score from t where id = ?;").parse()?, &storage)?;
+        assert_eq!(Executor::new(&mut storage).execute(plan.bind(&[Value::Integer(2)])?)?, ExecResult::Rows(ResultSet {
+            columns: vec!["name".to_string(), "score".to_string()],
+            column_types: vec![DataType::String, DataType::Float],
+            rows: vec![vec![Value::String("it's".to_string()), Value::Float(0.0)]],
+        }));
+
+        // 绑定后的值在执行时校验类型
+        let plan = Plan::build_with_catalog(Parser::new(" insert into t values (?, ?, ?);").parse()?, &storage)?;
+        let plan = plan.bind(&[Value::String("x".to_string()), Value::String("b".to_string()), Value::Null])?;
+        assert_eq!(Executor::new(&mut storage).execute(plan).unwrap_err().to_string(), r#"Column id in row 0: Type mismatch: expected Integer, got String("x")"#);
+
+        assert_eq!(execute(&mut storage, " delete from t where id = ?;").unwrap_err().to_string(), "Placeholder $1 is not bound");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_column_types() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+        execute(&mut storage, " create table t (a varchar, b int, c float null, d decimal(8, 2) null, e date null);")?;
+        execute(&mut storage, " insert into t (a, b) values ('x', 1), ('y', 2), ('x', 3);")?;
+
+        let result = query(&mut storage, " select a, count(*) from t group by a;")?;
+        assert_eq!(result.columns, vec!["a".to_string(), "expr1".to_string()]);
+        assert_eq!(result.column_types, vec![DataType::String, DataType::Integer]);
+
+        let types = |storage: &mut MemoryStorage, sql: &str| query(storage, sql).map(|result| result.column_types);
+        assert_eq!(types(&mut storage, " select sum(b), avg(b), min(a), max(d), sum(c) from t;")?, vec![
+            DataType::Integer, DataType::Float, DataType::String, DataType::Decimal { precision: 8, scale: 2 }, DataType::Float,
+        ]);
+        assert_eq!(types(&mut storage, " select b + 1, b / 2, b * 1.5, d * 2, d * d, d / 2, -b, a || 'z' from t;")?, vec![
+            DataType::Integer, DataType::Integer, DataType::Float,
+            DataType::Decimal { precision: 38, scale: 2 }, DataType::Decimal { precision: 38, scale: 4 }, DataType::Float,
+            DataType::Integer, DataType::String,
+        ]);
+        assert_eq!(types(&mut storage, " select b > 1, a like 'x%', c is null, not b in (1, 2) from t;")?, vec![DataType::Boolean; 4]);
+        assert_eq!(types(&mut storage, " select cast(b as varchar), cast(e as timestamp), coalesce(null, c, 0), nullif(b, 1) from t;")?, vec![
+            DataType::String, DataType::Timestamp, DataType::Float, DataType::Integer,
+        ]);
+        assert_eq!(types(&mut storage, " select case when b > 1 then null else e end, null, -c from t where b = 0;")?, vec![
+            DataType::Date, DataType::String, DataType::Float,
+        ]);
+        assert_eq!(types(&mut storage, " select * from t;")?, vec![
+            DataType::String, DataType::Integer, DataType::Float, DataType::Decimal { precision: 8, scale: 2 }, DataType::Date,
+        ]);
+
+        // 类型不兼容的运算在取得结果列时即报错
+        assert_eq!(query(&mut storage, " select a + 1 from t;").unwrap_err().to_string(), "Cannot apply Add to VARCHAR and INTEGER");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_system_tables() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table users (id int primary key, name varchar(20) null comment 'display name') comment 'registered users';")?;
+        execute(&mut storage, " create table orders (id int, amount decimal(8, 2), user_id int);")?;
+
+        let string = |s: &str| Value::String(s.to_string());
+        assert_eq!(query(&mut storage, " select * from __tables__;")?, ResultSet {
+            columns: vec!["table_name".to_string(), "comment".to_string()],
+            column_types: vec![DataType::String, DataType::String],
+            rows: vec![vec![string("orders"), Value::Null], vec![string("users"), string("registered users")]],
+        });
+
+        assert_eq!(query(&mut storage, " select * from __columns__;")?, ResultSet {
+            columns: vec!["table_name".to_string(), "column_name".to_string(), "data_type".to_string(), "nullable".to_string(), "comment".to_string()],
+            column_types: vec![DataType::String, DataType::String, DataType::String, DataType::Boolean, DataType::String],
+            rows: vec![
+                vec![string("orders"), string("id"), string("INTEGER"), Value::Boolean(false), Value::Null],
+                vec![string("orders"), string("amount"), string("DECIMAL(8, 2)"), Value::Boolean(false), Value::Null],
+                vec![string("orders"), string("user_id"), string("INTEGER"), Value::Boolean(false), Value::Null],
+                vec![string("users"), string("id"), string("INTEGER"), Value::Boolean(false), Value::Null],
+                vec![string("users"), string("name"), string("VARCHAR(20)"), Value::Boolean(true), string("display name")],
+            ],
+        });
+
+        // 系统表可以像普通表一样过滤和投影
+        assert_eq!(query(&mut storage, " select column_name from __columns__ where table_name = 'users' and nullable;")?.rows, vec![
+            vec![string("name")],
+        ]);
+
+        assert_eq!(execute(&mut storage, " insert into __tables__ values ('t');").unwrap_err().to_string(), "Table __tables__ is read-only");
+        assert_eq!(execute(&mut storage, " create table __columns__ (a int);").unwrap_err().to_string(), "Table __columns__ is read-only");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_truncate() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (id int autoincrement, a int);")?;
+        execute(&mut storage, " insert into t (a) values (1), (2), (3);")?;
+
+        assert_eq!(execute(&mut storage, " truncate table t;")?, ExecResult::Affected(3));
+        assert_eq!(query(&mut storage, " select * from t;")?, ResultSet {
+            columns: vec!["id".to_string(), "a".to_string()],
+            column_types: vec![DataType::Integer, DataType::Integer],
+            rows: vec![],
+        });
+
+        // 表结构保留, 自增序号从头开始
+        execute(&mut storage, " insert into t (a) values (4);")?;
+        assert_eq!(query(&mut storage, " select * from t;")?.rows, vec![vec![Value::Integer(1), Value::Integer(4)]]);
+
+        assert_eq!(execute(&mut storage, " truncate table missing;").unwrap_err().to_string(), "Table missing does not exist");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_unplanned_insert() -> Result<()> {
+        let mut storage = MemoryStorage::default();
+
+        execute(&mut storage, " create table t (a int, b int default 2);")?;
+
+        // 未使用目录规划的插入由执行器补全默认值
+        let plan = Plan::build(Parser::new(" insert into t (a) values (1);").parse()?)?;
+        Executor::new(&mut storage).execute(plan)?;
+
+        let plan = Plan::build(Parser::new(" insert into t values (1);").parse()?)?;
+        assert_eq!(Executor::new(&mut storage).execute(plan).unwrap_err().to_string(), "Insert row 0 has 1 values, expected 2");
+
+        assert_eq!(query(&mut storage, " select * from t;")?.rows, vec![vec![Value::Integer(1), Value::Integer(2)]]);
+
+        Ok(())
+    }
+
+    // 记录扫描产出的行数
+    #[derive(Debug, Default)]
+    struct CountingStorage {
+        inner: MemoryStorage,
+        scanned: Cell<usize>,
+        // 设置后模拟主键索引, 记录索引查找的次数
+        indexed: bool,
+        lookups: Cell<usize>,
+    }
+
+    impl Catalog for CountingStorage {
+        fn get_table(&self, name: &str) -> Option<&Table> {
+            self.inner.get_table(name)
+        }
+
+        fn tables(&self) -> Vec<&Table> {
+            self.inner.tables()
+        }
+    }
+
+    impl Storage for CountingStorage {
+        fn create_table(&mut self, table: Table) -> Result<()> {
+            self.inner.create_table(table)
+        }
+
+        fn drop_table(&mut self, table_name: &str) -> Result<()> {
+            self.inner.drop_table(table_name)
+        }
+
+        fn add_column(&mut self, table_name: &str, column: Column) -> Result<()> {
+            self.inner.add_column(table_name, column)
+        }
+
+        fn drop_column(&mut self, table_name: &str, column: &str) -> Result<()> {
+            self.inner.drop_column(table_name, column)
+        }
+
+        fn insert(&mut self, table_name: &str, rows: Vec<Vec<Value>>) -> Result<()> {
+            self.inner.insert(table_name, rows)
+        }
+
+        fn scan(&self, table_name: &str) -> Result<Rows<'_>> {
+            Ok(Box::new(self.inner.scan(table_name)?.inspect(|_| self.scanned.set(self.scanned.get() + 1))))
+        }
+
+        fn index_lookup(&self, table_name: &str, column: &str, value: &Value) -> Result<Option<Rows<'_>>> {
+            if !self.indexed {
+                return Ok(None);
+            }
+
+            self.lookups.set(self.lookups.get() + 1);
+            let i = self.inner.get_table(table_name).and_then(|table| table.columns.iter().position(|col| col.name == column)).unwrap();
+            let value = value.clone();
+            Ok(Some(Box::new(self.inner.scan(table_name)?.filter(move |row| row[i] == value))))
+        }
+
+        fn update(&mut self, table_name: &str, rows: Vec<(usize, Vec<Value>)>) -> Result<()> {
+            self.inner.update(table_name, rows)
+        }
+
+        fn delete(&mut self, table_name: &str, indexes: &[usize]) -> Result<()> {
+            self.inner.delete(table_name, indexes)
+        }
+
+        fn sequence(&self, table_name: &str) -> Result<i64> {
+            self.inner.sequence(table_name)
+        }
+
+        fn set_sequence(&mut self, table_name: &str, value: i64) -> Result<()> {
+            self.inner.set_sequence(table_name, value)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    #[test]
+    fn test_execute_limit_stops_scan() -> Result<()> {
+        let mut storage = CountingStorage::default();
+        let mut execute = |sql: &str| {
+            let plan = Plan::build_with_catalog(Parser::new(sql).parse()?, &storage)?;
+            storage.scanned.set(0);
+            let result = Executor::new(&mut storage).execute(plan)?;
+            Ok::<_, anyhow::Error>((result, storage.scanned.get()))
+        };
+
+        execute(" create table t (a int);")?;
+        execute(&format!(" insert into t values {};", (0..100).map(|i| format!("({})", i)).collect::<Vec<_>>().join(", ")))?;
+
+        let (result, scanned) = execute(" select a from t limit 3 offset 2;")?;
+        assert_eq!(result, ExecResult::Rows(ResultSet {
+            columns: vec!["a".to_string()],
+            column_types: vec![DataType::Integer],
+            rows: vec![vec![Value::Integer(2)], vec![Value::Integer(3)], vec![Value::Integer(4)]],
+        }));
+        assert_eq!(scanned, 5);
+
+        // 过滤掉的行也须扫描
+        let (_, scanned) = execute(" select * from t where a % 10 = 0 limit 2;")?;
+        assert_eq!(scanned, 11);
+
+        assert_eq!(execute(" select * from t limit 0;")?.1, 0);
+        assert_eq!(execute(" select distinct a from t limit 1 offset 1;")?.1, 2);
+
+        // 聚合须读完输入
+        assert_eq!(execute(" select count(*) from t limit 1;")?.1, 100);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_index_lookup() -> Result<()> {
+        let mut storage = CountingStorage { indexed: true, ..Default::default() };
+        let mut execute = |sql: &str| {
+            let plan = Plan::build_with_catalog(Parser::new(sql).parse()?, &storage)?;
+            storage.scanned.set(0);
+            storage.lookups.set(0);
+            let result = Executor::new(&mut storage).execute(plan)?;
+            Ok::<_, anyhow::Error>((result, storage.scanned.get(), storage.lookups.get()))
+        };
+
+        execute(" create table t (id int primary key, a int);")?;
+        execute(&format!(" insert into t values {};", (0..100).map(|i| format!("({}, {})", i, i % 10)).collect::<Vec<_>>().join(", ")))?;
+
+        // 主键等值条件交由索引查找, 其余条件照常过滤命中的行
+        let rows = |result: ExecResult| match result {
+            ExecResult::Rows(result) => result.rows,
+            result => panic!("not a query result: {:?}", result),
+        };
+        let (result, scanned, lookups) = execute(" select a from t where id = 42;")?;
+        assert_eq!((rows(result), scanned, lookups), (vec![vec![Value::Integer(2)]], 0, 1));
+        let (result, _, lookups) = execute(" select id from t where a = 3 and 73 = id;")?;
+        assert_eq!((rows(result), lookups), (vec![vec![Value::Integer(73)]], 1));
+        let (result, _, lookups) = execute(" select id from t where id = 42 and a = 3;")?;
+        assert_eq!((rows(result), lookups), (vec![], 1));
+
+        // 非主键列、OR 条件及类型不同的常量全表扫描
+        let (_, scanned, lookups) = execute(" select id from t where a = 3;")?;
+        assert_eq!((scanned, lookups), (100, 0));
+        assert_eq!(execute(" select id from t where id = 1 or id = 2;")?.2, 0);
+        assert_eq!(execute(" select id from t where id = 1.0;")?.2, 0);
+
+        Ok(())
+    }
+}
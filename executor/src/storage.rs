@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use common::schema::{Catalog, Column, ColumnDefault, Table};
+use common::types::Value;
+
+pub type Rows<'a> = Box<dyn Iterator<Item=&'a Vec<Value>> + 'a>;
+
+// 存储引擎, 保存表结构及各表的行数据
+pub trait Storage: Catalog {
+    fn create_table(&mut self, table: Table) -> Result<()>;
+
+    fn drop_table(&mut self, table_name: &str) -> Result<()>;
+
+    fn add_column(&mut self, table_name: &str, column: Column) -> Result<()>;
+
+    fn drop_column(&mut self, table_name: &str, column: &str) -> Result<()>;
+
+    fn insert(&mut self, table_name: &str, rows: Vec<Vec<Value>>) -> Result<()>;
+
+    // 按插入顺序逐行读取
+    fn scan(&self, table_name: &str) -> Result<Rows<'_>>;
+
+    // 按主键列的值查找行, 供过滤条件 主键列 = 常量 跳过全表扫描
+    // 返回 None 表示不支持索引, 由执行器全表扫描
+    fn index_lookup(&self, _table_name: &str, _column: &str, _value: &Value) -> Result<Option<Rows<'_>>> {
+        Ok(None)
+    }
+
+    // 按行号替换行, 行号为 scan 结果中的位置
+    fn update(&mut self, table_name: &str, rows: Vec<(usize, Vec<Value>)>) -> Result<()>;
+
+    // 按行号删除行
+    fn delete(&mut self, table_name: &str, indexes: &[usize]) -> Result<()>;
+
+    // 表中自增列已分配的最大序号, 未分配过为 0
+    fn sequence(&self, table_name: &str) -> Result<i64>;
+
+    fn set_sequence(&mut self, table_name: &str, value: i64) -> Result<()>;
+
+    // 将数据写入持久化介质
+    fn flush(&mut self) -> Result<()>;
+}
+
+// 内存存储, 表结构及各表的行数据
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MemoryStorage {
+    tables: HashMap<String, Table>,
+    rows: HashMap<String, Vec<Vec<Value>>>,
+    sequences: HashMap<String, i64>,
+}
+
+impl Storage for MemoryStorage {
+    fn create_table(&mut self, table: Table) -> Result<()> {
+        if self.tables.contains_key(&table.name) {
+            bail!("Table {} already exists", table.name);
+        }
+
+        self.rows.insert(table.name.clone(), vec![]);
+        self.sequences.insert(table.name.clone(), 0);
+        self.tables.insert(table.name.clone(), table);
+
+        Ok(())
+    }
+
+    fn drop_table(&mut self, table_name: &str) -> Result<()> {
+        self.tables.remove(table_name).ok_or(anyhow!("Table {} does not exist", table_name))?;
+        self.rows.remove(table_name);
+        self.sequences.remove(table_name);
+
+        Ok(())
+    }
+
+    // 已有的行追加新列的默认值
+    fn add_column(&mut self, table_name: &str, column: Column) -> Result<()> {
+        let table = self.tables.get_mut(table_name).ok_or(anyhow!("Table {} does not exist", table_name))?;
+        if table.get_column(&column.name).is_some() {
+            bail!("Column {} already exists in table {}", column.name, table_name);
+        }
+
+        let rows = self.rows.entry(table_name.to_string()).or_default();
+        let default = match &column.default {
+            Some(ColumnDefault::Const(default)) => default.clone(),
+            _ if rows.is_empty() => Value::Null,
+            Some(ColumnDefault::Dynamic(_)) => bail!("Column {} with a dynamic default cannot be added to a non-empty table", column.name),
+            None => bail!("Column {} is not nullable and has no default value", column.name),
+        };
+        for row in rows.iter_mut() {
+            row.push(default.clone());
+        }
+
+        table.columns.push(column);
+
+        Ok(())
+    }
+
+    // 从表结构及每行中移除该列
+    fn drop_column(&mut self, table_name: &str, column: &str) -> Result<()> {
+        let table = self.tables.get_mut(table_name).ok_or(anyhow!("Table {} does not exist", table_name))?;
+
+        let i = table.columns.iter()
+            .position(|col| col.name == column)
+            .ok_or(anyhow!("Column {} does not exist in table {}", column, table_name))?;
+        if table.columns[i].primary_key {
+            bail!("Cannot drop primary key column {}", column);
+        }
+        if table.columns.len() == 1 {
+            bail!("Cannot drop the only column {} of table {}", column, table_name);
+        }
+
+        table.columns.remove(i);
+        for row in self.rows.entry(table_name.to_string()).or_default() {
+            row.remove(i);
+        }
+
+        Ok(())
+    }
+
+    fn insert(&mut self, table_name: &str, rows: Vec<Vec<Value>>) -> Result<()> {
+        self.rows.get_mut(table_name)
+            .ok_or(anyhow!("Table {} does not exist", table_name))?
+            .extend(rows);
+
+        Ok(())
+    }
+
+    fn scan(&self, table_name: &str) -> Result<Rows<'_>> {
+        let rows = self.rows.get(table_name).ok_or(anyhow!("Table {} does not exist", table_name))?;
+
+        Ok(Box::new(rows.iter()))
+    }
+
+    fn update(&mut self, table_name: &str, rows: Vec<(usize, Vec<Value>)>) -> Result<()> {
+        let table_rows = self.rows.get_mut(table_name).ok_or(anyhow!("Table {} does not exist", table_name))?;
+
+        for (i, row) in rows {
+            *table_rows.get_mut(i).ok_or(anyhow!("Row {} does not exist in table {}", i, table_name))? = row;
+        }
+
+        Ok(())
+    }
+
+    fn delete(&mut self, table_name: &str, indexes: &[usize]) -> Result<()> {
+        let rows = self.rows.get_mut(table_name).ok_or(anyhow!("Table {} does not exist", table_name))?;
+
+        let indexes = indexes.iter().collect::<HashSet<_>>();
+        let mut i = 0;
+        rows.retain(|_| {
+            i += 1;
+            !indexes.contains(&(i - 1))
+        });
+
+        Ok(())
+    }
+
+    fn sequence(&self, table_name: &str) -> Result<i64> {
+        self.sequences.get(table_name).copied().ok_or(anyhow!("Table {} does not exist", table_name))
+    }
+
+    fn set_sequence(&mut self, table_name: &str, value: i64) -> Result<()> {
+        *self.sequences.get_mut(table_name).ok_or(anyhow!("Table {} does not exist", table_name))? = value;
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Catalog for MemoryStorage {
+    fn get_table(&self, name: &str) -> Option<&Table> {
+        self.tables.get(name)
+    }
+
+    fn tables(&self) -> Vec<&Table> {
+        self.tables.values().collect()
+    }
+}
+
+// 文件存储, 数据保存在内存中, flush 时整体写入文件, 打开时从文件加载
+// 文件按 MemoryStorage 的字段顺序编码, 字段变化后旧文件无法加载
+#[derive(Debug)]
+pub struct FileStorage {
+    path: PathBuf,
+    inner: MemoryStorage,
+}
+
+impl FileStorage {
+    // 文件不存在时为空库
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let inner = match fs::read(&path) {
+            Ok(bytes) => bincode::deserialize(&bytes)
+                .map_err(|e| anyhow!("Failed to load {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => MemoryStorage::default(),
+            Err(e) => bail!("Failed to read {}: {}", path.display(), e),
+        };
+
+        Ok(Self { path, inner })
+    }
+
+    pub fn close(mut self) -> Result<()> {
+        self.flush()
+    }
+}
+
+impl Storage for FileStorage {
+    fn create_table(&mut self, table: Table) -> Result<()> {
+        self.inner.create_table(table)
+    }
+
+    fn drop_table(&mut self, table_name: &str) -> Result<()> {
+        self.inner.drop_table(table_name)
+    }
+
+    fn add_column(&mut self, table_name: &str, column: Column) -> Result<()> {
+        self.inner.add_column(table_name, column)
+    }
+
+    fn drop_column(&mut self, table_name: &str, column: &str) -> Result<()> {
+        self.inner.drop_column(table_name, column)
+    }
+
+    fn insert(&mut self, table_name: &str, rows: Vec<Vec<Value>>) -> Result<()> {
+        self.inner.insert(table_name, rows)
+    }
+
+    fn scan(&self, table_name: &str) -> Result<Rows<'_>> {
+        self.inner.scan(table_name)
+    }
+
+    fn update(&mut self, table_name: &str, rows: Vec<(usize, Vec<Value>)>) -> Result<()> {
+        self.inner.update(table_name, rows)
+    }
+
+    fn delete(&mut self, table_name: &str, indexes: &[usize]) -> Result<()> {
+        self.inner.delete(table_name, indexes)
+    }
+
+    fn sequence(&self, table_name: &str) -> Result<i64> {
+        self.inner.sequence(table_name)
+    }
+
+    fn set_sequence(&mut self, table_name: &str, value: i64) -> Result<()> {
+        self.inner.set_sequence(table_name, value)
+    }
+
+    // 先写临时文件再重命名, 避免写入中断时损坏原文件
+    fn flush(&mut self) -> Result<()> {
+        let bytes = bincode::serialize(&self.inner)?;
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+}
+
+impl Catalog for FileStorage {
+    fn get_table(&self, name: &str) -> Option<&Table> {
+        self.inner.get_table(name)
+    }
+
+    fn tables(&self) -> Vec<&Table> {
+        self.inner.tables()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::types::{Collation, DataType};
+
+    fn table() -> Table {
+        Table {
+            name: "t".to_string(),
+            columns: vec![Column {
+                name: "a".to_string(),
+                data_type: DataType::Float,
+                max_length: None,
+                nullable: true,
+                default: Some(ColumnDefault::Const(Value::Null)),
+                primary_key: false,
+                unique: false,
+                check: None,
+                auto_increment: false,
+                collation: Collation::Binary,
+                comment: None,
+            }],
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_file_storage_reopen() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("rsqldb_test_{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let rows = vec![vec![Value::Float(1.5)], vec![Value::Null], vec![Value::Float(f64::NAN)]];
+
+        let mut storage = FileStorage::open(&path)?;
+        storage.create_table(table())?;
+        storage.insert("t", rows.clone())?;
+        storage.close()?;
+
+        // 重新打开后表结构及数据不变, 浮点按位还原
+        let mut storage = FileStorage::open(&path)?;
+        assert_eq!(storage.get_table("t"), Some(&table()));
+        assert_eq!(storage.scan("t")?.cloned().collect::<Vec<_>>(), rows);
+
+        // 未 flush 的修改不会写入文件
+        storage.insert("t", vec![vec![Value::Float(2.0)]])?;
+        drop(storage);
+        assert_eq!(FileStorage::open(&path)?.scan("t")?.count(), 3);
+
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+}
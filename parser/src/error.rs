@@ -0,0 +1,50 @@
+use std::fmt::{Display, Formatter};
+use crate::token::Token;
+
+// 语法错误, 调用方可据此区分输入不完整与真正的语法错误
+// 对外仍以 anyhow::Error 返回, 通过 downcast_ref::<ParseError>() 取得
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    // 输入提前结束
+    UnexpectedEof,
+    UnexpectedToken(Token),
+    ExpectedToken { expected: Token, found: Token },
+    UnknownKeyword(String),
+    // 词法错误, position 为在输入中的字节偏移
+    InvalidToken { message: String, position: usize },
+    // 列定义列表中的一项之后既不是 ',' 也不是 ')', after 为该项的描述, found 为 None 表示输入已结束
+    UnterminatedColumnList { after: String, found: Option<Token> },
+    // 其余语法错误
+    Syntax(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::UnexpectedEof => write!(f, "Unexpected end of input"),
+            ParseError::UnexpectedToken(token) => write!(f, "Unexpected token: {:?}", token),
+            ParseError::ExpectedToken { expected, found } => write!(f, "Expected {:?}, got {:?}", expected, found),
+            ParseError::UnknownKeyword(keyword) => write!(f, "Unknown keyword: {}", keyword),
+            ParseError::InvalidToken { message, position } => write!(f, "{} at position {}", message, position),
+            ParseError::UnterminatedColumnList { after, found } => match found {
+                Some(token) => write!(f, "Expected ',' or ')' in column definition list after {}, got {:?}", after, token),
+                None => write!(f, "Expected ',' or ')' in column definition list after {}, got EOF", after),
+            },
+            ParseError::Syntax(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    // 输入提前结束, 补全后可能合法, 如未闭合的括号、引号
+    pub fn is_incomplete(&self) -> bool {
+        matches!(self, ParseError::UnexpectedEof | ParseError::UnterminatedColumnList { found: None, .. })
+    }
+}
+
+// 交互式输入据此决定继续读取下一行还是报错
+pub fn is_incomplete(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ParseError>().is_some_and(ParseError::is_incomplete)
+}
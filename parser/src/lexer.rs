@@ -1,101 +1,279 @@
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
 use std::iter::Peekable;
-use std::str::{Chars, FromStr};
+use std::ops::Range;
+use std::str::{CharIndices, FromStr};
+use crate::error::ParseError;
 use crate::token::{Keyword, Symbol, Token};
 
 // 词法分析
 pub struct Lexer<'a> {
-    inner: Peekable<Chars<'a>>,
+    input: &'a str,
+    inner: Peekable<CharIndices<'a>>,
+    // 自定义关键字, 键为大写, 优先于内置关键字
+    keywords: HashMap<String, Keyword>,
+    // 恢复模式下出错时产生 Token::Error, 跳到下一个空白处继续扫描
+    recover: bool,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
         Self {
-            inner: input.chars().peekable(),
+            input,
+            inner: input.char_indices().peekable(),
+            keywords: HashMap::new(),
+            recover: false,
         }
     }
 
+    // 追加或覆盖关键字, 如 ("BIGINT", Keyword::Int) 使 BIGINT 作为 INT 解析, 不区分大小写
+    pub fn with_keywords<S: Into<String>>(input: &'a str, keywords: impl IntoIterator<Item=(S, Keyword)>) -> Self {
+        Self {
+            keywords: keywords.into_iter().map(|(name, keyword)| (name.into().to_uppercase(), keyword)).collect(),
+            ..Self::new(input)
+        }
+    }
+
+    pub fn with_recovery(mut self) -> Self {
+        self.recover = true;
+        self
+    }
+
+    // 同时返回 token 在输入中的字节范围 start..end, 不含前面的空白和注释
+    pub fn scan_spanned(&mut self) -> Result<Option<(Token, Range<usize>)>> {
+        self.skip_whitespace_and_comments();
 
+        let start = self.offset();
+        let token = match self.scan_token() {
+            Err(e) if self.recover => {
+                while self.next_char_if(|c| !c.is_whitespace()).is_some() {}
+                Some(Token::Error { message: e.to_string(), position: start })
+            }
+            token => token?,
+        };
+
+        Ok(token.map(|token| (token, start..self.offset())))
+    }
+
+    // 输入结束返回 Ok(None)
     fn scan(&mut self) -> Result<Option<Token>> {
-        while self.inner.next_if(|c| c.is_whitespace()).is_some() {}
+        self.scan_spanned().map(|spanned| spanned.map(|(token, _)| token))
+    }
 
-        Ok(match self.inner.peek() {
-            Some('\'') => self.scan_string(),
+    fn scan_token(&mut self) -> Result<Option<Token>> {
+        Ok(Some(match self.peek_char() {
+            None => return Ok(None),
+            Some('\'') => self.scan_string()?,
+            Some(c @ ('"' | '`')) => self.scan_quoted_ident(c)?,
+            Some('0') if matches!(self.peek_second(), Some('x' | 'X' | 'b' | 'B')) => self.scan_radix_number()?,
             Some(c) if c.is_ascii_digit() => self.scan_number(),
-            Some(c) if c.is_alphabetic() => self.scan_keyword_or_ident(),
-            Some(c) if c.is_ascii_punctuation() => self.scan_symbol(),
-            _ => bail!("Unexpected end of input"),
-        })
+            // .5 是数字,其余的 . 是符号
+            Some('.') if self.peek_second().is_some_and(|c| c.is_ascii_digit()) => self.scan_number(),
+            Some(c) if c.is_alphabetic() || c == '_' => self.scan_keyword_or_ident(),
+            Some('?' | '$') => self.scan_placeholder()?,
+            Some(c) if c.is_ascii_punctuation() => self.scan_symbol()?,
+            Some(c) => {
+                self.next_char();
+                bail!("Unexpected character: {}", c)
+            }
+        }))
+    }
+
+    // 跳过空白和 -- 行注释
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            while self.next_char_if(char::is_whitespace).is_some() {}
+
+            if self.peek_char() != Some('-') || self.peek_second() != Some('-') {
+                break;
+            }
+
+            while self.next_char_if(|c| c != '\n').is_some() {}
+        }
+    }
+
+    // 下一个字符的字节偏移, 输入结束时为输入长度
+    fn offset(&mut self) -> usize {
+        self.inner.peek().map_or(self.input.len(), |&(i, _)| i)
     }
 
-    // 'xxx' -> xxx
-    fn scan_string(&mut self) -> Option<Token> {
-        if self.inner.next_if(|&c| c == '\'').is_none() {
-            return None;
+    fn peek_char(&mut self) -> Option<char> {
+        self.inner.peek().map(|&(_, c)| c)
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        self.inner.next().map(|(_, c)| c)
+    }
+
+    fn next_char_if(&mut self, f: impl FnOnce(char) -> bool) -> Option<char> {
+        self.inner.next_if(|&(_, c)| f(c)).map(|(_, c)| c)
+    }
+
+    // 查看下一个之后的字符,不消耗
+    fn peek_second(&self) -> Option<char> {
+        let mut iter = self.inner.clone();
+        iter.next();
+        iter.next().map(|(_, c)| c)
+    }
+
+    // 'xxx' -> xxx, 'it''s' -> it's
+    fn scan_string(&mut self) -> Result<Token> {
+        self.next_char_if(|c| c == '\'');
+
+        let mut val = String::new();
+
+        loop {
+            match self.next_char() {
+                Some('\'') if self.next_char_if(|c| c == '\'').is_some() => val.push('\''),
+                Some('\'') => break,
+                Some(c) => val.push(c),
+                None => return Err(anyhow!(ParseError::UnexpectedEof).context(format!("Unterminated string: '{}", val))),
+            }
         }
 
+        Ok(Token::String(val))
+    }
+
+    // "Users" `order` -> Users order, 保留大小写, 关键字也作为标识符
+    fn scan_quoted_ident(&mut self, quote: char) -> Result<Token> {
+        self.next_char_if(|c| c == quote);
+
         let mut val = String::new();
 
         loop {
-            match self.inner.next()? {
-                '\'' => break,
-                c => val.push(c),
+            match self.next_char() {
+                Some(c) if c == quote => break,
+                Some(c) => val.push(c),
+                None => return Err(anyhow!(ParseError::UnexpectedEof).context(format!("Unterminated identifier: {}{}", quote, val))),
             }
         }
 
-        Some(Token::String(val))
+        if val.is_empty() {
+            bail!("Empty quoted identifier");
+        }
+        // 列名以 table.column 表示限定, 名称中不能含 .
+        if val.contains('.') {
+            bail!("Quoted identifier {} cannot contain '.'", val);
+        }
+
+        Ok(Token::Ident(val))
     }
 
-    // 1.23
-    fn scan_number(&mut self) -> Option<Token> {
+    // 0x1F 0b1010 -> 31 10, 转为十进制; 其后紧跟的字母数字都属于该字面量
+    fn scan_radix_number(&mut self) -> Result<Token> {
+        let mut literal = String::new();
+
+        while let Some(c) = self.next_char_if(|c| c.is_alphanumeric() || c == '_') {
+            literal.push(c);
+        }
+
+        let (radix, name) = match &literal[1..2] {
+            "x" | "X" => (16, "hexadecimal"),
+            _ => (2, "binary"),
+        };
+        let digits = &literal[2..];
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            bail!("Invalid {} literal: {}", name, literal);
+        }
+
+        match u64::from_str_radix(digits, radix) {
+            Ok(v) => Ok(Token::Number(v.to_string())),
+            Err(_) => bail!("Integer literal {} out of range", literal),
+        }
+    }
+
+    // 1.23 .5
+    fn scan_number(&mut self) -> Token {
         let mut num = String::new();
 
-        while let Some(c) = self.inner.next_if(|&c| c.is_numeric()) {
+        while let Some(c) = self.next_char_if(|c| c.is_numeric()) {
             num.push(c);
         }
 
-        if let Some(sep) = self.inner.next_if(|&c| c == '.') {
+        if let Some(sep) = self.next_char_if(|c| c == '.') {
             num.push(sep);
 
-            while let Some(c) = self.inner.next_if(|&c| c.is_numeric()) {
+            while let Some(c) = self.next_char_if(|c| c.is_numeric()) {
                 num.push(c);
             }
         }
 
-        Some(Token::Number(num))
+        Token::Number(num)
     }
 
-    // tbl_name true
-    fn scan_keyword_or_ident(&mut self) -> Option<Token> {
+    // tbl_name _tmp true
+    // 未加引号的标识符不区分大小写, 统一转为小写
+    fn scan_keyword_or_ident(&mut self) -> Token {
         let mut val = String::new();
 
-        while let Some(c) = self.inner.next_if(|&c| c.is_alphabetic()) {
+        while let Some(c) = self.next_char_if(|c| c.is_alphabetic() || c == '_') {
             val.push(c);
         }
 
-        while let Some(c) = self.inner.next_if(|&c| c.is_alphanumeric()) {
+        while let Some(c) = self.next_char_if(|c| c.is_alphanumeric() || c == '_') {
             val.push(c);
         }
 
-        Some(Keyword::from_str(&val).map_or(Token::Ident(val), |k| Token::Keyword(k)))
+        match self.keywords.get(&val.to_uppercase()) {
+            Some(keyword) => Token::Keyword(keyword.clone()),
+            None => Keyword::from_str(&val).map_or(Token::Ident(val.to_lowercase()), Token::Keyword),
+        }
     }
 
-    fn scan_symbol(&mut self) -> Option<Token> {
-        let val = self.inner.peek()?;
+    // ? 按出现顺序编号, $1 直接给出编号, 编号从 1 开始
+    fn scan_placeholder(&mut self) -> Result<Token> {
+        if self.next_char_if(|c| c == '?').is_some() {
+            return Ok(Token::Placeholder(None));
+        }
+        self.next_char();
 
-        let symbol = Symbol::try_from(val).ok()?;
+        let mut num = String::new();
+        while let Some(c) = self.next_char_if(|c| c.is_ascii_digit()) {
+            num.push(c);
+        }
 
-        self.inner.next();
+        match num.parse() {
+            Ok(n) if n > 0 => Ok(Token::Placeholder(Some(n))),
+            _ => bail!("Invalid placeholder: ${}", num),
+        }
+    }
+
+    // <= >= <> != 为双字符符号
+    // 出错时也消耗该字符,避免迭代器卡在同一位置
+    fn scan_symbol(&mut self) -> Result<Token> {
+        let c = self.next_char().ok_or(anyhow!("Unexpected end of input"))?;
+
+        let symbol = match (c, self.peek_char()) {
+            ('<', Some('=')) => Symbol::LessThanOrEqual,
+            ('>', Some('=')) => Symbol::GreaterThanOrEqual,
+            ('<', Some('>')) | ('!', Some('=')) => Symbol::NotEqual,
+            ('|', Some('|')) => Symbol::Concat,
+            _ => return Ok(Token::Symbol(Symbol::try_from(c)?)),
+        };
+
+        self.next_char();
 
-        Some(Token::Symbol(symbol))
+        Ok(Token::Symbol(symbol))
     }
 }
 
+// 扫描出错时返回 Some(Err(..)),不再当作输入结束
 impl<'a> Iterator for Lexer<'a> {
-    type Item = Token;
+    type Item = Result<Token>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.scan().ok()?
+        self.scan().transpose()
+    }
+}
+
+// 同 Lexer, 每个 token 附带其字节范围, 见 scan_spanned
+pub struct Spanned<'a>(pub Lexer<'a>);
+
+impl<'a> Iterator for Spanned<'a> {
+    type Item = Result<(Token, Range<usize>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.scan_spanned().transpose()
     }
 }
 
@@ -105,7 +283,7 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_create() {
+    fn test_create() -> Result<()> {
         let input = "CREATE TABLE tbl (
             id1 INT PRIMARY KEY,
             id21 INTEGER,
@@ -115,7 +293,7 @@ mod tests {
         );";
 
         let tokens = Lexer::new(input)
-            .peekable().collect::<Vec<_>>();
+            .collect::<Result<Vec<_>>>()?;
 
         assert_eq!(tokens, vec![
             Token::Keyword(Keyword::Create),
@@ -145,15 +323,17 @@ mod tests {
             Token::String("abc".to_string()),
             Token::Symbol(Symbol::CloseParen),
             Token::Symbol(Symbol::Semicolon),
-        ])
+        ]);
+
+        Ok(())
     }
 
     #[test]
-    fn test_insert() {
+    fn test_insert() -> Result<()> {
         let input = "INSERT INTO tbl (id1, id2, c1, c2, c3) VALUES (1, 2, true, 3.14, 'abc');";
 
         let tokens = Lexer::new(input)
-            .peekable().collect::<Vec<_>>();
+            .collect::<Result<Vec<_>>>()?;
 
         assert_eq!(tokens, vec![
             Token::Keyword(Keyword::Insert),
@@ -183,14 +363,25 @@ mod tests {
             Token::String("abc".to_string()),
             Token::Symbol(Symbol::CloseParen),
             Token::Symbol(Symbol::Semicolon),
-        ])
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_escape() -> Result<()> {
+        let tokens = Lexer::new("'it''s' '''' ''").collect::<Result<Vec<_>>>()?;
+        assert_eq!(tokens, vec![Token::String("it's".to_string()), Token::String("'".to_string()), Token::String("".to_string())]);
+
+        assert!(Lexer::new("'it''").collect::<Result<Vec<_>>>().is_err());
+
+        Ok(())
     }
 
     #[test]
-    fn test_select() {
+    fn test_select() -> Result<()> {
         let tokens1 = Lexer::new("select * from tbl;")
-            .peekable()
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>>>()?;
 
         assert_eq!(
             tokens1,
@@ -202,5 +393,270 @@ mod tests {
                 Token::Symbol(Symbol::Semicolon),
             ]
         );
+
+        Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_comment() -> Result<()> {
+        let tokens = Lexer::new("-- leading note\nselect * -- columns\nfrom users; -- trailing note")
+            .collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Keyword(Keyword::Select),
+            Token::Symbol(Symbol::Asterisk),
+            Token::Keyword(Keyword::From),
+            Token::Ident("users".to_string()),
+            Token::Symbol(Symbol::Semicolon),
+        ]);
+
+        assert!(Lexer::new("--").next().is_none());
+
+        let tokens = Lexer::new("1 -2").collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Number("1".to_string()),
+            Token::Symbol(Symbol::Minus),
+            Token::Number("2".to_string()),
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_radix_number() -> Result<()> {
+        let tokens = Lexer::new("0x1F 0XfF+0b1010 0B0 0x0 0.5 0").collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Number("31".to_string()),
+            Token::Number("255".to_string()),
+            Token::Symbol(Symbol::Plus),
+            Token::Number("10".to_string()),
+            Token::Number("0".to_string()),
+            Token::Number("0".to_string()),
+            Token::Number("0.5".to_string()),
+            Token::Number("0".to_string()),
+        ]);
+        assert_eq!(Lexer::new("0xFFFFFFFFFFFFFFFF").next().unwrap()?, Token::Number(u64::MAX.to_string()));
+
+        for (input, err) in [
+            ("0xG", "Invalid hexadecimal literal: 0xG"),
+            ("0x", "Invalid hexadecimal literal: 0x"),
+            ("0x1g", "Invalid hexadecimal literal: 0x1g"),
+            ("0b102", "Invalid binary literal: 0b102"),
+            ("0b", "Invalid binary literal: 0b"),
+            ("0x10000000000000000", "Integer literal 0x10000000000000000 out of range"),
+        ] {
+            assert_eq!(Lexer::new(input).next().unwrap().unwrap_err().to_string(), err);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_operator() -> Result<()> {
+        let tokens = Lexer::new("a<=b").collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Ident("a".to_string()),
+            Token::Symbol(Symbol::LessThanOrEqual),
+            Token::Ident("b".to_string()),
+        ]);
+
+        let tokens = Lexer::new("= < > <= >= <> != <>=").collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Symbol(Symbol::Equal),
+            Token::Symbol(Symbol::LessThan),
+            Token::Symbol(Symbol::GreaterThan),
+            Token::Symbol(Symbol::LessThanOrEqual),
+            Token::Symbol(Symbol::GreaterThanOrEqual),
+            Token::Symbol(Symbol::NotEqual),
+            Token::Symbol(Symbol::NotEqual),
+            Token::Symbol(Symbol::NotEqual),
+            Token::Symbol(Symbol::Equal),
+        ]);
+
+        let tokens = Lexer::new("a%2").collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Ident("a".to_string()),
+            Token::Symbol(Symbol::Percent),
+            Token::Number("2".to_string()),
+        ]);
+
+        let tokens = Lexer::new("a||'b' || c").collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Ident("a".to_string()),
+            Token::Symbol(Symbol::Concat),
+            Token::String("b".to_string()),
+            Token::Symbol(Symbol::Concat),
+            Token::Ident("c".to_string()),
+        ]);
+
+        assert_eq!(Lexer::new("!").next().unwrap().unwrap_err().to_string(), "Unknown symbol: !");
+        assert_eq!(Lexer::new("|").next().unwrap().unwrap_err().to_string(), "Unknown symbol: |");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_placeholder() -> Result<()> {
+        let tokens = Lexer::new("a = ? and b in ($2, $10)").collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Ident("a".to_string()),
+            Token::Symbol(Symbol::Equal),
+            Token::Placeholder(None),
+            Token::Keyword(Keyword::And),
+            Token::Ident("b".to_string()),
+            Token::Keyword(Keyword::In),
+            Token::Symbol(Symbol::OpenParen),
+            Token::Placeholder(Some(2)),
+            Token::Symbol(Symbol::Comma),
+            Token::Placeholder(Some(10)),
+            Token::Symbol(Symbol::CloseParen),
+        ]);
+
+        assert_eq!(Lexer::new("$").next().unwrap().unwrap_err().to_string(), "Invalid placeholder: $");
+        assert_eq!(Lexer::new("$0").next().unwrap().unwrap_err().to_string(), "Invalid placeholder: $0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_underscore_ident() -> Result<()> {
+        let tokens = Lexer::new("user_id _tmp a1_b2 created_at from").collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Ident("user_id".to_string()),
+            Token::Ident("_tmp".to_string()),
+            Token::Ident("a1_b2".to_string()),
+            Token::Ident("created_at".to_string()),
+            Token::Keyword(Keyword::From),
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dot() -> Result<()> {
+        let tokens = Lexer::new("a.b 3.14 .5").collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Ident("a".to_string()),
+            Token::Symbol(Symbol::Dot),
+            Token::Ident("b".to_string()),
+            Token::Number("3.14".to_string()),
+            Token::Number(".5".to_string()),
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_error() {
+        let mut lexer = Lexer::new("select 'abc");
+        assert_eq!(lexer.next().unwrap().unwrap(), Token::Keyword(Keyword::Select));
+        assert_eq!(lexer.next().unwrap().unwrap_err().to_string(), "Unterminated string: 'abc");
+
+        let mut lexer = Lexer::new("a @ 1");
+        assert_eq!(lexer.next().unwrap().unwrap(), Token::Ident("a".to_string()));
+        assert_eq!(lexer.next().unwrap().unwrap_err().to_string(), "Unknown symbol: @");
+
+        assert!(Lexer::new("  ").next().is_none());
+    }
+
+    #[test]
+    fn test_ident_case() -> Result<()> {
+        let tokens = Lexer::new(r#"Users USERS users "Users" "select" "a b""#).collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Ident("users".to_string()),
+            Token::Ident("users".to_string()),
+            Token::Ident("users".to_string()),
+            Token::Ident("Users".to_string()),
+            Token::Ident("select".to_string()),
+            Token::Ident("a b".to_string()),
+        ]);
+
+        assert_eq!(Lexer::new(r#""abc"#).next().unwrap().unwrap_err().to_string(), r#"Unterminated identifier: "abc"#);
+        assert_eq!(Lexer::new("`a\"b").next().unwrap().unwrap_err().to_string(), r#"Unterminated identifier: `a"b"#);
+        assert_eq!(Lexer::new(r#""""#).next().unwrap().unwrap_err().to_string(), "Empty quoted identifier");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quoted_keyword() -> Result<()> {
+        let tokens = Lexer::new(r#"order "order" `order` `Table` "key""#).collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(tokens, vec![
+            Token::Keyword(Keyword::Order),
+            Token::Ident("order".to_string()),
+            Token::Ident("order".to_string()),
+            Token::Ident("Table".to_string()),
+            Token::Ident("key".to_string()),
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_keywords() -> Result<()> {
+        let keywords = [("bigint", Keyword::Int), ("MINUS", Keyword::Union), ("SELECT", Keyword::Insert)];
+        let tokens = Lexer::with_keywords("BigInt minus select bigint_col \"bigint\"", keywords).collect::<Result<Vec<_>>>()?;
+
+        // 自定义关键字优先, 带引号的仍为标识符
+        assert_eq!(tokens, vec![
+            Token::Keyword(Keyword::Int),
+            Token::Keyword(Keyword::Union),
+            Token::Keyword(Keyword::Insert),
+            Token::Ident("bigint_col".to_string()),
+            Token::Ident("bigint".to_string()),
+        ]);
+
+        assert_eq!(Lexer::new("bigint").collect::<Result<Vec<_>>>()?, vec![Token::Ident("bigint".to_string())]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recovery() -> Result<()> {
+        let tokens = Lexer::new("a ! b @c $ d").with_recovery().collect::<Result<Vec<_>>>()?;
+
+        // 出错后跳到下一个空白处
+        assert_eq!(tokens, vec![
+            Token::Ident("a".to_string()),
+            Token::Error { message: "Unknown symbol: !".to_string(), position: 2 },
+            Token::Ident("b".to_string()),
+            Token::Error { message: "Unknown symbol: @".to_string(), position: 6 },
+            Token::Error { message: "Invalid placeholder: $".to_string(), position: 9 },
+            Token::Ident("d".to_string()),
+        ]);
+
+        assert!(Lexer::new("a ! b").collect::<Result<Vec<_>>>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_spanned() -> Result<()> {
+        let mut lexer = Lexer::new("SELECT * FROM t");
+
+        assert_eq!(lexer.scan_spanned()?, Some((Token::Keyword(Keyword::Select), 0..6)));
+        assert_eq!(lexer.scan_spanned()?, Some((Token::Symbol(Symbol::Asterisk), 7..8)));
+        assert_eq!(lexer.scan_spanned()?, Some((Token::Keyword(Keyword::From), 9..13)));
+        assert_eq!(lexer.scan_spanned()?, Some((Token::Ident("t".to_string()), 14..15)));
+        assert_eq!(lexer.scan_spanned()?, None);
+
+        // 跳过注释, 多字节字符按字节计算, 与迭代器混用
+        let mut lexer = Lexer::new("-- 注释\n'é' <= \"名\"");
+        assert_eq!(lexer.scan_spanned()?, Some((Token::String("é".to_string()), 10..14)));
+        assert_eq!(lexer.next().transpose()?, Some(Token::Symbol(Symbol::LessThanOrEqual)));
+        assert_eq!(lexer.scan_spanned()?, Some((Token::Ident("名".to_string()), 18..23)));
+
+        Ok(())
+    }
+}
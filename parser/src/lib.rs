@@ -1,20 +1,25 @@
 use std::iter::Peekable;
-use common::ast::{Column, Const, Expression, Statement};
-use crate::lexer::Lexer;
+use common::ast::{AggregateFunction, AlterOperation, Column, Const, Expression, FromItem, Operator, Position, Projection, ScalarFunction, Statement, UnaryOperator};
+pub use common::ast::to_sql;
+use crate::lexer::{Lexer, Spanned};
 use anyhow::{anyhow, bail, Result};
-use crate::token::{Keyword, Symbol, Token};
-use common::types::DataType;
+use common::types::{Collation, DataType, Decimal};
 
+mod error;
 mod lexer;
 mod token;
 
+pub use error::{is_incomplete, ParseError};
+pub use token::{Keyword, Symbol, Token};
+
 /// 语法分析
 /// support sql:
 /// 1.
 /// ```sql
-/// CREATE TABLE table_name (
+/// CREATE TABLE [IF NOT EXISTS] table_name (
 /// [ column_name data_type[column_constraint [...]]
 /// [, ...]
+/// [, PRIMARY KEY (column_name [, ...])]
 /// );
 ///
 /// ```
@@ -22,63 +27,503 @@ mod token;
 /// 2.
 /// ```sql
 /// INSERT INTO table_name (column1, column2,...)
-/// VALUES (value1, value2,...);
+/// VALUES ({ value | DEFAULT }, ...) [, ...] [RETURNING { * | expression [AS alias] [, ...] }];
 /// ```
 ///
 /// 3.
 /// ```sql
-/// SELECT * FROM table_name;
+/// SELECT [DISTINCT] { * | expression [AS alias] [, ...] }
+/// FROM table_name [{ [INNER] JOIN table_name ON condition | , table_name } [...]] [WHERE condition]
+/// [GROUP BY expression [, ...]] [HAVING condition]
+/// [ORDER BY expression [ASC | DESC] [, ...]] [LIMIT count] [OFFSET start];
+/// ```
+///
+/// 4.
+/// ```sql
+/// UPDATE table_name SET column_name = expression [, ...] [WHERE condition] [RETURNING ...];
+/// ```
+///
+/// 5.
+/// ```sql
+/// DELETE FROM table_name [WHERE condition] [RETURNING ...];
+/// ```
+///
+/// 6.
+/// ```sql
+/// DROP TABLE [IF EXISTS] table_name;
+/// ```
+///
+/// 7.
+/// ```sql
+/// EXPLAIN statement;
+/// ```
+///
+/// 8.
+/// ```sql
+/// ALTER TABLE table_name ADD [COLUMN] column_name data_type[column_constraint [...]];
+/// ALTER TABLE table_name DROP [COLUMN] column_name;
+/// ```
+///
+/// 9.
+/// ```sql
+/// TRUNCATE TABLE table_name;
+/// ```
+///
+/// 10.
+/// ```sql
+/// select_statement UNION [ALL] select_statement [...];
 /// ```
 pub struct Parser<'a> {
-    lexer: Peekable<Lexer<'a>>,
+    input: &'a str,
+    lexer: Peekable<Spanned<'a>>,
+    // 当前语句中已出现的 ? 个数, 用于给 ? 依次编号
+    placeholders: usize,
+    // 标识符的最大字符数
+    max_ident_length: usize,
+    // 自定义关键字, 重建词法分析器时使用
+    keywords: Vec<(String, Keyword)>,
+    // 上一个消耗的 token 是分号, 出错恢复时据此判断语句是否已结束
+    after_semicolon: bool,
+    // 超出 i64 范围的整数字面量转为浮点数, 否则报错
+    lenient_integers: bool,
+    // parse_all 中单独成行的批分隔符, 如 GO
+    batch_separator: Option<String>,
+    // 已解析的比较表达式及其起始位置
+    positions: Vec<(Expression, Position)>,
 }
 
 impl<'a> Parser<'a> {
+    pub const DEFAULT_MAX_IDENT_LENGTH: usize = 64;
+
     pub fn new(input: &'a str) -> Self {
         Self {
-            lexer: Lexer::new(input).peekable(),
+            input,
+            lexer: Spanned(Lexer::new(input)).peekable(),
+            placeholders: 0,
+            max_ident_length: Self::DEFAULT_MAX_IDENT_LENGTH,
+            keywords: vec![],
+            after_semicolon: false,
+            lenient_integers: false,
+            batch_separator: None,
+            positions: vec![],
         }
     }
 
+    pub fn with_max_ident_length(mut self, max_ident_length: usize) -> Self {
+        self.max_ident_length = max_ident_length;
+        self
+    }
+
+    pub fn with_lenient_integers(mut self, lenient_integers: bool) -> Self {
+        self.lenient_integers = lenient_integers;
+        self
+    }
+
+    // 自定义关键字, 用于兼容其他方言, 见 Lexer::with_keywords
+    pub fn with_keywords<S: Into<String>>(mut self, keywords: impl IntoIterator<Item=(S, Keyword)>) -> Self {
+        self.keywords = keywords.into_iter().map(|(name, keyword)| (name.into(), keyword)).collect();
+        self.lexer = Spanned(Lexer::with_keywords(self.input, self.keywords.clone())).peekable();
+        self
+    }
+
+    // 除分号外, 单独成一行的 separator (忽略大小写及首尾空白) 也结束语句, 用于执行其他工具导出的脚本
+    pub fn with_batch_separator(mut self, separator: impl Into<String>) -> Self {
+        self.batch_separator = Some(separator.into());
+        self
+    }
+
     pub fn parse(&mut self) -> Result<Statement> {
         let stmt = self.parse_statement()?;
 
-        // 语句后面必须是分号
-        self.next_expect(&Token::Symbol(Symbol::Semicolon))?;
+        self.parse_terminator()?;
 
         // 分号之后还有东西
-        if let Ok(token) = self.peek() {
-            bail!("Unexpected token: {:?}", token);
+        if let Some(token) = self.lexer.next() {
+            bail!(ParseError::UnexpectedToken(token?.0));
         }
 
         Ok(stmt)
     }
 
+    // 已解析的比较表达式 (如 a = 1) 及其在输入中的起始位置, 按解析顺序排列
+    // 规划时据此标明警告的位置, 见 Planner::with_positions
+    pub fn positions(&self) -> &[(Expression, Position)] {
+        &self.positions
+    }
+
+    // 同 parse_all, 但出错时不中断: 记录错误并跳过该语句余下的 token, 从下一条语句继续
+    // 词法错误不中断扫描, 同一语句中的多个词法错误都会报告; 有错误时按出现顺序返回全部错误
+    pub fn parse_with_recovery(&mut self) -> std::result::Result<Vec<Statement>, Vec<ParseError>> {
+        self.lexer = Spanned(Lexer::with_keywords(self.input, self.keywords.clone()).with_recovery()).peekable();
+
+        let mut stmts = vec![];
+        let mut errors = vec![];
+
+        while self.lexer.peek().is_some() {
+            self.placeholders = 0;
+            self.after_semicolon = false;
+            match self.parse_statement().and_then(|stmt| self.parse_terminator().map(|_| stmt)) {
+                Ok(stmt) => stmts.push(stmt),
+                Err(e) => {
+                    errors.push(match e.downcast_ref::<ParseError>() {
+                        // 带有上下文的错误保留完整信息
+                        Some(err) if e.chain().count() == 1 => err.clone(),
+                        _ => ParseError::Syntax(e.to_string()),
+                    });
+                    self.skip_statement(&mut errors);
+                }
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(stmts),
+            false => Err(errors),
+        }
+    }
+
+    // 跳到分号之后, 途经的词法错误计入 errors, 出错处未消耗的错误 token 不重复计入
+    fn skip_statement(&mut self, errors: &mut Vec<ParseError>) {
+        if self.after_semicolon {
+            return;
+        }
+
+        for token in self.lexer.by_ref() {
+            match token.map(|(token, _)| token) {
+                Ok(Token::Symbol(Symbol::Semicolon)) => break,
+                Ok(Token::Error { message, position }) => {
+                    let err = ParseError::InvalidToken { message, position };
+                    if errors.last() != Some(&err) {
+                        errors.push(err);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // 解析以分号结尾的多条语句, 错误信息标明出错的是第几条语句
+    // 设置了批分隔符时先按分隔行拆分, 每批的最后一条语句可省略分号
+    pub fn parse_all(&mut self) -> Result<Vec<Statement>> {
+        let mut stmts = vec![];
+
+        match self.batch_separator.clone() {
+            Some(separator) => {
+                for (line, batch) in Self::split_batches(self.input, &separator) {
+                    let mut parser = Parser::new(batch)
+                        .with_max_ident_length(self.max_ident_length)
+                        .with_lenient_integers(self.lenient_integers)
+                        .with_keywords(self.keywords.clone());
+                    let result = parser.parse_statements(&mut stmts);
+                    // 每批从新的一行开始, 行号加上之前的行数
+                    self.positions.extend(parser.positions.into_iter().map(|(expr, position)| (expr, Position { line: position.line + line, ..position })));
+                    result?;
+                }
+                // 与不分批时一致, 解析后输入已消耗完
+                self.lexer = Spanned(Lexer::new("")).peekable();
+            }
+            None => self.parse_statements(&mut stmts)?,
+        }
+
+        Ok(stmts)
+    }
+
+    // 按单独成行的分隔符拆分输入, 不识别跨行字符串中的分隔行
+    // 同时返回每批之前的行数
+    fn split_batches<'s>(input: &'s str, separator: &str) -> Vec<(usize, &'s str)> {
+        let mut batches = vec![];
+        let (mut start, mut offset, mut start_line) = (0, 0, 0);
+        for (i, line) in input.split_inclusive('\n').enumerate() {
+            if line.trim().eq_ignore_ascii_case(separator) {
+                batches.push((start_line, &input[start..offset]));
+                start = offset + line.len();
+                start_line = i + 1;
+            }
+            offset += line.len();
+        }
+        batches.push((start_line, &input[start..]));
+
+        batches
+    }
+
+    // 依次解析语句追加到 stmts, 编号接着已有的语句
+    fn parse_statements(&mut self, stmts: &mut Vec<Statement>) -> Result<()> {
+        while self.lexer.peek().is_some() {
+            self.placeholders = 0;
+            let stmt = self.parse_statement()
+                .and_then(|stmt| self.parse_terminator().map(|_| stmt))
+                .map_err(|e| {
+                    let msg = format!("Statement {}: {}", stmts.len() + 1, e);
+                    e.context(msg)
+                })?;
+            stmts.push(stmt);
+        }
+
+        Ok(())
+    }
+
+    // 语句以分号结尾, 输入结束时可省略
+    fn parse_terminator(&mut self) -> Result<()> {
+        if self.lexer.peek().is_some() {
+            self.next_expect(&Token::Symbol(Symbol::Semicolon))?;
+        }
+
+        Ok(())
+    }
+
     fn parse_statement(&mut self) -> Result<Statement> {
         match self.peek()? {
-            Token::Keyword(Keyword::Create) => self.parse_ddl(),
-            Token::Keyword(Keyword::Select) => self.parse_select(),
+            Token::Keyword(Keyword::Create) | Token::Keyword(Keyword::Drop) | Token::Keyword(Keyword::Alter)
+            | Token::Keyword(Keyword::Truncate) => self.parse_ddl(),
+            Token::Keyword(Keyword::Select) => self.parse_query(),
             Token::Keyword(Keyword::Insert) => self.parse_insert(),
-            token => bail!("Unexpected token: {:?}", token),
+            Token::Keyword(Keyword::Update) => self.parse_update(),
+            Token::Keyword(Keyword::Delete) => self.parse_delete(),
+            Token::Keyword(Keyword::Explain) => {
+                self.next()?;
+                Ok(Statement::Explain(Box::new(self.parse_statement()?)))
+            }
+            token => bail!(ParseError::UnexpectedToken(token.clone())),
         }
     }
 
     fn parse_ddl(&mut self) -> Result<Statement> {
         match (self.next()?, self.next()?) {
             (Token::Keyword(Keyword::Create), Token::Keyword(Keyword::Table)) => self.parse_ddl_create_table(),
+            (Token::Keyword(Keyword::Drop), Token::Keyword(Keyword::Table)) => self.parse_ddl_drop_table(),
+            (Token::Keyword(Keyword::Alter), Token::Keyword(Keyword::Table)) => self.parse_ddl_alter_table(),
+            (Token::Keyword(Keyword::Truncate), Token::Keyword(Keyword::Table)) => Ok(Statement::Truncate { table_name: self.next_ident()? }),
             (token1, token2) => bail!("Not a ddl statement: {:?}, {:?}", token1, token2),
         }
     }
 
+    // select ... [UNION [ALL] select ...]
+    fn parse_query(&mut self) -> Result<Statement> {
+        let mut stmt = self.parse_select()?;
+
+        while self.next_expect(&Token::Keyword(Keyword::Union)).is_ok() {
+            let all = self.next_expect(&Token::Keyword(Keyword::All)).is_ok();
+            stmt = Statement::Union { left: Box::new(stmt), right: Box::new(self.parse_select()?), all };
+        }
+
+        Ok(stmt)
+    }
+
     fn parse_select(&mut self) -> Result<Statement> {
-        // select * from
+        // select * from / select a, b from
         self.next_expect(&Token::Keyword(Keyword::Select))?;
-        self.next_expect(&Token::Symbol(Symbol::Asterisk))?;
+
+        let distinct = self.next_expect(&Token::Keyword(Keyword::Distinct)).is_ok();
+
+        let projection = self.parse_projection()?;
+
+        self.next_expect(&Token::Keyword(Keyword::From))?;
+
+        let from = self.parse_from()?;
+
+        let filter = self.parse_where()?;
+
+        let (group_by, having) = self.parse_group_by()?;
+
+        let order_by = self.parse_order_by()?;
+
+        let limit = self.parse_count(Keyword::Limit)?;
+        let offset = self.parse_count(Keyword::Offset)?;
+
+        Ok(Statement::Select { distinct, projection, from, filter, group_by, having, order_by, limit, offset })
+    }
+
+    // * 或 expr [AS alias] [, ...]
+    fn parse_projection(&mut self) -> Result<Projection> {
+        if self.next_expect(&Token::Symbol(Symbol::Asterisk)).is_ok() {
+            return Ok(Projection::All);
+        }
+
+        let mut cols = vec![];
+
+        loop {
+            let expr = self.parse_expression()?;
+            let alias = match self.next_expect(&Token::Keyword(Keyword::As)) {
+                Ok(_) => Some(self.next_ident()?),
+                Err(_) => None,
+            };
+            cols.push((expr, alias));
+
+            if self.next_expect(&Token::Symbol(Symbol::Comma)).is_err() {
+                break;
+            }
+        }
+
+        Ok(Projection::Columns(cols))
+    }
+
+    // [RETURNING projection]
+    fn parse_returning(&mut self) -> Result<Option<Projection>> {
+        if self.next_expect(&Token::Keyword(Keyword::Returning)).is_err() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.parse_projection()?))
+    }
+
+    // table_name [[INNER] JOIN table_name ON condition | , table_name [...]], 左深连接
+    // 逗号分隔的表为无条件的交叉连接
+    fn parse_from(&mut self) -> Result<FromItem> {
+        let mut from = self.parse_from_item()?;
+
+        loop {
+            if self.next_expect(&Token::Symbol(Symbol::Comma)).is_ok() {
+                let right = self.parse_from_item()?;
+                from = FromItem::Join { left: Box::new(from), right: Box::new(right), on: None };
+                continue;
+            }
+
+            let inner = self.next_expect(&Token::Keyword(Keyword::Inner)).is_ok();
+            if self.next_expect(&Token::Keyword(Keyword::Join)).is_err() {
+                if inner {
+                    bail!("Expected {:?} after INNER, got {:?}", Keyword::Join, self.peek()?);
+                }
+                break;
+            }
+
+            let right = self.parse_from_item()?;
+            self.next_expect(&Token::Keyword(Keyword::On))?;
+            let on = Some(self.parse_expression()?);
+
+            from = FromItem::Join { left: Box::new(from), right: Box::new(right), on };
+        }
+
+        Ok(from)
+    }
+
+    // table_name 或 (select ...) [AS] alias, 派生表必须有别名
+    fn parse_from_item(&mut self) -> Result<FromItem> {
+        if self.next_expect(&Token::Symbol(Symbol::OpenParen)).is_err() {
+            return Ok(FromItem::Table(self.next_ident()?));
+        }
+
+        let query = self.parse_query()?;
+        self.next_expect(&Token::Symbol(Symbol::CloseParen))?;
+
+        let has_as = self.next_expect(&Token::Keyword(Keyword::As)).is_ok();
+        let alias = match self.peek() {
+            Ok(Token::Ident(_)) => self.next_ident()?,
+            _ if has_as => self.next_ident()?,
+            _ => bail!("Subquery in FROM must have an alias"),
+        };
+
+        Ok(FromItem::Subquery { query: Box::new(query), alias })
+    }
+
+    // [LIMIT n] / [OFFSET n], n 须为非负整数
+    fn parse_count(&mut self, keyword: Keyword) -> Result<Option<u64>> {
+        let keyword = Token::Keyword(keyword);
+
+        if self.next_expect(&keyword).is_err() {
+            return Ok(None);
+        }
+
+        match self.next()? {
+            Token::Number(n) if n.chars().all(|c| c.is_ascii_digit()) => Ok(Some(n.parse()?)),
+            token => bail!("{:?} expects a non-negative integer, got {:?}", keyword, token),
+        }
+    }
+
+    // [GROUP BY expr [, ...]] [HAVING condition]
+    fn parse_group_by(&mut self) -> Result<(Vec<Expression>, Option<Expression>)> {
+        let mut keys = vec![];
+
+        if self.next_expect(&Token::Keyword(Keyword::Group)).is_ok() {
+            self.next_expect(&Token::Keyword(Keyword::By))?;
+
+            loop {
+                keys.push(self.parse_expression()?);
+
+                if self.next_expect(&Token::Symbol(Symbol::Comma)).is_err() {
+                    break;
+                }
+            }
+        }
+
+        let having = match self.next_expect(&Token::Keyword(Keyword::Having)) {
+            Ok(_) => Some(self.parse_expression()?),
+            Err(_) => None,
+        };
+
+        Ok((keys, having))
+    }
+
+    // [ORDER BY expr [ASC | DESC] [, ...]], 默认升序
+    fn parse_order_by(&mut self) -> Result<Vec<(Expression, bool)>> {
+        let mut keys = vec![];
+
+        if self.next_expect(&Token::Keyword(Keyword::Order)).is_err() {
+            return Ok(keys);
+        }
+        self.next_expect(&Token::Keyword(Keyword::By))?;
+
+        loop {
+            let expr = self.parse_expression()?;
+
+            let desc = self.next_expect(&Token::Keyword(Keyword::Desc)).is_ok();
+            if !desc {
+                self.next_expect(&Token::Keyword(Keyword::Asc)).ok();
+            }
+            keys.push((expr, desc));
+
+            if self.next_expect(&Token::Symbol(Symbol::Comma)).is_err() {
+                break;
+            }
+        }
+
+        Ok(keys)
+    }
+
+    fn parse_update(&mut self) -> Result<Statement> {
+        // update tbl set a = 1, b = a + 1 where id = 2
+        self.next_expect(&Token::Keyword(Keyword::Update))?;
+
+        let table_name = self.next_ident()?;
+
+        self.next_expect(&Token::Keyword(Keyword::Set))?;
+
+        let mut assignments = vec![];
+
+        loop {
+            let column = self.next_ident()?;
+            self.next_expect(&Token::Symbol(Symbol::Equal))?;
+            assignments.push((column, self.parse_expression()?));
+
+            if self.next_expect(&Token::Symbol(Symbol::Comma)).is_err() {
+                break;
+            }
+        }
+
+        let filter = self.parse_where()?;
+        let returning = self.parse_returning()?;
+
+        Ok(Statement::Update { table_name, assignments, filter, returning })
+    }
+
+    fn parse_delete(&mut self) -> Result<Statement> {
+        // delete from tbl where id = 1, 无 where 时删除所有行
+        self.next_expect(&Token::Keyword(Keyword::Delete))?;
         self.next_expect(&Token::Keyword(Keyword::From))?;
 
         let table_name = self.next_ident()?;
 
-        Ok(Statement::Select { table_name })
+        let filter = self.parse_where()?;
+        let returning = self.parse_returning()?;
+
+        Ok(Statement::Delete { table_name, filter, returning })
+    }
+
+    // [WHERE condition]
+    fn parse_where(&mut self) -> Result<Option<Expression>> {
+        if self.next_expect(&Token::Keyword(Keyword::Where)).is_err() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.parse_expression()?))
     }
 
     fn parse_insert(&mut self) -> Result<Statement> {
@@ -97,7 +542,7 @@ impl<'a> Parser<'a> {
                 match self.next()? {
                     Token::Symbol(Symbol::CloseParen) => break,
                     Token::Symbol(Symbol::Comma) => continue,
-                    token => bail!("Unexpected token: {:?}", token),
+                    token => bail!(ParseError::UnexpectedToken(token)),
                 }
             }
             Some(cols)
@@ -110,71 +555,202 @@ impl<'a> Parser<'a> {
         let mut values = vec![];
 
         loop {
-            self.next_expect(&Token::Symbol(Symbol::OpenParen))?;
+            values.push(self.parse_insert_values()?);
 
-            let mut exprs = vec![];
+            if self.next_expect(&Token::Symbol(Symbol::Comma)).is_err() {
+                break;
+            }
+        }
 
-            loop {
-                exprs.push(self.parse_expression()?);
+        let returning = self.parse_returning()?;
 
-                match self.next()? {
-                    Token::Symbol(Symbol::CloseParen) => break,
-                    Token::Symbol(Symbol::Comma) => continue,
-                    token => bail!("Unexpected token: {:?}", token),
-                }
-            }
-            values.push(exprs);
+        Ok(Statement::Insert { table_name, columns, values, returning })
+    }
 
-            if self.next_expect(&Token::Symbol(Symbol::Comma)).is_err() {
-                break;
+    // (value, ...), 值可为 DEFAULT
+    fn parse_insert_values(&mut self) -> Result<Vec<Expression>> {
+        self.next_expect(&Token::Symbol(Symbol::OpenParen))?;
+
+        let mut values = vec![];
+
+        loop {
+            values.push(match self.next_expect(&Token::Keyword(Keyword::Default)) {
+                Ok(_) => Expression::Default,
+                Err(_) => self.parse_expression()?,
+            });
+
+            match self.next()? {
+                Token::Symbol(Symbol::CloseParen) => break,
+                Token::Symbol(Symbol::Comma) => continue,
+                token => bail!(ParseError::UnexpectedToken(token)),
             }
         }
 
-        Ok(Statement::Insert { table_name, columns, values })
+        Ok(values)
     }
 
     fn parse_ddl_create_table(&mut self) -> Result<Statement> {
+        let if_not_exists = if self.next_expect(&Token::Keyword(Keyword::If)).is_ok() {
+            self.next_expect(&Token::Keyword(Keyword::Not))?;
+            self.next_expect(&Token::Keyword(Keyword::Exists))?;
+            true
+        } else {
+            false
+        };
+
         let table_name = self.next_ident()?;
 
         self.next_expect(&Token::Symbol(Symbol::OpenParen))?;
 
         let mut columns = vec![];
+        let mut primary_key = vec![];
 
         loop {
-            columns.push(self.parse_ddl_column()?);
+            let after = if self.next_expect(&Token::Keyword(Keyword::Primary)).is_ok() {
+                if !primary_key.is_empty() {
+                    bail!("Multiple primary keys for table {}", table_name);
+                }
+                primary_key = self.parse_ddl_primary_key()?;
+                "PRIMARY KEY".to_string()
+            } else {
+                let column = self.parse_ddl_column()?;
+                let after = format!("column {}", column.name);
+                columns.push(column);
+                after
+            };
 
-            if self.next_expect(&Token::Symbol(Symbol::Comma)).is_err() {
-                break;
+            // 每项之后须为 ',' 或 ')', 指出最后一项以便定位被截断或漏写逗号的位置
+            match self.peek() {
+                Ok(Token::Symbol(Symbol::Comma)) => self.next()?,
+                Ok(Token::Symbol(Symbol::CloseParen)) => {
+                    self.next()?;
+                    break;
+                }
+                Ok(token) => bail!(ParseError::UnterminatedColumnList { after, found: Some(token.clone()) }),
+                Err(e) if is_incomplete(&e) => bail!(ParseError::UnterminatedColumnList { after, found: None }),
+                Err(e) => return Err(e),
+            };
+        }
+
+        if let Some(name) = primary_key.iter().find(|&name| !columns.iter().any(|col| &col.name == name)) {
+            bail!("Primary key column {} does not exist", name);
+        }
+
+        let comment = match self.next_expect(&Token::Keyword(Keyword::Comment)) {
+            Ok(_) => Some(self.parse_comment()?),
+            Err(_) => None,
+        };
+
+        Ok(Statement::Create { table_name, columns, if_not_exists, primary_key, comment })
+    }
+
+    // COMMENT 'text', COMMENT 已消耗
+    fn parse_comment(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::String(comment) => Ok(comment),
+            token => bail!("Expected comment string, got {:?}", token),
+        }
+    }
+
+    // 表级主键 PRIMARY KEY (a, b), PRIMARY 已消耗
+    fn parse_ddl_primary_key(&mut self) -> Result<Vec<String>> {
+        self.next_expect(&Token::Keyword(Keyword::Key))?;
+        self.next_expect(&Token::Symbol(Symbol::OpenParen))?;
+
+        let mut cols = vec![];
+
+        loop {
+            cols.push(self.next_ident()?);
+
+            match self.next()? {
+                Token::Symbol(Symbol::CloseParen) => break,
+                Token::Symbol(Symbol::Comma) => continue,
+                token => bail!(ParseError::UnexpectedToken(token)),
             }
         }
 
-        self.next_expect(&Token::Symbol(Symbol::CloseParen))?;
+        Ok(cols)
+    }
+
+    fn parse_ddl_drop_table(&mut self) -> Result<Statement> {
+        let if_exists = if self.next_expect(&Token::Keyword(Keyword::If)).is_ok() {
+            self.next_expect(&Token::Keyword(Keyword::Exists))?;
+            true
+        } else {
+            false
+        };
+
+        let table_name = self.next_ident()?;
+
+        Ok(Statement::Drop { table_name, if_exists })
+    }
 
-        Ok(Statement::Create { table_name, columns })
+    fn parse_ddl_alter_table(&mut self) -> Result<Statement> {
+        let table_name = self.next_ident()?;
+
+        let op = match self.next()? {
+            Token::Keyword(Keyword::Add) => {
+                self.next_expect(&Token::Keyword(Keyword::Column)).ok();
+                AlterOperation::AddColumn(self.parse_ddl_column()?)
+            }
+            Token::Keyword(Keyword::Drop) => {
+                self.next_expect(&Token::Keyword(Keyword::Column)).ok();
+                AlterOperation::DropColumn(self.next_ident()?)
+            }
+            token => bail!(ParseError::UnexpectedToken(token)),
+        };
+
+        Ok(Statement::AlterTable { table_name, op })
     }
 
     fn parse_ddl_column(&mut self) -> Result<Column> {
+        let name = self.next_ident()?;
+        let (data_type, max_length) = self.parse_data_type()?;
         let mut col = Column {
-            name: self.next_ident()?,
-            data_type: match self.next()? {
-                Token::Keyword(Keyword::Integer) | Token::Keyword(Keyword::Int) => DataType::Integer,
-                Token::Keyword(Keyword::Bool) | Token::Keyword(Keyword::Boolean) => DataType::Boolean,
-                Token::Keyword(Keyword::Float) => DataType::Float,
-                Token::Keyword(Keyword::String) | Token::Keyword(Keyword::Text) | Token::Keyword(Keyword::Varchar) => DataType::String,
-                token => bail!("Unexpected token: {:?}", token),
-            },
+            name,
+            data_type,
+            max_length,
             nullable: None,
             default: None,
+            primary_key: false,
+            unique: false,
+            check: None,
+            auto_increment: false,
+            collation: None,
+            comment: None,
         };
 
-        while let Some(Token::Keyword(keyword)) = self.lexer.next_if(|token| matches!(token, Token::Keyword(_))) {
+        while let Some(Ok((Token::Keyword(keyword), _))) = self.lexer.next_if(|token| matches!(token, Ok((Token::Keyword(_), _)))) {
             match keyword {
                 Keyword::Null => col.nullable = Some(true),
                 Keyword::Not => {
                     self.next_expect(&Token::Keyword(Keyword::Null))?;
                     col.nullable = Some(false);
                 }
-                Keyword::Default => col.default = Some(self.parse_expression()?),
+                // 默认值只解析算术运算, 以免把后续的 NOT NULL 当作 NOT BETWEEN
+                Keyword::Default => col.default = Some(self.parse_expression_with(4)?),
+                Keyword::Primary => {
+                    self.next_expect(&Token::Keyword(Keyword::Key))?;
+                    col.primary_key = true;
+                }
+                Keyword::Unique => col.unique = true,
+                Keyword::Autoincrement => col.auto_increment = true,
+                // 多个 CHECK 须同时满足
+                Keyword::Check => {
+                    self.next_expect(&Token::Symbol(Symbol::OpenParen))?;
+                    let check = self.parse_expression()?;
+                    self.next_expect(&Token::Symbol(Symbol::CloseParen))?;
+
+                    col.check = Some(match col.check.take() {
+                        Some(prev) => Expression::binary(prev, Operator::And, check),
+                        None => check,
+                    });
+                }
+                Keyword::Comment => col.comment = Some(self.parse_comment()?),
+                Keyword::Collate => {
+                    let name = self.next_ident()?;
+                    col.collation = Some(Collation::from_name(&name).ok_or(anyhow!("Unknown collation: {}", name))?);
+                }
                 k => bail!("Unexpected keyword: {:?}", k),
             }
         }
@@ -182,29 +758,357 @@ impl<'a> Parser<'a> {
         Ok(col)
     }
 
-    fn parse_expression(&mut self) -> Result<Expression> {
-        Ok(match self.next()? {
-            Token::Number(n) => {
-                if n.chars().all(|c| c.is_ascii_digit()) {
-                    Const::Integer(n.parse()?).into()
-                } else {
-                    Const::Float(n.parse()?).into()
+    // 类型名及其参数, VARCHAR(n) 另返回长度上限
+    fn parse_data_type(&mut self) -> Result<(DataType, Option<usize>)> {
+        let mut max_length = None;
+        let data_type = match self.next()? {
+            Token::Keyword(Keyword::Integer) | Token::Keyword(Keyword::Int) => DataType::Integer,
+            Token::Keyword(Keyword::Bool) | Token::Keyword(Keyword::Boolean) => DataType::Boolean,
+            Token::Keyword(Keyword::Float) => DataType::Float,
+            Token::Keyword(Keyword::String) | Token::Keyword(Keyword::Text) => DataType::String,
+            Token::Keyword(Keyword::Date) => DataType::Date,
+            Token::Keyword(Keyword::Timestamp) => DataType::Timestamp,
+            // VARCHAR(n)
+            Token::Keyword(Keyword::Varchar) => {
+                match self.parse_type_lengths("VARCHAR")?[..] {
+                    [] => {}
+                    [len] => max_length = Some(len),
+                    _ => bail!("VARCHAR takes at most one length"),
                 }
+                DataType::String
             }
-            Token::String(s) => Const::String(s).into(),
-            Token::Keyword(Keyword::True) => Const::Boolean(true).into(),
-            Token::Keyword(Keyword::False) => Const::Boolean(false).into(),
-            Token::Keyword(Keyword::Null) => Const::Null.into(),
-            exp => bail!("Unexpected expression token: {:?}", exp),
-        })
-    }
-
+            // DECIMAL(p, s), 默认 DECIMAL(38, 0)
+            Token::Keyword(Keyword::Decimal) | Token::Keyword(Keyword::Numeric) => {
+                let (precision, scale) = match self.parse_type_lengths("DECIMAL")?[..] {
+                    [] => (Decimal::MAX_PRECISION as usize, 0),
+                    [precision] => (precision, 0),
+                    [precision, scale] => (precision, scale),
+                    _ => bail!("DECIMAL takes at most precision and scale"),
+                };
+                if precision == 0 || precision > Decimal::MAX_PRECISION as usize {
+                    bail!("DECIMAL precision must be between 1 and {}, got {}", Decimal::MAX_PRECISION, precision);
+                }
+                if scale > precision {
+                    bail!("DECIMAL scale {} exceeds precision {}", scale, precision);
+                }
+                DataType::Decimal { precision: precision as u32, scale: scale as u32 }
+            }
+            token => bail!(ParseError::UnexpectedToken(token)),
+        };
+
+        Ok((data_type, max_length))
+    }
+
+    // 类型参数 (n [, ...]), 无括号时为空
+    fn parse_type_lengths(&mut self, type_name: &str) -> Result<Vec<usize>> {
+        let mut lengths = vec![];
+        if self.next_expect(&Token::Symbol(Symbol::OpenParen)).is_err() {
+            return Ok(lengths);
+        }
+
+        loop {
+            lengths.push(match self.next()? {
+                Token::Number(n) => n.parse().map_err(|_| anyhow!("Invalid {} length: {}", type_name, n))?,
+                token => bail!("Expected {} length, got {:?}", type_name, token),
+            });
+
+            if self.next_expect(&Token::Symbol(Symbol::Comma)).is_err() {
+                break;
+            }
+        }
+        self.next_expect(&Token::Symbol(Symbol::CloseParen))?;
+
+        Ok(lengths)
+    }
+
+    fn parse_expression(&mut self) -> Result<Expression> {
+        self.parse_expression_with(1)
+    }
+
+    // 优先级爬升, 只结合优先级不低于 min_prec 的运算符, 同级左结合
+    fn parse_expression_with(&mut self, min_prec: u8) -> Result<Expression> {
+        let start = self.offset();
+        let mut left = self.parse_expression_atom()?;
+
+        loop {
+            // IS [NOT] NULL, [NOT] BETWEEN, [NOT] IN, [NOT] LIKE 为后缀谓词, 与比较运算同级
+            if min_prec <= 3 {
+                if self.next_expect(&Token::Keyword(Keyword::Is)).is_ok() {
+                    let negated = self.next_expect(&Token::Keyword(Keyword::Not)).is_ok();
+                    self.next_expect(&Token::Keyword(Keyword::Null))?;
+                    left = Expression::IsNull { expr: Box::new(left), negated };
+                    continue;
+                }
+
+                let negated = self.next_expect(&Token::Keyword(Keyword::Not)).is_ok();
+
+                // 边界不含比较和逻辑运算, 避免吞掉 BETWEEN 的 AND
+                if self.next_expect(&Token::Keyword(Keyword::Between)).is_ok() {
+                    let low = self.parse_expression_with(4)?;
+                    self.next_expect(&Token::Keyword(Keyword::And))?;
+                    let high = self.parse_expression_with(4)?;
+                    left = Expression::Between { expr: Box::new(left), low: Box::new(low), high: Box::new(high), negated };
+                    continue;
+                }
+
+                if self.next_expect(&Token::Keyword(Keyword::In)).is_ok() {
+                    let list = self.parse_expression_list()?;
+                    left = Expression::InList { expr: Box::new(left), list, negated };
+                    continue;
+                }
+
+                if self.next_expect(&Token::Keyword(Keyword::Like)).is_ok() {
+                    let pattern = self.parse_expression_with(4)?;
+                    left = Expression::Like { expr: Box::new(left), pattern: Box::new(pattern), negated };
+                    continue;
+                }
+
+                if negated {
+                    bail!("Expected BETWEEN, IN or LIKE after NOT, got {:?}", self.peek()?);
+                }
+            }
+
+            let Some((op, prec)) = self.peek_binary_operator() else { break };
+            if prec < min_prec {
+                break;
+            }
+            self.next()?;
+
+            let right = self.parse_expression_with(prec + 1)?;
+            left = Expression::binary(left, op, right);
+            // 比较运算记下起始位置
+            if prec == 3 {
+                self.positions.push((left.clone(), self.position(start)));
+            }
+        }
+
+        Ok(left)
+    }
+
+    // (expr, ...)
+    fn parse_expression_list(&mut self) -> Result<Vec<Expression>> {
+        self.next_expect(&Token::Symbol(Symbol::OpenParen))?;
+
+        let mut exprs = vec![];
+
+        loop {
+            exprs.push(self.parse_expression()?);
+
+            match self.next()? {
+                Token::Symbol(Symbol::CloseParen) => break,
+                Token::Symbol(Symbol::Comma) => continue,
+                token => bail!(ParseError::UnexpectedToken(token)),
+            }
+        }
+
+        Ok(exprs)
+    }
+
+    // 二元运算符及其优先级, 数值越大结合越紧
+    // NOT 介于 AND 与比较运算之间, 负号最高
+    fn peek_binary_operator(&mut self) -> Option<(Operator, u8)> {
+        Some(match self.peek().ok()? {
+            Token::Keyword(Keyword::Or) => (Operator::Or, 1),
+            Token::Keyword(Keyword::And) => (Operator::And, 2),
+            Token::Symbol(Symbol::Equal) => (Operator::Equal, 3),
+            Token::Symbol(Symbol::NotEqual) => (Operator::NotEqual, 3),
+            Token::Symbol(Symbol::LessThan) => (Operator::LessThan, 3),
+            Token::Symbol(Symbol::LessThanOrEqual) => (Operator::LessThanOrEqual, 3),
+            Token::Symbol(Symbol::GreaterThan) => (Operator::GreaterThan, 3),
+            Token::Symbol(Symbol::GreaterThanOrEqual) => (Operator::GreaterThanOrEqual, 3),
+            Token::Symbol(Symbol::Plus) => (Operator::Add, 4),
+            Token::Symbol(Symbol::Minus) => (Operator::Subtract, 4),
+            Token::Symbol(Symbol::Concat) => (Operator::Concat, 4),
+            Token::Symbol(Symbol::Asterisk) => (Operator::Multiply, 5),
+            Token::Symbol(Symbol::Slash) => (Operator::Divide, 5),
+            Token::Symbol(Symbol::Percent) => (Operator::Modulo, 5),
+            _ => return None,
+        })
+    }
+
+    // 常量、列名、括号、前缀运算
+    fn parse_expression_atom(&mut self) -> Result<Expression> {
+        Ok(match self.next()? {
+            Token::Number(n) => {
+                if !n.chars().all(|c| c.is_ascii_digit()) {
+                    Const::Float(n.parse()?).into()
+                } else if let Ok(v) = n.parse() {
+                    Const::Integer(v).into()
+                } else if self.lenient_integers {
+                    Const::Float(n.parse()?).into()
+                } else {
+                    bail!("Integer literal {} out of range for i64", n)
+                }
+            }
+            Token::String(s) => Const::String(s).into(),
+            Token::Keyword(Keyword::True) => Const::Boolean(true).into(),
+            Token::Keyword(Keyword::False) => Const::Boolean(false).into(),
+            Token::Keyword(Keyword::Null) => Const::Null.into(),
+            Token::Keyword(Keyword::Case) => self.parse_case()?,
+            // CAST(expr AS type)
+            Token::Keyword(Keyword::Cast) => {
+                self.next_expect(&Token::Symbol(Symbol::OpenParen))?;
+                let expr = self.parse_expression()?;
+                self.next_expect(&Token::Keyword(Keyword::As))?;
+                let (target, _) = self.parse_data_type()?;
+                self.next_expect(&Token::Symbol(Symbol::CloseParen))?;
+
+                Expression::Cast { expr: Box::new(expr), target }
+            }
+            // ? 按出现顺序编号, $n 直接指定编号
+            Token::Placeholder(None) => {
+                self.placeholders += 1;
+                Expression::Placeholder(self.placeholders)
+            }
+            Token::Placeholder(Some(n)) => Expression::Placeholder(n),
+            // count(*) sum(a) coalesce(a, b)
+            Token::Ident(ident) if self.next_expect(&Token::Symbol(Symbol::OpenParen)).is_ok() => match ScalarFunction::from_name(&ident) {
+                Some(func) => self.parse_function(func)?,
+                None => self.parse_aggregate(ident)?,
+            },
+            // tbl.col
+            Token::Ident(ident) if self.next_expect(&Token::Symbol(Symbol::Dot)).is_ok() => Expression::Column(format!("{}.{}", ident, self.next_ident()?)),
+            Token::Ident(ident) => Expression::Column(ident),
+            // NOT a = 1 -> NOT (a = 1)
+            Token::Keyword(Keyword::Not) => Expression::unary(UnaryOperator::Not, self.parse_expression_with(3)?),
+            // i64::MIN 的绝对值超出 i64, 与负号一起解析
+            Token::Symbol(Symbol::Minus) if matches!(self.peek(), Ok(Token::Number(n)) if *n == i64::MIN.unsigned_abs().to_string()) => {
+                self.next()?;
+                Const::Integer(i64::MIN).into()
+            }
+            // -a * b -> (-a) * b
+            Token::Symbol(Symbol::Minus) => Expression::unary(UnaryOperator::Negate, self.parse_expression_with(6)?),
+            // (select ...) 标量子查询
+            Token::Symbol(Symbol::OpenParen) if matches!(self.peek(), Ok(Token::Keyword(Keyword::Select))) => {
+                let query = self.parse_query()?;
+                self.next_expect(&Token::Symbol(Symbol::CloseParen))?;
+
+                Expression::Subquery(Box::new(query))
+            }
+            // (expr) 分组
+            Token::Symbol(Symbol::OpenParen) => {
+                let expr = self.parse_expression()?;
+
+                match self.lexer.next().transpose()?.map(|(token, _)| token) {
+                    Some(Token::Symbol(Symbol::CloseParen)) => expr,
+                    Some(token) => bail!("Expected ')' to close '(', got {:?}", token),
+                    None => return Err(anyhow!(ParseError::UnexpectedEof).context("Expected ')' to close '(', got end of input")),
+                }
+            }
+            exp => bail!("Unexpected expression token: {:?}", exp),
+        })
+    }
+
+    // CASE 已读取, 至少一个 WHEN 分支
+    fn parse_case(&mut self) -> Result<Expression> {
+        self.next_expect(&Token::Keyword(Keyword::When))?;
+
+        let mut branches = vec![];
+        loop {
+            let cond = self.parse_expression()?;
+            self.next_expect(&Token::Keyword(Keyword::Then))?;
+            branches.push((cond, self.parse_expression()?));
+
+            if self.next_expect(&Token::Keyword(Keyword::When)).is_err() {
+                break;
+            }
+        }
+
+        let else_ = match self.next_expect(&Token::Keyword(Keyword::Else)) {
+            Ok(_) => Some(Box::new(self.parse_expression()?)),
+            Err(_) => None,
+        };
+        self.next_expect(&Token::Keyword(Keyword::End))?;
+
+        Ok(Expression::Case { branches, else_ })
+    }
+
+    // 函数名及左括号已读取, 参数以逗号分隔
+    fn parse_function(&mut self, func: ScalarFunction) -> Result<Expression> {
+        let mut args = vec![self.parse_expression()?];
+        while self.next_expect(&Token::Symbol(Symbol::Comma)).is_ok() {
+            args.push(self.parse_expression()?);
+        }
+        self.next_expect(&Token::Symbol(Symbol::CloseParen))?;
+
+        if func == ScalarFunction::NullIf && args.len() != 2 {
+            bail!("{} expects 2 arguments, got {}", func, args.len());
+        }
+
+        Ok(Expression::Function { func, args })
+    }
+
+    // 函数名及左括号已读取
+    fn parse_aggregate(&mut self, name: String) -> Result<Expression> {
+        let func = match name.to_uppercase().as_str() {
+            "COUNT" => AggregateFunction::Count,
+            "SUM" => AggregateFunction::Sum,
+            "AVG" => AggregateFunction::Avg,
+            "MIN" => AggregateFunction::Min,
+            "MAX" => AggregateFunction::Max,
+            _ => bail!("Unknown function: {}", name),
+        };
+
+        let arg = match self.next_expect(&Token::Symbol(Symbol::Asterisk)) {
+            Ok(_) if func == AggregateFunction::Count => None,
+            Ok(_) => bail!("{:?} does not accept *", func),
+            Err(_) => Some(Box::new(self.parse_expression()?)),
+        };
+
+        self.next_expect(&Token::Symbol(Symbol::CloseParen))?;
+
+        Ok(Expression::Aggregate { func, arg })
+    }
+
+    // 下一个 token 的字节偏移, 输入结束时为输入的长度
+    fn offset(&mut self) -> usize {
+        match self.lexer.peek() {
+            Some(Ok((_, span))) => span.start,
+            _ => self.input.len(),
+        }
+    }
+
+    // 字节偏移对应的行列
+    fn position(&self, offset: usize) -> Position {
+        let before = &self.input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Position { line: before.matches('\n').count() + 1, column: before[line_start..].chars().count() + 1 }
+    }
+
+    // 词法错误无法借出,复制其信息返回, 保留其中的 ParseError 以便判断输入是否完整
     fn peek(&mut self) -> Result<&Token> {
-        self.lexer.peek().ok_or(anyhow!("Unexpected end of input"))
+        match self.lexer.peek() {
+            Some(Ok((Token::Error { message, position }, _))) => bail!(ParseError::InvalidToken { message: message.clone(), position: *position }),
+            Some(Ok((token, _))) => Ok(token),
+            Some(Err(e)) => match e.downcast_ref::<ParseError>() {
+                Some(kind) => Err(anyhow!(kind.clone()).context(e.to_string())),
+                None => bail!("{}", e),
+            },
+            None => bail!(ParseError::UnexpectedEof),
+        }
     }
 
+    // 标识符在此统一检查长度, 按字符计数
     fn next(&mut self) -> Result<Token> {
-        self.lexer.next().ok_or(anyhow!("Unexpected end of input"))
+        let (token, _) = self.lexer.next().ok_or(ParseError::UnexpectedEof)??;
+        self.after_semicolon = token == Token::Symbol(Symbol::Semicolon);
+        if let Token::Error { message, position } = token {
+            bail!(ParseError::InvalidToken { message, position });
+        }
+        if let Token::Ident(ident) = &token {
+            let len = ident.chars().count();
+            if len > self.max_ident_length {
+                // 过长的名称只显示开头
+                let name = match ident.char_indices().nth(16) {
+                    Some((i, _)) => format!("{}...", &ident[..i]),
+                    None => ident.clone(),
+                };
+                bail!("Identifier {} is {} characters long, exceeding the maximum of {}", name, len, self.max_ident_length);
+            }
+        }
+
+        Ok(token)
     }
 
     fn next_ident(&mut self) -> Result<String> {
@@ -218,7 +1122,7 @@ impl<'a> Parser<'a> {
     fn next_expect(&mut self, expected: &Token) -> Result<Token> {
         match self.peek()? {
             token if token == expected => Ok(self.next()?),
-            token => bail!("Expected {:?}, got {:?}", expected, token),
+            token => bail!(ParseError::ExpectedToken { expected: expected.clone(), found: token.clone() }),
         }
     }
 }
@@ -244,30 +1148,224 @@ mod tests {
                 Column {
                     name: "a".to_string(),
                     data_type: DataType::Integer,
+                    max_length: None,
                     nullable: Some(false),
                     default: Some(Const::Integer(0).into()),
+                    primary_key: false,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
                 },
                 Column {
                     name: "b".to_string(),
                     data_type: DataType::Float,
+                    max_length: None,
                     nullable: Some(false),
                     default: None,
+                    primary_key: false,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
                 },
                 Column {
                     name: "c".to_string(),
                     data_type: DataType::String,
+                    max_length: None,
                     nullable: Some(true),
                     default: None,
+                    primary_key: false,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
                 },
                 Column {
                     name: "d".to_string(),
                     data_type: DataType::Boolean,
+                    max_length: None,
                     nullable: None,
                     default: Some(Const::Boolean(true).into()),
+                    primary_key: false,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
+                },
+            ],
+            if_not_exists: false,
+            primary_key: vec![],
+            comment: None,
+        });
+
+        sql = " create table if not exists users (a int);";
+
+        assert_eq!(Parser::new(sql).parse()?, Statement::Create {
+            table_name: "users".to_string(),
+            columns: vec![
+                Column {
+                    name: "a".to_string(),
+                    data_type: DataType::Integer,
+                    max_length: None,
+                    nullable: None,
+                    default: None,
+                    primary_key: false,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
+                },
+            ],
+            if_not_exists: true,
+            primary_key: vec![],
+            comment: None,
+        });
+
+        sql = " create table users (id int primary key, a int not null primary key);";
+
+        assert_eq!(Parser::new(sql).parse()?, Statement::Create {
+            table_name: "users".to_string(),
+            columns: vec![
+                Column {
+                    name: "id".to_string(),
+                    data_type: DataType::Integer,
+                    max_length: None,
+                    nullable: None,
+                    default: None,
+                    primary_key: true,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
+                },
+                Column {
+                    name: "a".to_string(),
+                    data_type: DataType::Integer,
+                    max_length: None,
+                    nullable: Some(false),
+                    default: None,
+                    primary_key: true,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
+                },
+            ],
+            if_not_exists: false,
+            primary_key: vec![],
+            comment: None,
+        });
+
+        sql = " create table users (id int primary key unique, email varchar unique not null);";
+
+        assert_eq!(Parser::new(sql).parse()?, Statement::Create {
+            table_name: "users".to_string(),
+            columns: vec![
+                Column {
+                    name: "id".to_string(),
+                    data_type: DataType::Integer,
+                    max_length: None,
+                    nullable: None,
+                    default: None,
+                    primary_key: true,
+                    unique: true,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
+                },
+                Column {
+                    name: "email".to_string(),
+                    data_type: DataType::String,
+                    max_length: None,
+                    nullable: Some(false),
+                    default: None,
+                    primary_key: false,
+                    unique: true,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
+                },
+            ],
+            if_not_exists: false,
+            primary_key: vec![],
+            comment: None,
+        });
+
+        sql = " create table orders (user_id int, item_id int, qty int default 1, primary key (user_id, item_id));";
+
+        assert_eq!(Parser::new(sql).parse()?, Statement::Create {
+            table_name: "orders".to_string(),
+            columns: vec![
+                Column {
+                    name: "user_id".to_string(),
+                    data_type: DataType::Integer,
+                    max_length: None,
+                    nullable: None,
+                    default: None,
+                    primary_key: false,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
+                },
+                Column {
+                    name: "item_id".to_string(),
+                    data_type: DataType::Integer,
+                    max_length: None,
+                    nullable: None,
+                    default: None,
+                    primary_key: false,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
+                },
+                Column {
+                    name: "qty".to_string(),
+                    data_type: DataType::Integer,
+                    max_length: None,
+                    nullable: None,
+                    default: Some(Const::Integer(1).into()),
+                    primary_key: false,
+                    unique: false,
+                    check: None,
+                    auto_increment: false,
+                    collation: None,
+                    comment: None,
                 },
             ],
+            if_not_exists: false,
+            primary_key: vec!["user_id".to_string(), "item_id".to_string()],
+            comment: None,
         });
 
+        sql = " create table t (a int, primary key (a));";
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Create { primary_key, .. } if primary_key == vec!["a".to_string()]));
+
+        sql = " create table t (a int, primary key (a, b));";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Primary key column b does not exist");
+
+        sql = " create table t (a int, primary key (a), primary key (a));";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Multiple primary keys for table t");
+
+        sql = " create table users (id int primary);";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected Keyword(Key), got Symbol(CloseParen)");
+
+        sql = " create table if exists users (a int);";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected Keyword(Not), got Keyword(Exists)");
+
         sql = "
             create tabe users (
                 a int default 0 not null,
@@ -298,55 +1396,1627 @@ mod tests {
                 )
         ";
 
-        // println!("{:#?}", Parser::new(sql).parse().unwrap_err());
-        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Unexpected end of input"#);
+        // 输入结束时分号可省略
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Create { .. }));
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_insert() -> Result<()> {
-        let mut sql = " insert into users values (1, 2.3, 'abc', true);";
-        assert_eq!(Parser::new(sql).parse()?, Statement::Insert {
-            table_name: "users".to_string(),
-            columns: None,
-            values: vec![vec![
-                Const::Integer(1).into(),
-                Const::Float(2.3).into(),
-                Const::String("abc".to_string()).into(),
-                Const::Boolean(true).into(),
-            ]],
-        });
+    fn test_parse_data_types() -> Result<()> {
+        let data_types = |sql: &str| -> Result<Vec<DataType>> {
+            match Parser::new(sql).parse()? {
+                Statement::Create { columns, .. } => Ok(columns.into_iter().map(|col| col.data_type).collect()),
+                stmt => panic!("not a create statement: {:?}", stmt),
+            }
+        };
 
-        sql = " insert into users (c1,c2,c3,c4) values (1, 2.3, 'abc', true), (2, 4.5, 'def', false);";
-        assert_eq!(Parser::new(sql).parse()?, Statement::Insert {
-            table_name: "users".to_string(),
-            columns: Some(vec!["c1".to_string(), "c2".to_string(), "c3".to_string(), "c4".to_string()]),
-            values: vec![
-                vec![
-                    Const::Integer(1).into(),
-                    Const::Float(2.3).into(),
-                    Const::String("abc".to_string()).into(),
-                    Const::Boolean(true).into(),
-                ],
-                vec![
-                    Const::Integer(2).into(),
-                    Const::Float(4.5).into(),
-                    Const::String("def".to_string()).into(),
-                    Const::Boolean(false).into(),
-                ]
-            ],
-        });
+        assert_eq!(data_types(" create table t (a decimal(10, 2), b numeric(5), c decimal not null, d numeric(38,38));")?, vec![
+            DataType::Decimal { precision: 10, scale: 2 },
+            DataType::Decimal { precision: 5, scale: 0 },
+            DataType::Decimal { precision: 38, scale: 0 },
+            DataType::Decimal { precision: 38, scale: 38 },
+        ]);
+
+        assert_eq!(data_types(" create table t (a date, b timestamp null);")?, vec![DataType::Date, DataType::Timestamp]);
+
+        assert_eq!(Parser::new(" create table t (a decimal(0));").parse().unwrap_err().to_string(), "DECIMAL precision must be between 1 and 38, got 0");
+        assert_eq!(Parser::new(" create table t (a decimal(39, 2));").parse().unwrap_err().to_string(), "DECIMAL precision must be between 1 and 38, got 39");
+        assert_eq!(Parser::new(" create table t (a decimal(2, 3));").parse().unwrap_err().to_string(), "DECIMAL scale 3 exceeds precision 2");
+        assert_eq!(Parser::new(" create table t (a decimal(5, 2, 1));").parse().unwrap_err().to_string(), "DECIMAL takes at most precision and scale");
+        assert_eq!(Parser::new(" create table t (a decimal(5,));").parse().unwrap_err().to_string(), "Expected DECIMAL length, got Symbol(CloseParen)");
+        assert_eq!(Parser::new(" create table t (a varchar(5, 2));").parse().unwrap_err().to_string(), "VARCHAR takes at most one length");
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_select() -> Result<()> {
-        let mut sql = " select * from users; ";
-        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
-            table_name: "users".to_string(),
-        });
+    fn test_parse_varchar_length() -> Result<()> {
+        let max_lengths = |sql: &str| -> Result<Vec<Option<usize>>> {
+            match Parser::new(sql).parse()? {
+                Statement::Create { columns, .. } => Ok(columns.into_iter().map(|col| col.max_length).collect()),
+                stmt => panic!("not a create statement: {:?}", stmt),
+            }
+        };
+
+        assert_eq!(max_lengths(" create table t (a varchar(10) not null, b varchar, c text, d varchar (255) default 'x');")?, vec![Some(10), None, None, Some(255)]);
+
+        assert_eq!(Parser::new(" create table t (a varchar(abc));").parse().unwrap_err().to_string(), r#"Expected VARCHAR length, got Ident("abc")"#);
+        assert_eq!(Parser::new(" create table t (a varchar(1.5));").parse().unwrap_err().to_string(), "Invalid VARCHAR length: 1.5");
+        assert_eq!(
+            Parser::new(" create table t (a varchar(10);").parse().unwrap_err().to_string(),
+            "Expected ',' or ')' in column definition list after column a, got Symbol(Semicolon)",
+        );
+        assert_eq!(
+            Parser::new(" create table t (a int(10));").parse().unwrap_err().to_string(),
+            "Expected ',' or ')' in column definition list after column a, got Symbol(OpenParen)",
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_unterminated_column_list() -> Result<()> {
+        let err = Parser::new(" create table users (id int primary key, name varchar null").parse().unwrap_err();
+        assert_eq!(err.to_string(), "Expected ',' or ')' in column definition list after column name, got EOF");
+        // 补全后可能合法, 交互式输入应继续读取
+        assert!(is_incomplete(&err));
+
+        let err = Parser::new(" create table t (a int, b int, primary key (a, b)").parse().unwrap_err();
+        assert_eq!(err.to_string(), "Expected ',' or ')' in column definition list after PRIMARY KEY, got EOF");
+
+        // 漏写逗号时指出前一列
+        let err = Parser::new(" create table t (a int b int);").parse().unwrap_err();
+        assert_eq!(err.to_string(), r#"Expected ',' or ')' in column definition list after column a, got Ident("b")"#);
+        assert!(!is_incomplete(&err));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_comment() -> Result<()> {
+        let stmt = Parser::new(" create table users (id int primary key comment 'user id', name varchar null) comment 'registered users';").parse()?;
+        let Statement::Create { columns, comment, .. } = &stmt else { panic!("not a create statement: {:?}", stmt) };
+        assert_eq!(comment.as_deref(), Some("registered users"));
+        assert_eq!(columns.iter().map(|col| col.comment.as_deref()).collect::<Vec<_>>(), vec![Some("user id"), None]);
+        assert_eq!(to_sql(&stmt), "CREATE TABLE users (id INTEGER PRIMARY KEY COMMENT 'user id', name VARCHAR NULL) COMMENT 'registered users';");
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        // 列注释可与其他约束任意排列
+        let stmt = Parser::new(" alter table users add column age int comment 'in years' null;").parse()?;
+        let Statement::AlterTable { op: AlterOperation::AddColumn(col), .. } = stmt else { panic!("not an add column statement: {:?}", stmt) };
+        assert_eq!((col.comment.as_deref(), col.nullable), (Some("in years"), Some(true)));
+
+        // 注释及字符串中的 ' 输出为 '', 重新解析得到原值
+        let stmt = Parser::new(" create table t (a varchar default 'it''s' comment 'user''s name') comment '''quoted''';").parse()?;
+        let Statement::Create { columns, comment, .. } = &stmt else { panic!("not a create statement: {:?}", stmt) };
+        assert_eq!((comment.as_deref(), columns[0].comment.as_deref()), (Some("'quoted'"), Some("user's name")));
+        assert_eq!(columns[0].default, Some(Const::String("it's".to_string()).into()));
+        assert_eq!(to_sql(&stmt), "CREATE TABLE t (a VARCHAR DEFAULT 'it''s' COMMENT 'user''s name') COMMENT '''quoted''';");
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        assert_eq!(Parser::new(" create table t (a int) comment;").parse().unwrap_err().to_string(), "Expected comment string, got Symbol(Semicolon)");
+        assert_eq!(Parser::new(" create table t (a int comment 1);").parse().unwrap_err().to_string(), r#"Expected comment string, got Number("1")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_collate() -> Result<()> {
+        let stmt = Parser::new(" create table t (a varchar collate nocase not null, b text COLLATE Binary, c int);").parse()?;
+        let Statement::Create { columns, .. } = &stmt else { panic!("not a create statement: {:?}", stmt) };
+        assert_eq!(columns.iter().map(|col| col.collation).collect::<Vec<_>>(), vec![Some(Collation::NoCase), Some(Collation::Binary), None]);
+        assert_eq!(to_sql(&stmt), "CREATE TABLE t (a VARCHAR NOT NULL COLLATE NOCASE, b VARCHAR COLLATE BINARY, c INTEGER);");
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        assert_eq!(Parser::new(" create table t (a varchar collate latin1);").parse().unwrap_err().to_string(), "Unknown collation: latin1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_check() -> Result<()> {
+        let checks = |sql: &str| -> Result<Vec<Option<Expression>>> {
+            match Parser::new(sql).parse()? {
+                Statement::Create { columns, .. } => Ok(columns.into_iter().map(|col| col.check).collect()),
+                stmt => bail!("Unexpected statement: {:?}", stmt),
+            }
+        };
+        let col = |c: &str| Expression::Column(c.to_string());
+
+        assert_eq!(checks(" create table t (age int check (age >= 0) not null, b int, c int check (c > b) check (c < 10));")?, vec![
+            Some(Expression::binary(col("age"), Operator::GreaterThanOrEqual, Const::Integer(0).into())),
+            None,
+            Some(Expression::binary(
+                Expression::binary(col("c"), Operator::GreaterThan, col("b")),
+                Operator::And,
+                Expression::binary(col("c"), Operator::LessThan, Const::Integer(10).into()),
+            )),
+        ]);
+
+        assert_eq!(
+            Parser::new(" create table t (a int check a > 0);").parse().unwrap_err().to_string(),
+            r#"Expected Symbol(OpenParen), got Ident("a")"#,
+        );
+        assert_eq!(
+            Parser::new(" create table t (a int check (a > 0);").parse().unwrap_err().to_string(),
+            "Expected ',' or ')' in column definition list after column a, got Symbol(Semicolon)",
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_ident_length() -> Result<()> {
+        let name = "a".repeat(Parser::DEFAULT_MAX_IDENT_LENGTH);
+        let stmt = Parser::new(&format!("create table {} ({} int);", name, name)).parse()?;
+        assert!(matches!(stmt, Statement::Create { ref table_name, .. } if *table_name == name));
+
+        // 多字节字符按字符计数
+        Parser::new(&format!(r#"select "{}" from t;"#, "表".repeat(64))).parse()?;
+
+        let long = format!("{}b", name);
+        let err = "Identifier aaaaaaaaaaaaaaaa... is 65 characters long, exceeding the maximum of 64";
+        assert_eq!(Parser::new(&format!("create table {} (a int);", long)).parse().unwrap_err().to_string(), err);
+        assert_eq!(Parser::new(&format!("create table t ({} int);", long)).parse().unwrap_err().to_string(), err);
+        assert_eq!(Parser::new(&format!(r#"select t.`{}` from t;"#, long)).parse().unwrap_err().to_string(), err);
+
+        let parse = |sql: &str| Parser::new(sql).with_max_ident_length(3).parse();
+        parse("select abc from t;")?;
+        assert_eq!(parse("select abcd from t;").unwrap_err().to_string(), "Identifier abcd is 4 characters long, exceeding the maximum of 3");
+
+        assert_eq!(Parser::new(r#"create table "" (a int);"#).parse().unwrap_err().to_string(), "Empty quoted identifier");
+        assert_eq!(Parser::new(r#"create table t ("a.b" int);"#).parse().unwrap_err().to_string(), "Quoted identifier a.b cannot contain '.'");
+        assert_eq!(Parser::new("select `t.a` from t;").parse().unwrap_err().to_string(), "Quoted identifier t.a cannot contain '.'");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_custom_keywords() -> Result<()> {
+        let sql = " create table t (a bigint, b nvarchar(10));";
+        let stmt = Parser::new(sql).with_keywords([("BIGINT", Keyword::Int), ("NVARCHAR", Keyword::Varchar)]).parse()?;
+        assert_eq!(stmt, Parser::new(" create table t (a int, b varchar(10));").parse()?);
+
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Unexpected token: Ident("bigint")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_autoincrement() -> Result<()> {
+        let stmt = Parser::new(" create table t (id int primary key autoincrement, name varchar);").parse()?;
+        let Statement::Create { columns, .. } = &stmt else {
+            bail!("Unexpected statement: {:?}", stmt);
+        };
+        assert_eq!(columns.iter().map(|col| col.auto_increment).collect::<Vec<_>>(), vec![true, false]);
+
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_placeholder() -> Result<()> {
+        let sql = " insert into t values (?, ?), ($1, ? + 1);";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Insert {
+            table_name: "t".to_string(),
+            columns: None,
+            values: vec![
+                vec![Expression::Placeholder(1), Expression::Placeholder(2)],
+                vec![Expression::Placeholder(1), Expression::binary(Expression::Placeholder(3), Operator::Add, Const::Integer(1).into())],
+            ],
+            returning: None,
+        });
+
+        let stmt = Parser::new(" select * from t where a = $2 and b like $1;").parse()?;
+        assert_eq!(to_sql(&stmt), "SELECT * FROM t WHERE (a = $2) AND b LIKE $1;");
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        // 每条语句的 ? 从 1 开始编号
+        let stmts = Parser::new(" delete from t where a = ?; delete from t where a = ?;").parse_all()?;
+        assert!(stmts.iter().all(|stmt| matches!(stmt, Statement::Delete { filter: Some(Expression::BinaryOp { right, .. }), .. } if **right == Expression::Placeholder(1))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_truncate_table() -> Result<()> {
+        assert_eq!(Parser::new(" truncate table users;").parse()?, Statement::Truncate { table_name: "users".to_string() });
+        assert_eq!(Parser::new(r#" TRUNCATE TABLE "Users""#).parse()?, Statement::Truncate { table_name: "Users".to_string() });
+
+        assert_eq!(Parser::new(" truncate users;").parse().unwrap_err().to_string(), r#"Not a ddl statement: Keyword(Truncate), Ident("users")"#);
+        assert_eq!(Parser::new(" truncate table;").parse().unwrap_err().to_string(), "Expected ident, got Symbol(Semicolon)");
+        assert_eq!(Parser::new(" truncate table a, b;").parse().unwrap_err().to_string(), "Expected Symbol(Semicolon), got Symbol(Comma)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_drop_table() -> Result<()> {
+        let mut sql = " drop table users;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Drop {
+            table_name: "users".to_string(),
+            if_exists: false,
+        });
+
+        sql = " drop table if exists users;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Drop {
+            table_name: "users".to_string(),
+            if_exists: true,
+        });
+
+        sql = " drop table;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected ident, got Symbol(Semicolon)");
+
+        sql = " drop table if users;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Expected Keyword(Exists), got Ident("users")"#);
+
+        sql = " drop users;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Not a ddl statement: Keyword(Drop), Ident("users")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_alter_table() -> Result<()> {
+        let mut sql = " alter table t add column c int default 0;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::AlterTable {
+            table_name: "t".to_string(),
+            op: AlterOperation::AddColumn(Column {
+                name: "c".to_string(),
+                data_type: DataType::Integer,
+                max_length: None,
+                nullable: None,
+                default: Some(Const::Integer(0).into()),
+                primary_key: false,
+                unique: false,
+                check: None,
+                auto_increment: false,
+                collation: None,
+                comment: None,
+            }),
+        });
+
+        sql = " ALTER TABLE t ADD name varchar null;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::AlterTable {
+            table_name: "t".to_string(),
+            op: AlterOperation::AddColumn(Column {
+                name: "name".to_string(),
+                data_type: DataType::String,
+                max_length: None,
+                nullable: Some(true),
+                default: None,
+                primary_key: false,
+                unique: false,
+                check: None,
+                auto_increment: false,
+                collation: None,
+                comment: None,
+            }),
+        });
+
+        sql = " alter table t drop column c;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::AlterTable {
+            table_name: "t".to_string(),
+            op: AlterOperation::DropColumn("c".to_string()),
+        });
+
+        sql = " alter table t drop c;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::AlterTable {
+            table_name: "t".to_string(),
+            op: AlterOperation::DropColumn("c".to_string()),
+        });
+
+        sql = " alter table t drop column;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected ident, got Symbol(Semicolon)");
+
+        sql = " alter table t add column;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected ident, got Symbol(Semicolon)");
+
+        sql = " alter table t rename c;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Unexpected token: Ident("rename")"#);
+
+        sql = " alter t add c int;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Not a ddl statement: Keyword(Alter), Ident("t")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_insert() -> Result<()> {
+        let mut sql = " insert into users values (1, 2.3, 'abc', true);";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Insert {
+            table_name: "users".to_string(),
+            columns: None,
+            values: vec![vec![
+                Const::Integer(1).into(),
+                Const::Float(2.3).into(),
+                Const::String("abc".to_string()).into(),
+                Const::Boolean(true).into(),
+            ]],
+            returning: None,
+        });
+
+        sql = " insert into users (c1,c2,c3,c4) values (1, 2.3, 'abc', true), (2, 4.5, 'def', false);";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Insert {
+            table_name: "users".to_string(),
+            columns: Some(vec!["c1".to_string(), "c2".to_string(), "c3".to_string(), "c4".to_string()]),
+            values: vec![
+                vec![
+                    Const::Integer(1).into(),
+                    Const::Float(2.3).into(),
+                    Const::String("abc".to_string()).into(),
+                    Const::Boolean(true).into(),
+                ],
+                vec![
+                    Const::Integer(2).into(),
+                    Const::Float(4.5).into(),
+                    Const::String("def".to_string()).into(),
+                    Const::Boolean(false).into(),
+                ]
+            ],
+            returning: None,
+        });
+
+        sql = " insert into users (a, b) values (1, default), (DEFAULT, 2);";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Insert {
+            table_name: "users".to_string(),
+            columns: Some(vec!["a".to_string(), "b".to_string()]),
+            values: vec![
+                vec![Const::Integer(1).into(), Expression::Default],
+                vec![Expression::Default, Const::Integer(2).into()],
+            ],
+            returning: None,
+        });
+
+        sql = " insert into users values (-5, -2.3, - 1);";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Insert {
+            table_name: "users".to_string(),
+            columns: None,
+            values: vec![vec![
+                Expression::unary(UnaryOperator::Negate, Const::Integer(5).into()),
+                Expression::unary(UnaryOperator::Negate, Const::Float(2.3).into()),
+                Expression::unary(UnaryOperator::Negate, Const::Integer(1).into()),
+            ]],
+            returning: None,
+        });
+
+        // DEFAULT 只能单独作为值
+        assert_eq!(Parser::new(" insert into users values (default + 1);").parse().unwrap_err().to_string(), "Unexpected token: Symbol(Plus)");
+        assert_eq!(Parser::new(" select * from users where a = default;").parse().unwrap_err().to_string(), "Unexpected expression token: Keyword(Default)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_select() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+
+        let mut sql = " select * from users; ";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::All,
+            from: FromItem::Table("users".to_string()),
+            filter: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        sql = "-- all users\n select * from users; -- trailing note";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::All,
+            from: FromItem::Table("users".to_string()),
+            filter: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        sql = " select a from t;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::Columns(vec![(col("a"), None)]),
+            from: FromItem::Table("t".to_string()),
+            filter: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        sql = " select a, b, c from t;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::Columns(vec![(col("a"), None), (col("b"), None), (col("c"), None)]),
+            from: FromItem::Table("t".to_string()),
+            filter: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        sql = " select a as x, b + 1 as total, c from t;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::Columns(vec![
+                (col("a"), Some("x".to_string())),
+                (Expression::binary(col("b"), Operator::Add, Const::Integer(1).into()), Some("total".to_string())),
+                (col("c"), None),
+            ]),
+            from: FromItem::Table("t".to_string()),
+            filter: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        sql = " select distinct a, b from t;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: true,
+            projection: Projection::Columns(vec![(col("a"), None), (col("b"), None)]),
+            from: FromItem::Table("t".to_string()),
+            filter: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        sql = " select distinct * from t;";
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Select { distinct: true, projection: Projection::All, .. }));
+
+        sql = " select a, b, from t;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Unexpected expression token: Keyword(From)");
+
+        sql = " select a as from t;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected ident, got Keyword(From)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_select_where() -> Result<()> {
+        let mut sql = " select * from users where id = 1;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::All,
+            from: FromItem::Table("users".to_string()),
+            filter: Some(Expression::binary(Expression::Column("id".to_string()), Operator::Equal, Const::Integer(1).into())),
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        // a = 1 or (b <> 'x' and c >= 2.5)
+        sql = " select a from t where a = 1 or b <> 'x' and c >= 2.5;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::Columns(vec![(Expression::Column("a".to_string()), None)]),
+            from: FromItem::Table("t".to_string()),
+            filter: Some(Expression::binary(
+                Expression::binary(Expression::Column("a".to_string()), Operator::Equal, Const::Integer(1).into()),
+                Operator::Or,
+                Expression::binary(
+                    Expression::binary(Expression::Column("b".to_string()), Operator::NotEqual, Const::String("x".to_string()).into()),
+                    Operator::And,
+                    Expression::binary(Expression::Column("c".to_string()), Operator::GreaterThanOrEqual, Const::Float(2.5).into()),
+                ),
+            )),
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        sql = " select * from users where;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Unexpected expression token: Symbol(Semicolon)");
+
+        sql = " select * from users where id 1;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected Symbol(Semicolon), got Number(\"1\")");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_union() -> Result<()> {
+        let select = |sql: &str| Parser::new(sql).parse();
+
+        assert_eq!(Parser::new(" select a from t union select b from u;").parse()?, Statement::Union {
+            left: Box::new(select("select a from t")?),
+            right: Box::new(select("select b from u")?),
+            all: false,
+        });
+        assert_eq!(Parser::new(" select a from t where a > 1 UNION ALL select distinct b from u;").parse()?, Statement::Union {
+            left: Box::new(select("select a from t where a > 1")?),
+            right: Box::new(select("select distinct b from u")?),
+            all: true,
+        });
+
+        // 左结合
+        let stmt = Parser::new(" select a from t union all select b from u union select c from v;").parse()?;
+        assert_eq!(stmt, Statement::Union {
+            left: Box::new(Statement::Union {
+                left: Box::new(select("select a from t")?),
+                right: Box::new(select("select b from u")?),
+                all: true,
+            }),
+            right: Box::new(select("select c from v")?),
+            all: false,
+        });
+        assert_eq!(to_sql(&stmt), "SELECT a FROM t UNION ALL SELECT b FROM u UNION SELECT c FROM v;");
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        assert_eq!(Parser::new(" select a from t union;").parse().unwrap_err().to_string(), "Expected Keyword(Select), got Symbol(Semicolon)");
+        assert_eq!(Parser::new(" select a from t union all all select b from u;").parse().unwrap_err().to_string(), "Expected Keyword(Select), got Keyword(All)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_derived_table() -> Result<()> {
+        let stmt = Parser::new(" select * from (select a from t) as sub;").parse()?;
+        assert_eq!(stmt, Statement::Select {
+            distinct: false,
+            projection: Projection::All,
+            from: FromItem::Subquery { query: Box::new(Parser::new("select a from t").parse()?), alias: "sub".to_string() },
+            filter: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+        assert_eq!(to_sql(&stmt), "SELECT * FROM (SELECT a FROM t) AS sub;");
+
+        // AS 可省略, 可嵌套、参与连接, 内部可为 UNION
+        let sql = " select x.a, u.b from (select a from (select a from t) s) x join u on x.a = u.a, (select b from u union select c from v) as w;";
+        let stmt = Parser::new(sql).parse()?;
+        let Statement::Select { from: FromItem::Join { left, right, .. }, .. } = &stmt else { panic!("not a cross join: {:?}", stmt) };
+        assert!(matches!(**right, FromItem::Subquery { ref query, ref alias } if alias == "w" && matches!(**query, Statement::Union { .. })));
+        assert!(matches!(**left, FromItem::Join { ref left, .. } if matches!(**left, FromItem::Subquery { ref alias, .. } if alias == "x")));
+        assert_eq!(
+            to_sql(&stmt),
+            "SELECT x.a, u.b FROM (SELECT a FROM (SELECT a FROM t) AS s) AS x JOIN u ON x.a = u.a, (SELECT b FROM u UNION SELECT c FROM v) AS w;",
+        );
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        assert_eq!(Parser::new(" select * from (select a from t);").parse().unwrap_err().to_string(), "Subquery in FROM must have an alias");
+        assert_eq!(Parser::new(" select * from (select a from t) where a = 1;").parse().unwrap_err().to_string(), "Subquery in FROM must have an alias");
+        assert_eq!(Parser::new(" select * from (select a from t) as;").parse().unwrap_err().to_string(), "Expected ident, got Symbol(Semicolon)");
+        assert_eq!(Parser::new(" select * from (select a from t as sub;").parse().unwrap_err().to_string(), "Expected Symbol(CloseParen), got Keyword(As)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_select_join() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let table = |t: &str| Box::new(FromItem::Table(t.to_string()));
+
+        let mut sql = " select a.name, b.amount from a join b on a.id = b.a_id;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::Columns(vec![(col("a.name"), None), (col("b.amount"), None)]),
+            from: FromItem::Join {
+                left: table("a"),
+                right: table("b"),
+                on: Some(Expression::binary(col("a.id"), Operator::Equal, col("b.a_id"))),
+            },
+            filter: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        // (a join b) join c
+        sql = " select * from a inner join b on a.id = b.id join c on b.id = c.id where c.x > 1;";
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Select { from, filter: Some(_), .. } if from == FromItem::Join {
+            left: Box::new(FromItem::Join {
+                left: table("a"),
+                right: table("b"),
+                on: Some(Expression::binary(col("a.id"), Operator::Equal, col("b.id"))),
+            }),
+            right: table("c"),
+            on: Some(Expression::binary(col("b.id"), Operator::Equal, col("c.id"))),
+        }));
+
+        sql = " select * from a join b;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected Keyword(On), got Symbol(Semicolon)");
+
+        sql = " select * from a inner b on a.id = b.id;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Expected Join after INNER, got Ident("b")"#);
+
+        sql = " select a. from a;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected ident, got Keyword(From)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_select_cross_join() -> Result<()> {
+        let table = |t: &str| Box::new(FromItem::Table(t.to_string()));
+
+        let mut sql = " select * from a, b;";
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Select { from, .. } if from == FromItem::Join {
+            left: table("a"),
+            right: table("b"),
+            on: None,
+        }));
+
+        // (a, b), c
+        sql = " select * from a, b, c where a.id = c.id;";
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Select { from, .. } if from == FromItem::Join {
+            left: Box::new(FromItem::Join {
+                left: table("a"),
+                right: table("b"),
+                on: None,
+            }),
+            right: table("c"),
+            on: None,
+        }));
+
+        sql = " select * from a, ;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected ident, got Symbol(Semicolon)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_select_group_by() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let count = || Expression::Aggregate { func: AggregateFunction::Count, arg: None };
+
+        let mut sql = " select a, count(*) from t group by a;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::Columns(vec![(col("a"), None), (count(), None)]),
+            from: FromItem::Table("t".to_string()),
+            filter: None,
+            group_by: vec![col("a")],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        sql = " select a, b, count(*) from t where c > 0 group by a, b having count(*) > 1 order by a;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::Columns(vec![(col("a"), None), (col("b"), None), (count(), None)]),
+            from: FromItem::Table("t".to_string()),
+            filter: Some(Expression::binary(col("c"), Operator::GreaterThan, Const::Integer(0).into())),
+            group_by: vec![col("a"), col("b")],
+            having: Some(Expression::binary(count(), Operator::GreaterThan, Const::Integer(1).into())),
+            order_by: vec![(col("a"), false)],
+            limit: None,
+            offset: None,
+        });
+
+        sql = " select a from t group a;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Expected Keyword(By), got Ident("a")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_select_order_by() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+
+        let mut sql = " select * from t order by a;";
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Select { order_by, .. } if order_by == vec![(col("a"), false)]));
+
+        sql = " select a, b from t where a > 1 order by a, b desc, c asc, a + b desc;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::Columns(vec![(Expression::Column("a".to_string()), None), (Expression::Column("b".to_string()), None)]),
+            from: FromItem::Table("t".to_string()),
+            filter: Some(Expression::binary(col("a"), Operator::GreaterThan, Const::Integer(1).into())),
+            group_by: vec![],
+            having: None,
+            order_by: vec![
+                (col("a"), false),
+                (col("b"), true),
+                (col("c"), false),
+                (Expression::binary(col("a"), Operator::Add, col("b")), true),
+            ],
+            limit: None,
+            offset: None,
+        });
+
+        // 加引号的关键字作为列名
+        sql = r#" select "order" from t order by `order` desc;"#;
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Select { projection: Projection::Columns(cols), order_by, .. }
+            if cols == vec![(col("order"), None)] && order_by == vec![(col("order"), true)]));
+
+        sql = " select * from t order a;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Expected Keyword(By), got Ident("a")"#);
+
+        sql = " select * from t order by a asc desc;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected Symbol(Semicolon), got Keyword(Desc)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_select_limit() -> Result<()> {
+        let mut sql = " select * from t limit 10;";
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Select { limit: Some(10), offset: None, .. }));
+
+        sql = " select * from t order by a limit 10 offset 5;";
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Select { limit: Some(10), offset: Some(5), .. }));
+
+        sql = " select * from t offset 5;";
+        assert!(matches!(Parser::new(sql).parse()?, Statement::Select { limit: None, offset: Some(5), .. }));
+
+        sql = " select * from t limit 'x';";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Keyword(Limit) expects a non-negative integer, got String("x")"#);
+
+        sql = " select * from t limit -1;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Keyword(Limit) expects a non-negative integer, got Symbol(Minus)");
+
+        sql = " select * from t limit 1 offset 1.5;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Keyword(Offset) expects a non-negative integer, got Number("1.5")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_update() -> Result<()> {
+        let mut sql = " update users set a = 1;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Update {
+            table_name: "users".to_string(),
+            assignments: vec![("a".to_string(), Const::Integer(1).into())],
+            filter: None,
+            returning: None,
+        });
+
+        sql = " update users set a = 1, b = a + 1 where id = 2;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Update {
+            table_name: "users".to_string(),
+            assignments: vec![
+                ("a".to_string(), Const::Integer(1).into()),
+                ("b".to_string(), Expression::binary(Expression::Column("a".to_string()), Operator::Add, Const::Integer(1).into())),
+            ],
+            filter: Some(Expression::binary(Expression::Column("id".to_string()), Operator::Equal, Const::Integer(2).into())),
+            returning: None,
+        });
+
+        sql = " update users a = 1;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Expected Keyword(Set), got Ident("a")"#);
+
+        sql = " update users set a 1;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Expected Symbol(Equal), got Number("1")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_delete() -> Result<()> {
+        let mut sql = " delete from users;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Delete {
+            table_name: "users".to_string(),
+            filter: None,
+            returning: None,
+        });
+
+        sql = " delete from users where id >= 2;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Delete {
+            table_name: "users".to_string(),
+            filter: Some(Expression::binary(Expression::Column("id".to_string()), Operator::GreaterThanOrEqual, Const::Integer(2).into())),
+            returning: None,
+        });
+
+        sql = " delete users;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), r#"Expected Keyword(From), got Ident("users")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_returning() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+
+        let stmt = Parser::new(" insert into users (name) values ('a'), ('b') returning id, name as n;").parse()?;
+        assert_eq!(stmt, Statement::Insert {
+            table_name: "users".to_string(),
+            columns: Some(vec!["name".to_string()]),
+            values: vec![vec![Const::String("a".to_string()).into()], vec![Const::String("b".to_string()).into()]],
+            returning: Some(Projection::Columns(vec![(col("id"), None), (col("name"), Some("n".to_string()))])),
+        });
+        assert_eq!(to_sql(&stmt), "INSERT INTO users (name) VALUES ('a'), ('b') RETURNING id, name AS n;");
+
+        let stmt = Parser::new(" update users set score = score + 1 where id = 1 returning *;").parse()?;
+        assert!(matches!(stmt, Statement::Update { returning: Some(Projection::All), .. }));
+        assert_eq!(to_sql(&stmt), "UPDATE users SET score = score + 1 WHERE id = 1 RETURNING *;");
+
+        let stmt = Parser::new(" delete from users returning id * 2;").parse()?;
+        assert_eq!(stmt, Statement::Delete {
+            table_name: "users".to_string(),
+            filter: None,
+            returning: Some(Projection::Columns(vec![(Expression::binary(col("id"), Operator::Multiply, Const::Integer(2).into()), None)])),
+        });
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        assert_eq!(Parser::new(" delete from users returning;").parse().unwrap_err().to_string(), "Unexpected expression token: Symbol(Semicolon)");
+        assert_eq!(Parser::new(" delete from users returning id where id = 1;").parse().unwrap_err().to_string(), "Expected Symbol(Semicolon), got Keyword(Where)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_expression() -> Result<()> {
+        assert_eq!(Parser::new("id").parse_expression()?, Expression::Column("id".to_string()));
+        assert_eq!(Parser::new("1").parse_expression()?, Const::Integer(1).into());
+        assert_eq!(Parser::new("1.5").parse_expression()?, Const::Float(1.5).into());
+        assert_eq!(Parser::new("'a'").parse_expression()?, Const::String("a".to_string()).into());
+        assert_eq!(Parser::new("null").parse_expression()?, Const::Null.into());
+
+        assert_eq!(Parser::new("from").parse_expression().unwrap_err().to_string(), "Unexpected expression token: Keyword(From)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_binary_expression() -> Result<()> {
+        let int = |i| Expression::from(Const::Integer(i));
+        let col = |c: &str| Expression::Column(c.to_string());
+
+        // 1 + (2 * 3)
+        assert_eq!(Parser::new("1 + 2 * 3").parse_expression()?, Expression::binary(
+            int(1),
+            Operator::Add,
+            Expression::binary(int(2), Operator::Multiply, int(3)),
+        ));
+
+        // (1 * 2) + 3
+        assert_eq!(Parser::new("1 * 2 + 3").parse_expression()?, Expression::binary(
+            Expression::binary(int(1), Operator::Multiply, int(2)),
+            Operator::Add,
+            int(3),
+        ));
+
+        // (1 - 2) - 3
+        assert_eq!(Parser::new("1 - 2 - 3").parse_expression()?, Expression::binary(
+            Expression::binary(int(1), Operator::Subtract, int(2)),
+            Operator::Subtract,
+            int(3),
+        ));
+
+        // (a % 2) = 0
+        assert_eq!(Parser::new("a % 2 = 0").parse_expression()?, Expression::binary(
+            Expression::binary(col("a"), Operator::Modulo, int(2)),
+            Operator::Equal,
+            int(0),
+        ));
+
+        // ((a = 1) and (b = 2)) or ((a + 1) > (b / 2))
+        assert_eq!(Parser::new("a = 1 and b = 2 or a + 1 > b / 2").parse_expression()?, Expression::binary(
+            Expression::binary(
+                Expression::binary(col("a"), Operator::Equal, int(1)),
+                Operator::And,
+                Expression::binary(col("b"), Operator::Equal, int(2)),
+            ),
+            Operator::Or,
+            Expression::binary(
+                Expression::binary(col("a"), Operator::Add, int(1)),
+                Operator::GreaterThan,
+                Expression::binary(col("b"), Operator::Divide, int(2)),
+            ),
+        ));
+
+        assert_eq!(Parser::new("(1 + 2) * 3").parse_expression()?, Expression::binary(
+            Expression::binary(int(1), Operator::Add, int(2)),
+            Operator::Multiply,
+            int(3),
+        ));
+
+        // ((a || 'x') || b) = 'axb'
+        let string = |s: &str| Expression::from(Const::String(s.to_string()));
+        assert_eq!(Parser::new("a || 'x' || b = 'axb'").parse_expression()?, Expression::binary(
+            Expression::binary(
+                Expression::binary(col("a"), Operator::Concat, string("x")),
+                Operator::Concat,
+                col("b"),
+            ),
+            Operator::Equal,
+            string("axb"),
+        ));
+
+        assert_eq!(Parser::new("1 +").parse_expression().unwrap_err().to_string(), "Unexpected end of input");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_function() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let function = |func, args| Expression::Function { func, args };
+
+        assert_eq!(Parser::new("coalesce(a)").parse_expression()?, function(ScalarFunction::Coalesce, vec![col("a")]));
+        assert_eq!(Parser::new("COALESCE(a, b + 1, null)").parse_expression()?, function(ScalarFunction::Coalesce, vec![
+            col("a"),
+            Expression::binary(col("b"), Operator::Add, Const::Integer(1).into()),
+            Const::Null.into(),
+        ]));
+        assert_eq!(Parser::new("nullif(a, '') is null").parse_expression()?, Expression::IsNull {
+            expr: Box::new(function(ScalarFunction::NullIf, vec![col("a"), Const::String("".to_string()).into()])),
+            negated: false,
+        });
+
+        let stmt = Parser::new(" select coalesce(nullif(a, 0), b, 1) as c from t;").parse()?;
+        assert_eq!(to_sql(&stmt), "SELECT COALESCE(NULLIF(a, 0), b, 1) AS c FROM t;");
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        assert_eq!(Parser::new("nullif(a)").parse_expression().unwrap_err().to_string(), "NULLIF expects 2 arguments, got 1");
+        assert_eq!(Parser::new("nullif(a, b, c)").parse_expression().unwrap_err().to_string(), "NULLIF expects 2 arguments, got 3");
+        assert_eq!(Parser::new("coalesce()").parse_expression().unwrap_err().to_string(), "Unexpected expression token: Symbol(CloseParen)");
+        assert_eq!(Parser::new("coalesce(a,)").parse_expression().unwrap_err().to_string(), "Unexpected expression token: Symbol(CloseParen)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_subquery() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let subquery = |sql: &str| -> Result<Expression> { Ok(Expression::Subquery(Box::new(Parser::new(sql).parse()?))) };
+
+        assert_eq!(Parser::new("a = (select max(a) from t)").parse_expression()?, Expression::binary(col("a"), Operator::Equal, subquery("select max(a) from t")?));
+        assert_eq!(
+            Parser::new("(select a from t union select b from u) + 1").parse_expression()?,
+            Expression::binary(subquery("select a from t union select b from u")?, Operator::Add, Const::Integer(1).into()),
+        );
+        // 括号内不以 SELECT 开头的仍为分组
+        assert_eq!(Parser::new("((select 1 from t))").parse_expression()?, subquery("select 1 from t")?);
+
+        let stmt = Parser::new(" select a, (select count(*) from u where u.b = 1) as n from t where a > (select min(a) from t);").parse()?;
+        assert_eq!(to_sql(&stmt), "SELECT a, (SELECT COUNT(*) FROM u WHERE u.b = 1) AS n FROM t WHERE a > (SELECT MIN(a) FROM t);");
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        assert_eq!(Parser::new("(select a from t").parse_expression().unwrap_err().to_string(), "Unexpected end of input");
+        assert_eq!(Parser::new("(select a from t where)").parse_expression().unwrap_err().to_string(), "Unexpected expression token: Symbol(CloseParen)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_radix_integer() -> Result<()> {
+        assert_eq!(Parser::new("0x10").parse_expression()?, Const::Integer(16).into());
+        assert_eq!(
+            Parser::new("flags % 0b100 = 0x0a").parse_expression()?,
+            Expression::binary(
+                Expression::binary(Expression::Column("flags".to_string()), Operator::Modulo, Const::Integer(4).into()),
+                Operator::Equal,
+                Const::Integer(10).into(),
+            ),
+        );
+
+        assert_eq!(Parser::new(" select * from t where a = 0xG;").parse().unwrap_err().to_string(), "Invalid hexadecimal literal: 0xG");
+        // 超出 i64 的值同十进制字面量处理
+        assert_eq!(
+            Parser::new("0xFFFFFFFFFFFFFFFF").parse_expression().unwrap_err().to_string(),
+            "Integer literal 18446744073709551615 out of range for i64",
+        );
+        assert_eq!(Parser::new("0x8000000000000000").with_lenient_integers(true).parse_expression()?, Const::Float(9223372036854775808.0).into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_integer_range() -> Result<()> {
+        assert_eq!(Parser::new("9223372036854775807").parse_expression()?, Const::Integer(i64::MAX).into());
+        assert_eq!(Parser::new("-9223372036854775807").parse_expression()?, Expression::unary(UnaryOperator::Negate, Const::Integer(i64::MAX).into()));
+        assert_eq!(Parser::new("-9223372036854775808").parse_expression()?, Const::Integer(i64::MIN).into());
+        assert_eq!(Parser::new("-0x8000000000000000").parse_expression()?, Const::Integer(i64::MIN).into());
+        assert_eq!(
+            Parser::new("-9223372036854775808 * 2").parse_expression()?,
+            Expression::binary(Const::Integer(i64::MIN).into(), Operator::Multiply, Const::Integer(2).into()),
+        );
+        assert_eq!(Parser::new("- -9223372036854775808").parse_expression()?, Expression::unary(UnaryOperator::Negate, Const::Integer(i64::MIN).into()));
+        assert_eq!(Parser::new("- -9223372036854775808").parse_expression()?.to_string(), "-(-9223372036854775808)");
+        assert_eq!(
+            Parser::new("-9223372036854775809").parse_expression().unwrap_err().to_string(),
+            "Integer literal 9223372036854775809 out of range for i64",
+        );
+
+        let err = "Integer literal 99999999999999999999 out of range for i64";
+        assert_eq!(Parser::new("99999999999999999999").parse_expression().unwrap_err().to_string(), err);
+        assert_eq!(Parser::new(" select * from t where a > 99999999999999999999;").parse().unwrap_err().to_string(), err);
+
+        // 宽松模式下转为浮点数, 范围内的整数不变
+        let parse = |sql: &str| Parser::new(sql).with_lenient_integers(true).parse_expression();
+        assert_eq!(parse("99999999999999999999")?, Const::Float(1e20).into());
+        assert_eq!(parse("9223372036854775808 + 1")?, Expression::binary(Const::Float(9223372036854775808.0).into(), Operator::Add, Const::Integer(1).into()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_cast() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let cast = |expr, target| Expression::Cast { expr: Box::new(expr), target };
+
+        assert_eq!(Parser::new("cast(a as int)").parse_expression()?, cast(col("a"), DataType::Integer));
+        assert_eq!(Parser::new("CAST('1.5' AS decimal(4, 2)) * 2").parse_expression()?, Expression::binary(
+            cast(Const::String("1.5".to_string()).into(), DataType::Decimal { precision: 4, scale: 2 }),
+            Operator::Multiply,
+            Const::Integer(2).into(),
+        ));
+        assert_eq!(Parser::new("cast(a + 1 as varchar(10))").parse_expression()?, cast(
+            Expression::binary(col("a"), Operator::Add, Const::Integer(1).into()),
+            DataType::String,
+        ));
+
+        let stmt = Parser::new(" select cast(a as text), cast(b as timestamp) from t where cast(c as bool);").parse()?;
+        assert_eq!(to_sql(&stmt), "SELECT CAST(a AS VARCHAR), CAST(b AS TIMESTAMP) FROM t WHERE CAST(c AS BOOLEAN);");
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        assert_eq!(Parser::new("cast(a int)").parse_expression().unwrap_err().to_string(), "Expected Keyword(As), got Keyword(Int)");
+        assert_eq!(Parser::new("cast(a as foo)").parse_expression().unwrap_err().to_string(), "Unexpected token: Ident(\"foo\")");
+        assert_eq!(Parser::new("cast a as int").parse_expression().unwrap_err().to_string(), "Expected Symbol(OpenParen), got Ident(\"a\")");
+        assert_eq!(Parser::new("cast(a as int").parse_expression().unwrap_err().to_string(), "Unexpected end of input");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_case() -> Result<()> {
+        let int = |i| Expression::from(Const::Integer(i));
+        let col = |c: &str| Expression::Column(c.to_string());
+        let string = |s: &str| Expression::from(Const::String(s.to_string()));
+
+        assert_eq!(Parser::new("case when a > 1 then 'big' when a = 1 then 'one' else 'small' end").parse_expression()?, Expression::Case {
+            branches: vec![
+                (Expression::binary(col("a"), Operator::GreaterThan, int(1)), string("big")),
+                (Expression::binary(col("a"), Operator::Equal, int(1)), string("one")),
+            ],
+            else_: Some(Box::new(string("small"))),
+        });
+
+        // 无 ELSE, 结果可参与运算
+        assert_eq!(Parser::new("CASE WHEN b THEN a END + 1").parse_expression()?, Expression::binary(
+            Expression::Case { branches: vec![(col("b"), col("a"))], else_: None },
+            Operator::Add,
+            int(1),
+        ));
+
+        let stmt = Parser::new(" select case when a is null then 0 else a * 2 end as b from t;").parse()?;
+        assert_eq!(to_sql(&stmt), "SELECT CASE WHEN a IS NULL THEN 0 ELSE a * 2 END AS b FROM t;");
+        assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+
+        assert_eq!(Parser::new("case else 1 end").parse_expression().unwrap_err().to_string(), "Expected Keyword(When), got Keyword(Else)");
+        assert_eq!(Parser::new("case when a 1 end").parse_expression().unwrap_err().to_string(), "Expected Keyword(Then), got Number(\"1\")");
+        assert_eq!(Parser::new("case when a then 1").parse_expression().unwrap_err().to_string(), "Unexpected end of input");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_unary_expression() -> Result<()> {
+        let int = |i| Expression::from(Const::Integer(i));
+        let col = |c: &str| Expression::Column(c.to_string());
+
+        assert_eq!(Parser::new("-5").parse_expression()?, Expression::unary(UnaryOperator::Negate, int(5)));
+        assert_eq!(Parser::new("- -5").parse_expression()?, Expression::unary(
+            UnaryOperator::Negate,
+            Expression::unary(UnaryOperator::Negate, int(5)),
+        ));
+        assert_eq!(Parser::new("not true").parse_expression()?, Expression::unary(
+            UnaryOperator::Not,
+            Const::Boolean(true).into(),
+        ));
+
+        // (-a) * b
+        assert_eq!(Parser::new("-a * b").parse_expression()?, Expression::binary(
+            Expression::unary(UnaryOperator::Negate, col("a")),
+            Operator::Multiply,
+            col("b"),
+        ));
+
+        // (not (a = 1)) and b
+        assert_eq!(Parser::new("not a = 1 and b").parse_expression()?, Expression::binary(
+            Expression::unary(UnaryOperator::Not, Expression::binary(col("a"), Operator::Equal, int(1))),
+            Operator::And,
+            col("b"),
+        ));
+
+        let sql = " select * from t where a = -1;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::All,
+            from: FromItem::Table("t".to_string()),
+            filter: Some(Expression::binary(col("a"), Operator::Equal, Expression::unary(UnaryOperator::Negate, int(1)))),
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_is_null() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let is_null = |expr, negated| Expression::IsNull { expr: Box::new(expr), negated };
+
+        assert_eq!(Parser::new("a is null").parse_expression()?, is_null(col("a"), false));
+        assert_eq!(Parser::new("a IS NOT NULL").parse_expression()?, is_null(col("a"), true));
+
+        // (a + 1) is null
+        assert_eq!(Parser::new("a + 1 is null").parse_expression()?, is_null(
+            Expression::binary(col("a"), Operator::Add, Const::Integer(1).into()),
+            false,
+        ));
+
+        // (a is null) or (b is not null)
+        assert_eq!(Parser::new("a is null or b is not null").parse_expression()?, Expression::binary(
+            is_null(col("a"), false),
+            Operator::Or,
+            is_null(col("b"), true),
+        ));
+
+        // not (a is null)
+        assert_eq!(Parser::new("not a is null").parse_expression()?, Expression::unary(
+            UnaryOperator::Not,
+            is_null(col("a"), false),
+        ));
+
+        assert_eq!(Parser::new("a is 1").parse_expression().unwrap_err().to_string(), r#"Expected Keyword(Null), got Number("1")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_between() -> Result<()> {
+        let int = |i| Expression::from(Const::Integer(i));
+        let col = |c: &str| Expression::Column(c.to_string());
+        let between = |expr, low, high, negated| Expression::Between {
+            expr: Box::new(expr),
+            low: Box::new(low),
+            high: Box::new(high),
+            negated,
+        };
+
+        assert_eq!(Parser::new("a between 1 and 10").parse_expression()?, between(col("a"), int(1), int(10), false));
+        assert_eq!(Parser::new("a not between 1 and 10").parse_expression()?, between(col("a"), int(1), int(10), true));
+
+        // (a between 1 and 2) or b
+        assert_eq!(Parser::new("a between 1 and 2 or b").parse_expression()?, Expression::binary(
+            between(col("a"), int(1), int(2), false),
+            Operator::Or,
+            col("b"),
+        ));
+
+        // (a between (b + 1) and (c * 2)) and d
+        assert_eq!(Parser::new("a between b + 1 and c * 2 and d").parse_expression()?, Expression::binary(
+            between(
+                col("a"),
+                Expression::binary(col("b"), Operator::Add, int(1)),
+                Expression::binary(col("c"), Operator::Multiply, int(2)),
+                false,
+            ),
+            Operator::And,
+            col("d"),
+        ));
+
+        assert_eq!(Parser::new("a between 1 or 2").parse_expression().unwrap_err().to_string(), "Expected Keyword(And), got Keyword(Or)");
+        assert_eq!(Parser::new("a not 1").parse_expression().unwrap_err().to_string(), r#"Expected BETWEEN, IN or LIKE after NOT, got Number("1")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_in_list() -> Result<()> {
+        let int = |i| Expression::from(Const::Integer(i));
+        let col = |c: &str| Expression::Column(c.to_string());
+        let in_list = |expr, list, negated| Expression::InList { expr: Box::new(expr), list, negated };
+
+        assert_eq!(Parser::new("a in (1, 2, 3)").parse_expression()?, in_list(col("a"), vec![int(1), int(2), int(3)], false));
+        assert_eq!(Parser::new("a not in (1)").parse_expression()?, in_list(col("a"), vec![int(1)], true));
+
+        // (a in (b + 1, 'x')) and c
+        assert_eq!(Parser::new("a in (b + 1, 'x') and c").parse_expression()?, Expression::binary(
+            in_list(
+                col("a"),
+                vec![Expression::binary(col("b"), Operator::Add, int(1)), Const::String("x".to_string()).into()],
+                false,
+            ),
+            Operator::And,
+            col("c"),
+        ));
+
+        assert_eq!(Parser::new("a in ()").parse_expression().unwrap_err().to_string(), "Unexpected expression token: Symbol(CloseParen)");
+        assert_eq!(Parser::new("a in 1").parse_expression().unwrap_err().to_string(), r#"Expected Symbol(OpenParen), got Number("1")"#);
+        assert_eq!(Parser::new("a in (1 2)").parse_expression().unwrap_err().to_string(), r#"Unexpected token: Number("2")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_like() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let string = |s: &str| Expression::from(Const::String(s.to_string()));
+        let like = |expr, pattern, negated| Expression::Like { expr: Box::new(expr), pattern: Box::new(pattern), negated };
+
+        assert_eq!(Parser::new("name like 'a%'").parse_expression()?, like(col("name"), string("a%"), false));
+        assert_eq!(Parser::new("name NOT LIKE '_b'").parse_expression()?, like(col("name"), string("_b"), true));
+
+        // (name like 'a%') or (name like 'b%')
+        assert_eq!(Parser::new("name like 'a%' or name like 'b%'").parse_expression()?, Expression::binary(
+            like(col("name"), string("a%"), false),
+            Operator::Or,
+            like(col("name"), string("b%"), false),
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_aggregate() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let aggregate = |func, arg: Option<Expression>| Expression::Aggregate { func, arg: arg.map(Box::new) };
+
+        assert_eq!(Parser::new("count(*)").parse_expression()?, aggregate(AggregateFunction::Count, None));
+        assert_eq!(Parser::new("COUNT(a)").parse_expression()?, aggregate(AggregateFunction::Count, Some(col("a"))));
+        assert_eq!(Parser::new("sum(amount)").parse_expression()?, aggregate(AggregateFunction::Sum, Some(col("amount"))));
+        assert_eq!(Parser::new("avg(x)").parse_expression()?, aggregate(AggregateFunction::Avg, Some(col("x"))));
+        assert_eq!(Parser::new("min(a)").parse_expression()?, aggregate(AggregateFunction::Min, Some(col("a"))));
+        assert_eq!(Parser::new("max(a + 1)").parse_expression()?, aggregate(
+            AggregateFunction::Max,
+            Some(Expression::binary(col("a"), Operator::Add, Const::Integer(1).into())),
+        ));
+
+        let sql = " select count(*), sum(amount) as total from t;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Select {
+            distinct: false,
+            projection: Projection::Columns(vec![
+                (aggregate(AggregateFunction::Count, None), None),
+                (aggregate(AggregateFunction::Sum, Some(col("amount"))), Some("total".to_string())),
+            ]),
+            from: FromItem::Table("t".to_string()),
+            filter: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        });
+
+        assert_eq!(Parser::new("sum(*)").parse_expression().unwrap_err().to_string(), "Sum does not accept *");
+        assert_eq!(Parser::new("foo(a)").parse_expression().unwrap_err().to_string(), "Unknown function: foo");
+        assert_eq!(Parser::new("count(a").parse_expression().unwrap_err().to_string(), "Unexpected end of input");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_paren_expression() -> Result<()> {
+        let int = |i| Expression::from(Const::Integer(i));
+        let col = |c: &str| Expression::Column(c.to_string());
+
+        // 1 * (2 + 3), 括号覆盖默认优先级
+        assert_eq!(Parser::new("1 * (2 + 3)").parse_expression()?, Expression::binary(
+            int(1),
+            Operator::Multiply,
+            Expression::binary(int(2), Operator::Add, int(3)),
+        ));
+
+        assert_eq!(Parser::new("((a + b)) * (c - (1 - 2))").parse_expression()?, Expression::binary(
+            Expression::binary(col("a"), Operator::Add, col("b")),
+            Operator::Multiply,
+            Expression::binary(col("c"), Operator::Subtract, Expression::binary(int(1), Operator::Subtract, int(2))),
+        ));
+
+        assert_eq!(Parser::new("(a = 1 or b = 2) and c = 3").parse_expression()?, Expression::binary(
+            Expression::binary(
+                Expression::binary(col("a"), Operator::Equal, int(1)),
+                Operator::Or,
+                Expression::binary(col("b"), Operator::Equal, int(2)),
+            ),
+            Operator::And,
+            Expression::binary(col("c"), Operator::Equal, int(3)),
+        ));
+
+        assert_eq!(Parser::new("(1 + 2").parse_expression().unwrap_err().to_string(), "Expected ')' to close '(', got end of input");
+        assert_eq!(Parser::new("((1 + 2) * 3").parse_expression().unwrap_err().to_string(), "Expected ')' to close '(', got end of input");
+        assert_eq!(Parser::new("(1 2)").parse_expression().unwrap_err().to_string(), r#"Expected ')' to close '(', got Number("2")"#);
+        assert_eq!(Parser::new("()").parse_expression().unwrap_err().to_string(), "Unexpected expression token: Symbol(CloseParen)");
+
+        let sql = " select * from t where (a = 1;";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Expected ')' to close '(', got Symbol(Semicolon)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_optional_semicolon() -> Result<()> {
+        assert!(matches!(Parser::new("select * from t").parse()?, Statement::Select { .. }));
+        assert!(matches!(Parser::new("select * from t -- no semicolon").parse()?, Statement::Select { .. }));
+        assert!(matches!(Parser::new("delete from t where a = 1  ").parse()?, Statement::Delete { .. }));
+
+        assert_eq!(Parser::new("select * from t select * from t").parse().unwrap_err().to_string(), "Expected Symbol(Semicolon), got Keyword(Select)");
+        assert_eq!(Parser::new("select * from t;;").parse().unwrap_err().to_string(), "Unexpected token: Symbol(Semicolon)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_explain() -> Result<()> {
+        let sql = " explain select * from t where a = 1;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Explain(Box::new(Parser::new(" select * from t where a = 1;").parse()?)));
+
+        let sql = " EXPLAIN delete from t;";
+        assert_eq!(Parser::new(sql).parse()?, Statement::Explain(Box::new(Statement::Delete {
+            table_name: "t".to_string(),
+            filter: None,
+            returning: None,
+        })));
+
+        assert_eq!(Parser::new(" explain;").parse().unwrap_err().to_string(), "Unexpected token: Symbol(Semicolon)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_all() -> Result<()> {
+        let sql = "
+            create table t (a int);
+            insert into t values (1), (2); -- two rows
+            select a from t;
+            -- done
+        ";
+        let stmts = Parser::new(sql).parse_all()?;
+
+        assert_eq!(stmts.len(), 3);
+        assert!(matches!(stmts[0], Statement::Create { .. }));
+        assert!(matches!(stmts[1], Statement::Insert { .. }));
+        assert!(matches!(stmts[2], Statement::Select { .. }));
+
+        assert_eq!(Parser::new(" -- nothing\n ").parse_all()?, vec![]);
+
+        let sql = " create table t (a int); insert into t values (1; select * from t;";
+        assert_eq!(Parser::new(sql).parse_all().unwrap_err().to_string(), "Statement 2: Unexpected token: Symbol(Semicolon)");
+
+        let sql = " select * from t; select * from t";
+        assert_eq!(Parser::new(sql).parse_all()?.len(), 2);
+
+        let sql = " select * from t select * from t;";
+        assert_eq!(Parser::new(sql).parse_all().unwrap_err().to_string(), "Statement 1: Expected Symbol(Semicolon), got Keyword(Select)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_batch_separator() -> Result<()> {
+        let sql = "create table t (a int)\nGO\ninsert into t values (1); select a from t\n  go  \n";
+        let stmts = Parser::new(sql).with_batch_separator("GO").parse_all()?;
+        assert_eq!(stmts.len(), 3);
+        assert!(matches!(stmts[0], Statement::Create { .. }));
+        assert!(matches!(stmts[1], Statement::Insert { .. }));
+        assert!(matches!(stmts[2], Statement::Select { .. }));
+
+        // 分隔行前的分号可有可无
+        let sql = "select a from t;\nGO\nselect b from t\nGO";
+        assert_eq!(Parser::new(sql).with_batch_separator("GO").parse_all()?.len(), 2);
+
+        // 只有单独成行的才是分隔符, 编号跨批累计
+        let sql = "select a from t\nGO\nselect b from t go\n";
+        assert_eq!(
+            Parser::new(sql).with_batch_separator("GO").parse_all().unwrap_err().to_string(),
+            r#"Statement 2: Expected Symbol(Semicolon), got Ident("go")"#,
+        );
+
+        // 默认只以分号分隔
+        let sql = "select a from t\nGO\nselect b from t";
+        assert_eq!(Parser::new(sql).parse_all().unwrap_err().to_string(), r#"Statement 1: Expected Symbol(Semicolon), got Ident("go")"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_positions() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let at = |line, column| Position { line, column };
+
+        // 比较表达式从左操作数起算, 列按字符计
+        let mut parser = Parser::new(" select * from t where '名' = b and (a + 1 > 2 or c)\n  and d in (1) and e <> null;");
+        parser.parse()?;
+        assert_eq!(parser.positions(), &[
+            (Expression::binary(Const::String("名".to_string()).into(), Operator::Equal, col("b")), at(1, 24)),
+            (Expression::binary(Expression::binary(col("a"), Operator::Add, Const::Integer(1).into()), Operator::GreaterThan, Const::Integer(2).into()), at(1, 37)),
+            (Expression::binary(col("e"), Operator::NotEqual, Const::Null.into()), at(2, 20)),
+        ]);
+
+        // 分批解析时行号按整个输入计
+        let mut parser = Parser::new("select * from t where a = 1\nGO\nselect * from t\n where b = 2").with_batch_separator("GO");
+        parser.parse_all()?;
+        assert_eq!(parser.positions().iter().map(|(_, position)| *position).collect::<Vec<_>>(), vec![at(1, 23), at(4, 8)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_with_recovery() -> Result<()> {
+        let invalid = |message: &str, position| ParseError::InvalidToken { message: message.to_string(), position };
+
+        // 同一语句中的两个词法错误
+        let sql = "select a ! b from t where c = @x;";
+        assert_eq!(Parser::new(sql).parse_with_recovery().unwrap_err(), vec![
+            invalid("Unknown symbol: !", 9),
+            invalid("Unknown symbol: @", 30),
+        ]);
+        assert_eq!(invalid("Unknown symbol: !", 9).to_string(), "Unknown symbol: ! at position 9");
+        assert_eq!(Parser::new("select * from t; @ x; ~").parse_with_recovery().unwrap_err(), vec![
+            invalid("Unknown symbol: @", 17),
+            invalid("Unknown symbol: ~", 22),
+        ]);
+
+        // 出错的语句之后继续解析, 语法错误也一并报告
+        let sql = "select # from t; select a from; insert into t values (1 2);\nselect a from t where b = 'x";
+        assert_eq!(Parser::new(sql).parse_with_recovery().unwrap_err(), vec![
+            invalid("Unknown symbol: #", 7),
+            ParseError::Syntax("Expected ident, got Symbol(Semicolon)".to_string()),
+            ParseError::UnexpectedToken(Token::Number("2".to_string())),
+            invalid("Unterminated string: 'x", 86),
+        ]);
+
+        let sql = "select * from t; insert into t values (1)";
+        assert_eq!(Parser::new(sql).parse_with_recovery().unwrap(), Parser::new(sql).parse_all()?);
+        assert_eq!(Parser::new("select bigint from t").with_keywords([("BIGINT", Keyword::Int)]).parse_with_recovery().unwrap_err(), vec![
+            ParseError::Syntax("Unexpected expression token: Keyword(Int)".to_string()),
+        ]);
+        // 带上下文的错误按完整信息报告
+        assert_eq!(Parser::new("select (a from t").parse_with_recovery().unwrap_err(), vec![
+            ParseError::Syntax("Expected ')' to close '(', got Keyword(From)".to_string()),
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_lexer_error() {
+        let mut sql = " insert into users values ('abc);";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Unterminated string: 'abc);");
+
+        sql = " insert into users values (@);";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Unknown symbol: @");
+
+        sql = " select * from users; @";
+        assert_eq!(Parser::new(sql).parse().unwrap_err().to_string(), "Unknown symbol: @");
+    }
+
+    #[test]
+    fn test_to_sql_round_trip() -> Result<()> {
+        let round_trip = |sql: &str| -> Result<String> {
+            let stmt = Parser::new(sql).parse()?;
+            let printed = to_sql(&stmt);
+            assert_eq!(Parser::new(&printed).parse()?, stmt, "{}", printed);
+            Ok(printed)
+        };
+
+        assert_eq!(
+            round_trip("create table if not exists Users (id int not null primary key, name varchar(10) default 'a' unique, price numeric(6, 2) null, score float default -1.5 * 2, primary key (id));")?,
+            "CREATE TABLE IF NOT EXISTS users (id INTEGER NOT NULL PRIMARY KEY, name VARCHAR(10) DEFAULT 'a' UNIQUE, price DECIMAL(6, 2) NULL, score FLOAT DEFAULT -1.5 * 2, PRIMARY KEY (id));",
+        );
+        assert_eq!(
+            round_trip(r#"insert into "Users" ("Id", `order`) values (1, 'x'), (-2, null), (true, 3.0)"#)?,
+            r#"INSERT INTO "Users" ("Id", "order") VALUES (1, 'x'), (-2, NULL), (true, 3.0);"#,
+        );
+        assert_eq!(
+            round_trip("select distinct a as x, count(*), t.b from t inner join u on t.id = u.id, v where not a = 1 and b is not null or c not in (1, 2) group by a having count(*) > 1 order by a desc, b asc limit 10 offset 5")?,
+            "SELECT DISTINCT a AS x, COUNT(*), t.b FROM t JOIN u ON t.id = u.id, v WHERE (NOT (a = 1) AND b IS NOT NULL) OR c NOT IN (1, 2) GROUP BY a HAVING COUNT(*) > 1 ORDER BY a DESC, b LIMIT 10 OFFSET 5;",
+        );
+        assert_eq!(
+            round_trip("update t set a = a + 1, b = 'x' where c between 1 and 2 + 3")?,
+            "UPDATE t SET a = a + 1, b = 'x' WHERE c BETWEEN 1 AND (2 + 3);",
+        );
+        assert_eq!(round_trip("delete from t where name like 'a%'")?, "DELETE FROM t WHERE name LIKE 'a%';");
+        assert_eq!(round_trip("delete from t")?, "DELETE FROM t;");
+        assert_eq!(round_trip("drop table if exists t")?, "DROP TABLE IF EXISTS t;");
+        assert_eq!(round_trip("alter table t add c date default '2024-01-01'")?, "ALTER TABLE t ADD COLUMN c DATE DEFAULT '2024-01-01';");
+        assert_eq!(round_trip("alter table t drop column c")?, "ALTER TABLE t DROP COLUMN c;");
+        assert_eq!(round_trip("explain select * from t")?, "EXPLAIN SELECT * FROM t;");
+
+        // 需要括号才能保持结构的表达式
+        for sql in [
+            "select * from t where (not a) = b",
+            "select * from t where (a is null) is null",
+            "select * from t where (a or b) is null",
+            "select - -a, -(a + b) * c, (a = 1) in (true) from t",
+            "select * from t where (a between 1 and 2) = (b like 'x')",
+            "create table t (a bool default (1 = 1), b bool default (not true), c int default (1) + 2)",
+        ] {
+            round_trip(sql)?;
+        }
+        assert_eq!(round_trip("create table t (a bool default (1 = 1))")?, "CREATE TABLE t (a BOOLEAN DEFAULT (1 = 1));");
+
+        // 浮点数不输出指数形式
+        assert_eq!(round_trip("select 0.0000001, 100000000000000000000000.0 from t")?, "SELECT 0.0000001, 100000000000000000000000.0 FROM t;");
+        for v in [1e-300, 1e300, f64::MAX, f64::MIN_POSITIVE] {
+            let stmt = Statement::Select {
+                distinct: false,
+                projection: Projection::Columns(vec![(Const::Float(v).into(), None)]),
+                from: FromItem::Table("t".to_string()),
+                filter: None,
+                group_by: vec![],
+                having: None,
+                order_by: vec![],
+                limit: None,
+                offset: None,
+            };
+            assert_eq!(Parser::new(&to_sql(&stmt)).parse()?, stmt);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_sql_keywords() -> Result<()> {
+        for keyword in common::ast::KEYWORDS {
+            assert!(keyword.parse::<Keyword>().is_ok(), "{}", keyword);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_error_kind() -> Result<()> {
+        let kind = |sql: &str| Parser::new(sql).parse().unwrap_err().downcast::<ParseError>();
+
+        assert_eq!(kind("select * from")?, ParseError::UnexpectedEof);
+        assert_eq!(kind("insert into t values (1,")?, ParseError::UnexpectedEof);
+        assert_eq!(kind("select * from t;;")?, ParseError::UnexpectedToken(Token::Symbol(Symbol::Semicolon)));
+        assert_eq!(kind("create table t a int)")?, ParseError::ExpectedToken {
+            expected: Token::Symbol(Symbol::OpenParen),
+            found: Token::Ident("a".to_string()),
+        });
+        assert_eq!("tabel".parse::<Keyword>().unwrap_err(), ParseError::UnknownKeyword("tabel".to_string()));
+
+        // 其余词法错误不是 ParseError
+        assert!(Parser::new("select @").parse().unwrap_err().downcast_ref::<ParseError>().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_incomplete() -> Result<()> {
+        for sql in ["create table t (", "select * from t where a =", "select * from t where (a = 1", "insert into t values ('abc", r#"select "a"#, "select * from t; select"] {
+            let err = Parser::new(sql).parse_all().unwrap_err();
+            assert!(is_incomplete(&err), "{}: {}", sql, err);
+        }
+
+        // 分号可省略, 不缺少输入
+        assert!(Parser::new("select * from t").parse().is_ok());
+
+        for sql in ["create tabe t", "select * from t;;", "select * frm t", "select @", "create table t (a int))"] {
+            let err = Parser::new(sql).parse().unwrap_err();
+            assert!(!is_incomplete(&err), "{}: {}", sql, err);
+        }
+
+        // 保留原有错误信息
+        let err = Parser::new("select 'abc").parse().unwrap_err();
+        assert!(err.downcast_ref::<ParseError>().is_some_and(ParseError::is_incomplete));
+        assert_eq!(err.to_string(), "Unterminated string: 'abc");
+        assert_eq!(Parser::new("select * from t; select * from t where (a").parse_all().unwrap_err().to_string(), "Statement 2: Expected ')' to close '(', got end of input");
+
         Ok(())
     }
 }
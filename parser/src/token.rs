@@ -1,7 +1,8 @@
 use std::str::FromStr;
 use anyhow::bail;
+use crate::error::ParseError;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[derive(PartialEq)]
 pub enum Keyword {
     Create,
@@ -27,12 +28,61 @@ pub enum Keyword {
     Null,
     Primary,
     Key,
+    Where,
+    And,
+    Or,
+    Update,
+    Set,
+    Delete,
+    Drop,
+    If,
+    Exists,
+    Unique,
+    Order,
+    By,
+    Asc,
+    Desc,
+    Limit,
+    Offset,
+    Is,
+    Between,
+    In,
+    Like,
+    As,
+    Group,
+    Having,
+    Distinct,
+    Join,
+    Inner,
+    On,
+    Explain,
+    Alter,
+    Add,
+    Column,
+    Decimal,
+    Numeric,
+    Date,
+    Timestamp,
+    Check,
+    Autoincrement,
+    Truncate,
+    Case,
+    When,
+    Then,
+    Else,
+    End,
+    Cast,
+    Union,
+    All,
+    Returning,
+    Collate,
+    Comment,
 }
 
 impl FromStr for Keyword {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
-    fn from_str(s: &str) -> anyhow::Result<Self> {
+    fn from_str(s: &str) -> Result<Self, ParseError> {
         let keyword = match s.to_uppercase().as_str() {
             "CREATE" => Keyword::Create,
             "TABLE" => Keyword::Table,
@@ -57,14 +107,63 @@ impl FromStr for Keyword {
             "NULL" => Keyword::Null,
             "PRIMARY" => Keyword::Primary,
             "KEY" => Keyword::Key,
-            _ => bail!("Unknown keyword: {}", s),
+            "WHERE" => Keyword::Where,
+            "AND" => Keyword::And,
+            "OR" => Keyword::Or,
+            "UPDATE" => Keyword::Update,
+            "SET" => Keyword::Set,
+            "DELETE" => Keyword::Delete,
+            "DROP" => Keyword::Drop,
+            "IF" => Keyword::If,
+            "EXISTS" => Keyword::Exists,
+            "UNIQUE" => Keyword::Unique,
+            "ORDER" => Keyword::Order,
+            "BY" => Keyword::By,
+            "ASC" => Keyword::Asc,
+            "DESC" => Keyword::Desc,
+            "LIMIT" => Keyword::Limit,
+            "OFFSET" => Keyword::Offset,
+            "IS" => Keyword::Is,
+            "BETWEEN" => Keyword::Between,
+            "IN" => Keyword::In,
+            "LIKE" => Keyword::Like,
+            "AS" => Keyword::As,
+            "GROUP" => Keyword::Group,
+            "HAVING" => Keyword::Having,
+            "DISTINCT" => Keyword::Distinct,
+            "JOIN" => Keyword::Join,
+            "INNER" => Keyword::Inner,
+            "ON" => Keyword::On,
+            "EXPLAIN" => Keyword::Explain,
+            "ALTER" => Keyword::Alter,
+            "ADD" => Keyword::Add,
+            "COLUMN" => Keyword::Column,
+            "DECIMAL" => Keyword::Decimal,
+            "NUMERIC" => Keyword::Numeric,
+            "DATE" => Keyword::Date,
+            "TIMESTAMP" => Keyword::Timestamp,
+            "CHECK" => Keyword::Check,
+            "AUTOINCREMENT" => Keyword::Autoincrement,
+            "TRUNCATE" => Keyword::Truncate,
+            "CASE" => Keyword::Case,
+            "WHEN" => Keyword::When,
+            "THEN" => Keyword::Then,
+            "ELSE" => Keyword::Else,
+            "END" => Keyword::End,
+            "CAST" => Keyword::Cast,
+            "UNION" => Keyword::Union,
+            "ALL" => Keyword::All,
+            "RETURNING" => Keyword::Returning,
+            "COLLATE" => Keyword::Collate,
+            "COMMENT" => Keyword::Comment,
+            _ => return Err(ParseError::UnknownKeyword(s.to_string())),
         };
 
         Ok(keyword)
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Symbol {
     //左括号(
     OpenParen,
@@ -72,6 +171,8 @@ pub enum Symbol {
     CloseParen,
     //逗号,
     Comma,
+    //点. 限定列名 tbl.col
+    Dot,
     //分号;
     Semicolon,
     //星号
@@ -82,6 +183,22 @@ pub enum Symbol {
     Minus,
     // 斜杠/
     Slash,
+    // 百分号%
+    Percent,
+    // 等于=
+    Equal,
+    // 小于<
+    LessThan,
+    // 大于>
+    GreaterThan,
+    // 小于等于<=
+    LessThanOrEqual,
+    // 大于等于>=
+    GreaterThanOrEqual,
+    // 不等于<> !=
+    NotEqual,
+    // 字符串拼接||
+    Concat,
 }
 
 impl TryFrom<char> for Symbol {
@@ -92,11 +209,16 @@ impl TryFrom<char> for Symbol {
             '(' => Symbol::OpenParen,
             ')' => Symbol::CloseParen,
             ',' => Symbol::Comma,
+            '.' => Symbol::Dot,
             ';' => Symbol::Semicolon,
             '*' => Symbol::Asterisk,
             '+' => Symbol::Plus,
             '-' => Symbol::Minus,
             '/' => Symbol::Slash,
+            '%' => Symbol::Percent,
+            '=' => Symbol::Equal,
+            '<' => Symbol::LessThan,
+            '>' => Symbol::GreaterThan,
             _ => bail!("Unknown symbol: {}", c),
         };
 
@@ -112,11 +234,16 @@ impl TryFrom<&char> for Symbol {
             '(' => Symbol::OpenParen,
             ')' => Symbol::CloseParen,
             ',' => Symbol::Comma,
+            '.' => Symbol::Dot,
             ';' => Symbol::Semicolon,
             '*' => Symbol::Asterisk,
             '+' => Symbol::Plus,
             '-' => Symbol::Minus,
             '/' => Symbol::Slash,
+            '%' => Symbol::Percent,
+            '=' => Symbol::Equal,
+            '<' => Symbol::LessThan,
+            '>' => Symbol::GreaterThan,
             _ => bail!("Unknown symbol: {}", c),
         };
 
@@ -124,7 +251,7 @@ impl TryFrom<&char> for Symbol {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Keyword(Keyword),
     // 标识符 表名、列名
@@ -132,6 +259,10 @@ pub enum Token {
     String(String),
     Number(String),
     Symbol(Symbol),
+    // 参数占位符, ? 为 None, $n 为 Some(n)
+    Placeholder(Option<usize>),
+    // 恢复模式下的词法错误, position 为在输入中的字节偏移
+    Error { message: String, position: usize },
 }
 
 #[cfg(test)]
@@ -163,6 +294,55 @@ mod tests {
         assert_eq!(Keyword::from_str("NULL").unwrap(), Keyword::Null);
         assert_eq!(Keyword::from_str("PRIMARY").unwrap(), Keyword::Primary);
         assert_eq!(Keyword::from_str("KEY").unwrap(), Keyword::Key);
+        assert_eq!(Keyword::from_str("WHERE").unwrap(), Keyword::Where);
+        assert_eq!(Keyword::from_str("AND").unwrap(), Keyword::And);
+        assert_eq!(Keyword::from_str("OR").unwrap(), Keyword::Or);
+        assert_eq!(Keyword::from_str("UPDATE").unwrap(), Keyword::Update);
+        assert_eq!(Keyword::from_str("SET").unwrap(), Keyword::Set);
+        assert_eq!(Keyword::from_str("DELETE").unwrap(), Keyword::Delete);
+        assert_eq!(Keyword::from_str("DROP").unwrap(), Keyword::Drop);
+        assert_eq!(Keyword::from_str("IF").unwrap(), Keyword::If);
+        assert_eq!(Keyword::from_str("EXISTS").unwrap(), Keyword::Exists);
+        assert_eq!(Keyword::from_str("UNIQUE").unwrap(), Keyword::Unique);
+        assert_eq!(Keyword::from_str("ORDER").unwrap(), Keyword::Order);
+        assert_eq!(Keyword::from_str("BY").unwrap(), Keyword::By);
+        assert_eq!(Keyword::from_str("ASC").unwrap(), Keyword::Asc);
+        assert_eq!(Keyword::from_str("DESC").unwrap(), Keyword::Desc);
+        assert_eq!(Keyword::from_str("LIMIT").unwrap(), Keyword::Limit);
+        assert_eq!(Keyword::from_str("OFFSET").unwrap(), Keyword::Offset);
+        assert_eq!(Keyword::from_str("IS").unwrap(), Keyword::Is);
+        assert_eq!(Keyword::from_str("BETWEEN").unwrap(), Keyword::Between);
+        assert_eq!(Keyword::from_str("IN").unwrap(), Keyword::In);
+        assert_eq!(Keyword::from_str("LIKE").unwrap(), Keyword::Like);
+        assert_eq!(Keyword::from_str("AS").unwrap(), Keyword::As);
+        assert_eq!(Keyword::from_str("GROUP").unwrap(), Keyword::Group);
+        assert_eq!(Keyword::from_str("HAVING").unwrap(), Keyword::Having);
+        assert_eq!(Keyword::from_str("DISTINCT").unwrap(), Keyword::Distinct);
+        assert_eq!(Keyword::from_str("JOIN").unwrap(), Keyword::Join);
+        assert_eq!(Keyword::from_str("INNER").unwrap(), Keyword::Inner);
+        assert_eq!(Keyword::from_str("ON").unwrap(), Keyword::On);
+        assert_eq!(Keyword::from_str("EXPLAIN").unwrap(), Keyword::Explain);
+        assert_eq!(Keyword::from_str("ALTER").unwrap(), Keyword::Alter);
+        assert_eq!(Keyword::from_str("ADD").unwrap(), Keyword::Add);
+        assert_eq!(Keyword::from_str("COLUMN").unwrap(), Keyword::Column);
+        assert_eq!(Keyword::from_str("DECIMAL").unwrap(), Keyword::Decimal);
+        assert_eq!(Keyword::from_str("NUMERIC").unwrap(), Keyword::Numeric);
+        assert_eq!(Keyword::from_str("DATE").unwrap(), Keyword::Date);
+        assert_eq!(Keyword::from_str("TIMESTAMP").unwrap(), Keyword::Timestamp);
+        assert_eq!(Keyword::from_str("CHECK").unwrap(), Keyword::Check);
+        assert_eq!(Keyword::from_str("AUTOINCREMENT").unwrap(), Keyword::Autoincrement);
+        assert_eq!(Keyword::from_str("truncate").unwrap(), Keyword::Truncate);
+        assert_eq!(Keyword::from_str("CASE").unwrap(), Keyword::Case);
+        assert_eq!(Keyword::from_str("when").unwrap(), Keyword::When);
+        assert_eq!(Keyword::from_str("Then").unwrap(), Keyword::Then);
+        assert_eq!(Keyword::from_str("ELSE").unwrap(), Keyword::Else);
+        assert_eq!(Keyword::from_str("END").unwrap(), Keyword::End);
+        assert_eq!(Keyword::from_str("cast").unwrap(), Keyword::Cast);
+        assert_eq!(Keyword::from_str("UNION").unwrap(), Keyword::Union);
+        assert_eq!(Keyword::from_str("all").unwrap(), Keyword::All);
+        assert_eq!(Keyword::from_str("Returning").unwrap(), Keyword::Returning);
+        assert_eq!(Keyword::from_str("collate").unwrap(), Keyword::Collate);
+        assert_eq!(Keyword::from_str("Comment").unwrap(), Keyword::Comment);
 
         assert!(Keyword::from_str("KEY1").is_err());
     }
@@ -172,12 +352,20 @@ mod tests {
         assert_eq!(Symbol::try_from('(').unwrap(), Symbol::OpenParen);
         assert_eq!(Symbol::try_from(')').unwrap(), Symbol::CloseParen);
         assert_eq!(Symbol::try_from(',').unwrap(), Symbol::Comma);
+        assert_eq!(Symbol::try_from('.').unwrap(), Symbol::Dot);
         assert_eq!(Symbol::try_from(';').unwrap(), Symbol::Semicolon);
         assert_eq!(Symbol::try_from('*').unwrap(), Symbol::Asterisk);
         assert_eq!(Symbol::try_from('+').unwrap(), Symbol::Plus);
         assert_eq!(Symbol::try_from('-').unwrap(), Symbol::Minus);
         assert_eq!(Symbol::try_from('/').unwrap(), Symbol::Slash);
+        assert_eq!(Symbol::try_from('%').unwrap(), Symbol::Percent);
+        assert_eq!(Symbol::try_from(&'%').unwrap(), Symbol::Percent);
+        assert_eq!(Symbol::try_from('=').unwrap(), Symbol::Equal);
+        assert_eq!(Symbol::try_from('<').unwrap(), Symbol::LessThan);
+        assert_eq!(Symbol::try_from('>').unwrap(), Symbol::GreaterThan);
 
-        assert!(Symbol::try_from('=').is_err());
+        assert!(Symbol::try_from('@').is_err());
+        assert!(Symbol::try_from('!').is_err());
+        assert!(Symbol::try_from(&'^').is_err());
     }
 }
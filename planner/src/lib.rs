@@ -1,34 +1,333 @@
 mod planner;
 
-use common::ast::Statement;
-use common::schema::Table;
-use common::types::Value;
-use crate::planner::Planner;
+use std::fmt::{Display, Formatter};
+use anyhow::{bail, Result};
+use common::ast::{Expression, Position, Statement};
+use common::schema::{Catalog, Column, Table};
+use common::types::{DataType, Value};
+pub use crate::planner::Planner;
 
 // 执行节点
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Node {
     Create {
         schema: Table,
+        if_not_exists: bool,
     },
 
+    // 值均已折叠为常量, 含占位符时保留表达式, 绑定参数后由执行器求值
     Insert {
         table_name: String,
         columns: Vec<String>,
-        values: Vec<Vec<Value>>,
+        values: Vec<Vec<Expression>>,
+        // 同 Projection, 为空表示所有列; None 时返回影响的行数
+        returning: Option<Vec<(Expression, String)>>,
     },
 
     Scan {
         table_name: String,
     },
+
+    // 对左右两侧的每对行求连接条件, 结果行为左右两行拼接
+    NestedLoopJoin {
+        left: Box<Node>,
+        right: Box<Node>,
+        predicate: Expression,
+    },
+
+    Filter {
+        source: Box<Node>,
+        predicate: Expression,
+    },
+
+    // (表达式, 结果列名)
+    Projection {
+        source: Box<Node>,
+        exprs: Vec<(Expression, String)>,
+    },
+
+    // 按分组键分组后求聚合, 无分组键时所有行为一组
+    // aggregates 同 Projection, 聚合函数之外只引用分组键
+    Aggregate {
+        source: Box<Node>,
+        group_keys: Vec<Expression>,
+        aggregates: Vec<(Expression, String)>,
+        having: Option<Expression>,
+    },
+
+    // 派生表, 以别名作为 source 结果的表名
+    Derived {
+        source: Box<Node>,
+        alias: String,
+    },
+
+    // 依次输出左右两侧的行, 非 ALL 时按整行去重; 结果列名取左侧
+    Union {
+        left: Box<Node>,
+        right: Box<Node>,
+        all: bool,
+    },
+
+    // 按整行去重, 保留首次出现的行
+    Distinct {
+        source: Box<Node>,
+    },
+
+    // (排序表达式, 是否降序)
+    Sort {
+        source: Box<Node>,
+        keys: Vec<(Expression, bool)>,
+    },
+
+    Limit {
+        source: Box<Node>,
+        limit: Option<u64>,
+        offset: Option<u64>,
+    },
+
+    Update {
+        table_name: String,
+        assignments: Vec<(String, Expression)>,
+        filter: Option<Expression>,
+        returning: Option<Vec<(Expression, String)>>,
+    },
+
+    // filter 为空表示删除所有行
+    Delete {
+        table_name: String,
+        filter: Option<Expression>,
+        returning: Option<Vec<(Expression, String)>>,
+    },
+
+    Drop {
+        table_name: String,
+        if_exists: bool,
+    },
+
+    // 删除所有行并重置自增序号
+    Truncate {
+        table_name: String,
+    },
+
+    AlterTable {
+        table_name: String,
+        op: AlterOperation,
+    },
+
+    // 不执行 source, 只输出其计划树
+    Explain {
+        source: Box<Node>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlterOperation {
+    // 已有的行取新列的默认值
+    AddColumn(Column),
+    DropColumn(String),
+}
+
+impl Node {
+    // 节点树中所有待求值的表达式
+    fn expressions_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Node::Insert { values, returning, .. } => values.iter_mut().flatten().chain(returning.iter_mut().flatten().map(|(expr, _)| expr)).collect(),
+            Node::NestedLoopJoin { left, right, predicate } => {
+                std::iter::once(predicate).chain(left.expressions_mut()).chain(right.expressions_mut()).collect()
+            }
+            Node::Union { left, right, .. } => left.expressions_mut().into_iter().chain(right.expressions_mut()).collect(),
+            Node::Filter { source, predicate } => std::iter::once(predicate).chain(source.expressions_mut()).collect(),
+            Node::Projection { source, exprs } => exprs.iter_mut().map(|(expr, _)| expr).chain(source.expressions_mut()).collect(),
+            Node::Aggregate { source, group_keys, aggregates, having } => group_keys.iter_mut()
+                .chain(aggregates.iter_mut().map(|(expr, _)| expr))
+                .chain(having)
+                .chain(source.expressions_mut())
+                .collect(),
+            Node::Sort { source, keys } => keys.iter_mut().map(|(expr, _)| expr).chain(source.expressions_mut()).collect(),
+            Node::Derived { source, .. } | Node::Distinct { source } | Node::Limit { source, .. } | Node::Explain { source } => source.expressions_mut(),
+            Node::Update { assignments, filter, returning, .. } => assignments.iter_mut()
+                .map(|(_, expr)| expr)
+                .chain(filter)
+                .chain(returning.iter_mut().flatten().map(|(expr, _)| expr))
+                .collect(),
+            Node::Delete { filter, returning, .. } => filter.iter_mut().chain(returning.iter_mut().flatten().map(|(expr, _)| expr)).collect(),
+            Node::Create { .. } | Node::Scan { .. } | Node::Drop { .. } | Node::Truncate { .. } | Node::AlterTable { .. } => vec![],
+        }
+    }
+
+    // 每个节点一行, 子节点缩进两格
+    fn explain(&self, f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let list = |exprs: &mut dyn Iterator<Item=String>| exprs.collect::<Vec<_>>().join(", ");
+
+        write!(f, "{:indent$}", "", indent = depth * 2)?;
+        match self {
+            Node::Create { schema, .. } => writeln!(f, "Create: {}", schema.name),
+            Node::Insert { table_name, values, .. } => writeln!(f, "Insert: {} ({} rows)", table_name, values.len()),
+            Node::Scan { table_name } => writeln!(f, "Scan: {}", table_name),
+            Node::NestedLoopJoin { left, right, predicate } => {
+                writeln!(f, "NestedLoopJoin: {}", predicate)?;
+                left.explain(f, depth + 1)?;
+                return right.explain(f, depth + 1);
+            }
+            Node::Filter { predicate, .. } => writeln!(f, "Filter: {}", predicate),
+            Node::Projection { exprs, .. } => writeln!(f, "Projection: {}", list(&mut exprs.iter().map(|(expr, name)| {
+                match expr.to_string() {
+                    expr if &expr == name => expr,
+                    expr => format!("{} AS {}", expr, name),
+                }
+            }))),
+            Node::Aggregate { group_keys, aggregates, having, .. } => {
+                write!(f, "Aggregate: {}", list(&mut aggregates.iter().map(|(expr, _)| expr.to_string())))?;
+                if !group_keys.is_empty() {
+                    write!(f, " GROUP BY {}", list(&mut group_keys.iter().map(ToString::to_string)))?;
+                }
+                if let Some(having) = having {
+                    write!(f, " HAVING {}", having)?;
+                }
+                writeln!(f)
+            }
+            Node::Union { left, right, all } => {
+                writeln!(f, "Union{}", if *all { " All" } else { "" })?;
+                left.explain(f, depth + 1)?;
+                return right.explain(f, depth + 1);
+            }
+            Node::Derived { alias, .. } => writeln!(f, "Derived: {}", alias),
+            Node::Distinct { .. } => writeln!(f, "Distinct"),
+            Node::Sort { keys, .. } => writeln!(f, "Sort: {}", list(&mut keys.iter().map(|(expr, desc)| {
+                format!("{} {}", expr, if *desc { "DESC" } else { "ASC" })
+            }))),
+            Node::Limit { limit, offset, .. } => {
+                write!(f, "Limit:")?;
+                if let Some(limit) = limit {
+                    write!(f, " {}", limit)?;
+                }
+                if let Some(offset) = offset {
+                    write!(f, " OFFSET {}", offset)?;
+                }
+                writeln!(f)
+            }
+            Node::Update { table_name, .. } => writeln!(f, "Update: {}", table_name),
+            Node::Delete { table_name, .. } => writeln!(f, "Delete: {}", table_name),
+            Node::Drop { table_name, .. } => writeln!(f, "Drop: {}", table_name),
+            Node::Truncate { table_name } => writeln!(f, "Truncate: {}", table_name),
+            Node::AlterTable { table_name, op: AlterOperation::AddColumn(column) } => writeln!(f, "AddColumn: {}.{}", table_name, column.name),
+            Node::AlterTable { table_name, op: AlterOperation::DropColumn(column) } => writeln!(f, "DropColumn: {}.{}", table_name, column),
+            Node::Explain { .. } => writeln!(f, "Explain"),
+        }?;
+
+        match self {
+            Node::Filter { source, .. } | Node::Projection { source, .. } | Node::Aggregate { source, .. }
+            | Node::Derived { source, .. } | Node::Distinct { source } | Node::Sort { source, .. } | Node::Limit { source, .. }
+            | Node::Explain { source } => source.explain(f, depth + 1),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.explain(f, 0)
+    }
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Plan(pub Node);
 
+// 规划时发现的非致命问题, 不影响执行, 由调用方决定显示或忽略
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub message: String,
+    // 出问题处在输入中的位置, 规划时未给出解析位置则为 None
+    pub position: Option<Position>,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.position {
+            Some(position) => write!(f, "{} at {}", self.message, position),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
 impl Plan {
-    pub fn build(stmt: Statement) -> Self {
-        Planner::new().build(stmt)
+    pub fn build(stmt: Statement) -> Result<Self> {
+        Planner::new(None).build(stmt)
+    }
+
+    pub fn build_with_catalog(stmt: Statement, catalog: &dyn Catalog) -> Result<Self> {
+        Planner::new(Some(catalog)).build(stmt)
+    }
+
+    // 不执行查询, 按目录推导结果列的名称及类型; 计划应基于同一目录构建
+    pub fn output_schema(&self, catalog: &dyn Catalog) -> Result<Vec<(String, DataType)>> {
+        Planner::new(Some(catalog)).output_schema(&self.0)
+    }
+
+    // 将占位符 $n 替换为第 n 个参数, 参数个数须等于占位符的最大编号
+    pub fn bind(mut self, params: &[Value]) -> Result<Self> {
+        let mut exprs = self.0.expressions_mut();
+
+        let expected = exprs.iter().flat_map(|expr| expr.placeholders()).max().unwrap_or(0);
+        if params.len() != expected {
+            bail!("Expected {} parameters, got {}", expected, params.len());
+        }
+
+        for expr in exprs.iter_mut() {
+            Self::bind_expression(expr, params);
+        }
+
+        Ok(self)
+    }
+
+    fn bind_expression(expr: &mut Expression, params: &[Value]) {
+        match expr {
+            Expression::Placeholder(n) => {
+                *expr = Expression::Const(params[*n - 1].clone().into());
+                return;
+            }
+            // 子查询由执行器求值, 其中的占位符一并替换
+            Expression::Subquery(query) => {
+                for expr in query.expressions_mut() {
+                    Self::bind_expression(expr, params);
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        for child in expr.children_mut() {
+            Self::bind_expression(child, params);
+        }
+    }
+
+    // 将标量子查询替换为 eval 求得的值, 每个子查询求值一次; EXPLAIN 不求值
+    pub fn resolve_subqueries(mut self, eval: &mut dyn FnMut(&Statement) -> Result<Value>) -> Result<Self> {
+        if let Node::Explain { .. } = self.0 {
+            return Ok(self);
+        }
+
+        for expr in self.0.expressions_mut() {
+            Self::resolve_expression(expr, eval)?;
+        }
+
+        Ok(self)
+    }
+
+    fn resolve_expression(expr: &mut Expression, eval: &mut dyn FnMut(&Statement) -> Result<Value>) -> Result<()> {
+        if let Expression::Subquery(query) = expr {
+            *expr = Expression::Const(eval(query)?.into());
+            return Ok(());
+        }
+
+        for child in expr.children_mut() {
+            Self::resolve_expression(child, eval)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -36,25 +335,40 @@ impl Plan {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anyhow::Result;
-    use common::schema::Column;
-    use common::types::DataType;
+    use common::ast::{AggregateFunction, Const, Operator};
+    use common::schema::ColumnDefault;
+    use std::collections::HashMap;
+    use common::types::{Collation, DataType};
     use parser::Parser;
 
+    fn rows(values: Vec<Vec<Value>>) -> Vec<Vec<Expression>> {
+        values.into_iter().map(|row| row.into_iter().map(|v| Expression::Const(v.into())).collect()).collect()
+    }
+
+    // 由建表语句得到目录
+    fn catalog(ddl: &[&str]) -> Result<HashMap<String, Table>> {
+        ddl.iter().map(|sql| match Plan::build(Parser::new(sql).parse()?)? {
+            Plan(Node::Create { schema, .. }) => Ok((schema.name.clone(), schema)),
+            plan => bail!("not a create plan: {:?}", plan),
+        }).collect()
+    }
+
     #[test]
     fn test_plan_create_table() -> Result<()> {
-        let mut sql = "
+        let sql = "
             create table users (
                 a int default 0 not null,
                 b float not null,
                 c varchar null,
-                d bool default true
+                d bool default true,
+                e int default -1,
+                f float default -2.5
                 );
         ";
 
         let stmt = Parser::new(sql).parse()?;
 
-        let plan = Plan::build(stmt);
+        let plan = Plan::build(stmt)?;
 
         assert_eq!(plan, Plan(Node::Create {
             schema: Table {
@@ -63,32 +377,139 @@ mod tests {
                     Column {
                         name: "a".to_string(),
                         data_type: DataType::Integer,
+                        max_length: None,
                         nullable: false,
-                        default: Some(Value::Integer(0)),
+                        default: Some(ColumnDefault::Const(Value::Integer(0))),
+                        primary_key: false,
+                        unique: false,
+                        check: None,
+                        auto_increment: false,
+                        collation: Collation::Binary,
+                        comment: None,
                     },
                     Column {
                         name: "b".to_string(),
                         data_type: DataType::Float,
+                        max_length: None,
                         nullable: false,
                         default: None,
+                        primary_key: false,
+                        unique: false,
+                        check: None,
+                        auto_increment: false,
+                        collation: Collation::Binary,
+                        comment: None,
                     },
                     Column {
                         name: "c".to_string(),
                         data_type: DataType::String,
+                        max_length: None,
                         nullable: true,
-                        default: Some(Value::Null),
+                        default: Some(ColumnDefault::Const(Value::Null)),
+                        primary_key: false,
+                        unique: false,
+                        check: None,
+                        auto_increment: false,
+                        collation: Collation::Binary,
+                        comment: None,
                     },
                     Column {
                         name: "d".to_string(),
                         data_type: DataType::Boolean,
+                        max_length: None,
                         nullable: false,
-                        default: Some(Value::Boolean(true)),
+                        default: Some(ColumnDefault::Const(Value::Boolean(true))),
+                        primary_key: false,
+                        unique: false,
+                        check: None,
+                        auto_increment: false,
+                        collation: Collation::Binary,
+                        comment: None,
+                    },
+                    Column {
+                        name: "e".to_string(),
+                        data_type: DataType::Integer,
+                        max_length: None,
+                        nullable: false,
+                        default: Some(ColumnDefault::Const(Value::Integer(-1))),
+                        primary_key: false,
+                        unique: false,
+                        check: None,
+                        auto_increment: false,
+                        collation: Collation::Binary,
+                        comment: None,
+                    },
+                    Column {
+                        name: "f".to_string(),
+                        data_type: DataType::Float,
+                        max_length: None,
+                        nullable: false,
+                        default: Some(ColumnDefault::Const(Value::Float(-2.5))),
+                        primary_key: false,
+                        unique: false,
+                        check: None,
+                        auto_increment: false,
+                        collation: Collation::Binary,
+                        comment: None,
                     },
                 ],
-            }
+                comment: None,
+            },
+            if_not_exists: false,
         }
         ));
 
+        let stmt = Parser::new(" create table if not exists users (a int);").parse()?;
+
+        assert!(matches!(Plan::build(stmt)?, Plan(Node::Create { if_not_exists: true, .. })));
+
+        let stmt = Parser::new(" create table t (a int null, b int, c int, primary key (a, b));").parse()?;
+
+        let Plan(Node::Create { schema, .. }) = Plan::build(stmt)? else { panic!("not a create plan") };
+        assert_eq!(schema.columns.iter().map(|col| (col.primary_key, col.nullable)).collect::<Vec<_>>(), vec![
+            (true, false),
+            (true, false),
+            (false, false),
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_create_table_default_type() -> Result<()> {
+        let mut stmt = Parser::new(" create table t (a int default 'abc');").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), r#"Invalid default value for column a: Type mismatch: expected Integer, got String("abc")"#);
+
+        stmt = Parser::new(" create table t (a bool not null default null);").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "Invalid default value for column a: cannot be null");
+
+        stmt = Parser::new(" create table t (a bool default 1, b int default true);").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "Invalid default value for column a: Type mismatch: expected Boolean, got Integer(1)");
+
+        stmt = Parser::new(" create table t (a int default b);").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "Column b is not allowed in a constant expression");
+
+        // 默认值中的常量表达式先折叠
+        stmt = Parser::new(" create table t (a int default 60 * 60, b float default -(1 + 0.5));").parse()?;
+        let Plan(Node::Create { schema, .. }) = Plan::build(stmt)? else { panic!("not a create plan") };
+        assert_eq!(schema.columns[0].default, Some(ColumnDefault::Const(Value::Integer(3600))));
+        assert_eq!(schema.columns[1].default, Some(ColumnDefault::Const(Value::Float(-1.5))));
+
+        // 整数默认值转为浮点
+        stmt = Parser::new(" create table t (a float default 1, b varchar null default null);").parse()?;
+        let Plan(Node::Create { schema, .. }) = Plan::build(stmt)? else { panic!("not a create plan") };
+        assert_eq!(schema.columns[0].default, Some(ColumnDefault::Const(Value::Float(1.0))));
+        assert_eq!(schema.columns[1].default, Some(ColumnDefault::Const(Value::Null)));
+
+        // 含子查询的默认值保留为表达式, 插入时求值
+        stmt = Parser::new(" create table t (a int default (select count(*) from t) + 1);").parse()?;
+        let Plan(Node::Create { schema, .. }) = Plan::build(stmt)? else { panic!("not a create plan") };
+        let Some(ColumnDefault::Dynamic(expr)) = &schema.columns[0].default else { panic!("not a dynamic default") };
+        assert_eq!(expr.to_string(), "(SELECT COUNT(*) FROM t) + 1");
+
+        stmt = Parser::new(" create table t (a int, b int default (select count(*) from t) + a);").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "Column a is not allowed in a constant expression");
+
         Ok(())
     }
 
@@ -96,26 +517,136 @@ mod tests {
     fn test_plan_insert() -> Result<()> {
         let mut sql = " insert into users values (1, 2.3, 'abc', true);";
         let mut stmt = Parser::new(sql).parse()?;
-        let mut plan = Plan::build(stmt);
+        let mut plan = Plan::build(stmt)?;
 
         assert_eq!(plan, Plan(Node::Insert {
             table_name: "users".to_string(),
             columns: vec![],
-            values: vec![vec![Value::Integer(1), Value::Float(2.3), Value::String("abc".to_string()), Value::Boolean(true)]],
+            values: rows(vec![vec![Value::Integer(1), Value::Float(2.3), Value::String("abc".to_string()), Value::Boolean(true)]]),
+            returning: None,
         }));
 
         sql = " insert into users (c1,c2,c3,c4) values (1, 2.3, 'abc', true), (2, 4.5, 'def', false);";
         stmt = Parser::new(sql).parse()?;
-        plan = Plan::build(stmt);
+        plan = Plan::build(stmt)?;
 
         assert_eq!(plan, Plan(Node::Insert {
             table_name: "users".to_string(),
             columns: vec!["c1".to_string(), "c2".to_string(), "c3".to_string(), "c4".to_string()],
-            values: vec![
+            values: rows(vec![
                 vec![Value::Integer(1), Value::Float(2.3), Value::String("abc".to_string()), Value::Boolean(true)],
                 vec![Value::Integer(2), Value::Float(4.5), Value::String("def".to_string()), Value::Boolean(false)],
-            ],
+            ]),
+            returning: None,
+        }));
+
+        sql = " insert into t values (-5, -2.3), (-(-1), -0.5);";
+        stmt = Parser::new(sql).parse()?;
+        plan = Plan::build(stmt)?;
+
+        assert_eq!(plan, Plan(Node::Insert {
+            table_name: "t".to_string(),
+            columns: vec![],
+            values: rows(vec![
+                vec![Value::Integer(-5), Value::Float(-2.3)],
+                vec![Value::Integer(1), Value::Float(-0.5)],
+            ]),
+            returning: None,
+        }));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_fold_constant() -> Result<()> {
+        let fold = |sql: &str| match Parser::new(&format!(" select * from t where {};", sql)).parse()? {
+            Statement::Select { filter: Some(expr), .. } => Planner::fold_constant(&expr),
+            stmt => panic!("not a filtered select: {:?}", stmt),
+        };
+
+        assert_eq!(fold("1 + 2 * 3")?, Value::Integer(7));
+        assert_eq!(fold("-(7 % 4) - 0.5")?, Value::Float(-3.5));
+        assert_eq!(fold("1 < 2 and not false")?, Value::Boolean(true));
+        assert_eq!(fold("null + 1")?, Value::Null);
+        assert_eq!(fold("null is null")?, Value::Boolean(true));
+
+        assert_eq!(fold("1 / 0").unwrap_err().to_string(), "Division by zero");
+        assert_eq!(fold("a + 1").unwrap_err().to_string(), "Column a is not allowed in a constant expression");
+        assert_eq!(fold("1 between 0 and 2").unwrap_err().to_string(), "Not a constant expression: 1 BETWEEN 0 AND 2");
+
+        let stmt = Parser::new(" insert into t values (1 + 2, 'a'), (2 * -3, 'b');").parse()?;
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Insert {
+            table_name: "t".to_string(),
+            columns: vec![],
+            values: rows(vec![
+                vec![Value::Integer(3), Value::String("a".to_string())],
+                vec![Value::Integer(-6), Value::String("b".to_string())],
+            ]),
+            returning: None,
         }));
+
+        let stmt = Parser::new(" insert into t values (a + 1);").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "Column a is not allowed in a constant expression");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_bind() -> Result<()> {
+        let plan = Plan::build(Parser::new(" insert into t values (?, ?);").parse()?)?;
+        assert_eq!(plan, Plan(Node::Insert {
+            table_name: "t".to_string(),
+            columns: vec![],
+            values: vec![vec![Expression::Placeholder(1), Expression::Placeholder(2)]],
+            returning: None,
+        }));
+
+        assert_eq!(plan.bind(&[Value::Integer(1), Value::String("a".to_string())])?, Plan(Node::Insert {
+            table_name: "t".to_string(),
+            columns: vec![],
+            values: rows(vec![vec![Value::Integer(1), Value::String("a".to_string())]]),
+            returning: None,
+        }));
+
+        let plan = || -> Result<Plan> { Plan::build(Parser::new(" insert into t values (?, ?);").parse()?) };
+        assert_eq!(plan()?.bind(&[Value::Integer(1)]).unwrap_err().to_string(), "Expected 2 parameters, got 1");
+        assert_eq!(plan()?.bind(&[Value::Integer(1), Value::Integer(2), Value::Integer(3)]).unwrap_err().to_string(), "Expected 2 parameters, got 3");
+
+        // 同一参数可多次引用
+        let plan = Plan::build(Parser::new(" select a from t where a > $1 and b < $1;").parse()?)?.bind(&[Value::Float(1.5)])?;
+        let Plan(Node::Projection { source, .. }) = plan else { panic!("not a projection plan") };
+        assert_eq!(*source, Node::Filter {
+            source: Box::new(Node::Scan { table_name: "t".to_string() }),
+            predicate: Expression::binary(
+                Expression::binary(Expression::Column("a".to_string()), Operator::GreaterThan, Const::Float(1.5).into()),
+                Operator::And,
+                Expression::binary(Expression::Column("b".to_string()), Operator::LessThan, Const::Float(1.5).into()),
+            ),
+        });
+
+        // 标量子查询中的占位符计入参数个数并一并替换
+        let plan = || -> Result<Plan> { Plan::build(Parser::new(" select a from t where a = (select max(a) from t where a < ?);").parse()?) };
+        let Plan(Node::Projection { source, .. }) = plan()?.bind(&[Value::Integer(1)])? else { panic!("not a projection plan") };
+        let Node::Filter { predicate, .. } = *source else { panic!("not a filter") };
+        assert_eq!(predicate.to_string(), "a = (SELECT MAX(a) FROM t WHERE a < 1)");
+        assert_eq!(plan()?.bind(&[]).unwrap_err().to_string(), "Expected 1 parameters, got 0");
+
+        // 没有占位符时无需参数
+        assert!(Plan::build(Parser::new(" delete from t;").parse()?)?.bind(&[]).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_insert_arity() -> Result<()> {
+        let mut stmt = Parser::new(" insert into t (a, b) values (1, 2), (3);").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "Insert row 1 has 1 values, expected 2");
+
+        stmt = Parser::new(" insert into t (a, b) values (1, 2, 3);").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "Insert row 0 has 3 values, expected 2");
+
+        stmt = Parser::new(" insert into t (a, b) values (1, 2), (3, 4), (5, 6);").parse()?;
+        assert!(matches!(Plan::build(stmt)?, Plan(Node::Insert { values, .. }) if values.len() == 3));
+
         Ok(())
     }
 
@@ -123,12 +654,831 @@ mod tests {
     fn test_plan_select() -> Result<()> {
         let sql = " select * from users;";
         let stmt = Parser::new(sql).parse()?;
-        let plan = Plan::build(stmt);
+        let plan = Plan::build(stmt)?;
 
         assert_eq!(plan, Plan(Node::Scan {
             table_name: "users".to_string(),
         }));
 
+        let sql = " select a, b from users;";
+        let stmt = Parser::new(sql).parse()?;
+        let plan = Plan::build(stmt)?;
+
+        assert_eq!(plan, Plan(Node::Projection {
+            source: Box::new(Node::Scan {
+                table_name: "users".to_string(),
+            }),
+            exprs: vec![
+                (Expression::Column("a".to_string()), "a".to_string()),
+                (Expression::Column("b".to_string()), "b".to_string()),
+            ],
+        }));
+
+        let sql = " select * from users where id = 1;";
+        let stmt = Parser::new(sql).parse()?;
+        let plan = Plan::build(stmt)?;
+
+        assert_eq!(plan, Plan(Node::Filter {
+            source: Box::new(Node::Scan {
+                table_name: "users".to_string(),
+            }),
+            predicate: Expression::binary(Expression::Column("id".to_string()), Operator::Equal, Const::Integer(1).into()),
+        }));
+
+        let sql = " select a from users where id = 1;";
+        let stmt = Parser::new(sql).parse()?;
+        let plan = Plan::build(stmt)?;
+
+        // Projection -> Filter -> Scan
+        assert_eq!(plan, Plan(Node::Projection {
+            source: Box::new(Node::Filter {
+                source: Box::new(Node::Scan {
+                    table_name: "users".to_string(),
+                }),
+                predicate: Expression::binary(Expression::Column("id".to_string()), Operator::Equal, Const::Integer(1).into()),
+            }),
+            exprs: vec![(Expression::Column("a".to_string()), "a".to_string())],
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_select_join() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let scan = |t: &str| Box::new(Node::Scan { table_name: t.to_string() });
+
+        let stmt = Parser::new(" select * from a join b on a.id = b.a_id join c on b.id = c.b_id where a.x = 1;").parse()?;
+
+        // Filter -> Join(Join(a, b), c)
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Filter {
+            source: Box::new(Node::NestedLoopJoin {
+                left: Box::new(Node::NestedLoopJoin {
+                    left: scan("a"),
+                    right: scan("b"),
+                    predicate: Expression::binary(col("a.id"), Operator::Equal, col("b.a_id")),
+                }),
+                right: scan("c"),
+                predicate: Expression::binary(col("b.id"), Operator::Equal, col("c.b_id")),
+            }),
+            predicate: Expression::binary(col("a.x"), Operator::Equal, Const::Integer(1).into()),
+        }));
+
+        let catalog = catalog(&[" create table a (id int, name varchar);", " create table b (id int, a_id int);"])?;
+
+        let stmt = Parser::new(" select a.name, a_id from a join b on a.id = b.a_id;").parse()?;
+        assert!(Plan::build_with_catalog(stmt, &catalog).is_ok());
+
+        let stmt = Parser::new(" select a.a_id from a join b on a.id = b.a_id;").parse()?;
+        assert_eq!(Plan::build_with_catalog(stmt, &catalog).unwrap_err().to_string(), "Column a.a_id does not exist in table a, b");
+
+        let stmt = Parser::new(" select * from a join b on a.id = b.x;").parse()?;
+        assert_eq!(Plan::build_with_catalog(stmt, &catalog).unwrap_err().to_string(), "Column b.x does not exist in table a, b");
+
+        let stmt = Parser::new(" select * from a join c on a.id = c.id;").parse()?;
+        assert_eq!(Plan::build_with_catalog(stmt, &catalog).unwrap_err().to_string(), "Table c does not exist");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_select_clause_scope() -> Result<()> {
+        let catalog = catalog(&[" create table a (id int, name varchar, age int);", " create table b (id int, a_id int);"])?;
+        let plan = |sql: &str| Plan::build_with_catalog(Parser::new(sql).parse()?, &catalog);
+
+        assert!(plan(" select name from a where age > 1 order by id;").is_ok());
+        assert!(plan(" select age, count(*) as n from a group by age having count(id) > 1 order by n desc, age;").is_ok());
+        assert!(plan(" select age + 1 as x from a order by x;").is_ok());
+
+        // 未投影的列同样须属于来源表
+        assert_eq!(plan(" select * from a where agee = 1;").unwrap_err().to_string(), "Column agee does not exist in table a");
+        assert_eq!(plan(" select count(*) from a group by agee;").unwrap_err().to_string(), "Column agee does not exist in table a");
+        assert_eq!(plan(" select count(*) from a having max(agee) > 1;").unwrap_err().to_string(), "Column agee does not exist in table a");
+        assert_eq!(plan(" select name from a order by agee;").unwrap_err().to_string(), "Column agee does not exist in table a");
+        assert_eq!(plan(" select age, count(*) from a group by age order by agee;").unwrap_err().to_string(), "Column agee does not exist in table a");
+
+        // 多个表中都有的列须加表名
+        assert_eq!(plan(" select name from a join b on a.id = a_id where id > 10;").unwrap_err().to_string(), "Ambiguous column id");
+        assert_eq!(plan(" select name from a join b on a.id = a_id order by id;").unwrap_err().to_string(), "Ambiguous column id");
+        assert!(plan(" select name from a join b on a.id = a_id where b.id > 10 order by a.id;").is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_select_cross_join() -> Result<()> {
+        let scan = |t: &str| Box::new(Node::Scan { table_name: t.to_string() });
+        let always = || Expression::from(Const::Boolean(true));
+
+        let stmt = Parser::new(" select * from a, b;").parse()?;
+        assert_eq!(Plan::build(stmt)?, Plan(Node::NestedLoopJoin {
+            left: scan("a"),
+            right: scan("b"),
+            predicate: always(),
+        }));
+
+        // Join(Join(a, b), c)
+        let stmt = Parser::new(" select * from a, b, c;").parse()?;
+        assert_eq!(Plan::build(stmt)?, Plan(Node::NestedLoopJoin {
+            left: Box::new(Node::NestedLoopJoin {
+                left: scan("a"),
+                right: scan("b"),
+                predicate: always(),
+            }),
+            right: scan("c"),
+            predicate: always(),
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_alter_table() -> Result<()> {
+        let stmt = Parser::new(" alter table t add column c float default 1;").parse()?;
+        assert_eq!(Plan::build(stmt)?, Plan(Node::AlterTable {
+            table_name: "t".to_string(),
+            op: AlterOperation::AddColumn(Column {
+                name: "c".to_string(),
+                data_type: DataType::Float,
+                max_length: None,
+                nullable: false,
+                default: Some(ColumnDefault::Const(Value::Float(1.0))),
+                primary_key: false,
+                unique: false,
+                check: None,
+                auto_increment: false,
+                collation: Collation::Binary,
+                comment: None,
+            }),
+        }));
+
+        let catalog = catalog(&[" create table t (a int);", " create table p (id int primary key, a int);"])?;
+
+        let stmt = Parser::new(" alter table t add a int;").parse()?;
+        assert_eq!(Plan::build_with_catalog(stmt, &catalog).unwrap_err().to_string(), "Column a already exists in table t");
+
+        let stmt = Parser::new(" alter table u add a int;").parse()?;
+        assert_eq!(Plan::build_with_catalog(stmt, &catalog).unwrap_err().to_string(), "Table u does not exist");
+
+        let stmt = Parser::new(" alter table t add id int primary key;").parse()?;
+        assert_eq!(Plan::build_with_catalog(stmt, &catalog).unwrap_err().to_string(), "Cannot add primary key column id");
+
+        let stmt = Parser::new(" alter table t drop column b;").parse()?;
+        assert_eq!(Plan::build_with_catalog(stmt, &catalog).unwrap_err().to_string(), "Column b does not exist in table t");
+
+        let stmt = Parser::new(" alter table p drop column a;").parse()?;
+        assert_eq!(Plan::build_with_catalog(stmt, &catalog)?, Plan(Node::AlterTable {
+            table_name: "p".to_string(),
+            op: AlterOperation::DropColumn("a".to_string()),
+        }));
+
+        let stmt = Parser::new(" alter table p drop column id;").parse()?;
+        assert_eq!(Plan::build_with_catalog(stmt, &catalog).unwrap_err().to_string(), "Cannot drop primary key column id");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_explain() -> Result<()> {
+        let stmt = Parser::new(" explain select a, b + 1 as c from t where a > 1 order by b desc limit 10;").parse()?;
+        let plan = Plan::build(stmt)?;
+
+        assert!(matches!(&plan, Plan(Node::Explain { source }) if matches!(**source, Node::Limit { .. })));
+        assert_eq!(plan.0.to_string(), "\
+Explain
+  Limit: 10
+    Projection: a, b + 1 AS c
+      Sort: b DESC
+        Filter: a > 1
+          Scan: t
+");
+
+        let stmt = Parser::new(" select k, count(*) from a join b on a.id = b.id group by k having count(*) > 1;").parse()?;
+        assert_eq!(Plan::build(stmt)?.0.to_string(), "\
+Aggregate: k, COUNT(*) GROUP BY k HAVING COUNT(*) > 1
+  NestedLoopJoin: a.id = b.id
+    Scan: a
+    Scan: b
+");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_select_aggregate() -> Result<()> {
+        let stmt = Parser::new(" select count(*), sum(a) + 1 as total from users where a > 0;").parse()?;
+
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Aggregate {
+            source: Box::new(Node::Filter {
+                source: Box::new(Node::Scan {
+                    table_name: "users".to_string(),
+                }),
+                predicate: Expression::binary(Expression::Column("a".to_string()), Operator::GreaterThan, Const::Integer(0).into()),
+            }),
+            group_keys: vec![],
+            aggregates: vec![
+                (Expression::Aggregate { func: AggregateFunction::Count, arg: None }, "expr0".to_string()),
+                (Expression::binary(
+                    Expression::Aggregate { func: AggregateFunction::Sum, arg: Some(Box::new(Expression::Column("a".to_string()))) },
+                    Operator::Add,
+                    Const::Integer(1).into(),
+                ), "total".to_string()),
+            ],
+            having: None,
+        }));
+
+        let stmt = Parser::new(" select a, count(*) from users;").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "Column a must appear in GROUP BY or be used in an aggregate function");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_select_group_by() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let count = || Expression::Aggregate { func: AggregateFunction::Count, arg: None };
+
+        let stmt = Parser::new(" select a, count(*) from users group by a;").parse()?;
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Aggregate {
+            source: Box::new(Node::Scan {
+                table_name: "users".to_string(),
+            }),
+            group_keys: vec![col("a")],
+            aggregates: vec![(col("a"), "a".to_string()), (count(), "expr1".to_string())],
+            having: None,
+        }));
+
+        let stmt = Parser::new(" select a + 1 as k, max(b) from users group by a + 1 having count(*) > 1;").parse()?;
+        let key = Expression::binary(col("a"), Operator::Add, Const::Integer(1).into());
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Aggregate {
+            source: Box::new(Node::Scan {
+                table_name: "users".to_string(),
+            }),
+            group_keys: vec![key.clone()],
+            aggregates: vec![
+                (key, "k".to_string()),
+                (Expression::Aggregate { func: AggregateFunction::Max, arg: Some(Box::new(col("b"))) }, "expr1".to_string()),
+            ],
+            having: Some(Expression::binary(count(), Operator::GreaterThan, Const::Integer(1).into())),
+        }));
+
+        let stmt = Parser::new(" select a, b from users group by a;").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "Column b must appear in GROUP BY or be used in an aggregate function");
+
+        let stmt = Parser::new(" select a from users group by a having b > 1;").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "Column b must appear in GROUP BY or be used in an aggregate function");
+
+        let stmt = Parser::new(" select * from users group by a;").parse()?;
+        assert_eq!(Plan::build(stmt).unwrap_err().to_string(), "SELECT * cannot be used with aggregation");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_select_distinct() -> Result<()> {
+        let stmt = Parser::new(" select distinct a from users limit 2;").parse()?;
+
+        // Limit -> Distinct -> Projection -> Scan
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Limit {
+            source: Box::new(Node::Distinct {
+                source: Box::new(Node::Projection {
+                    source: Box::new(Node::Scan {
+                        table_name: "users".to_string(),
+                    }),
+                    exprs: vec![(Expression::Column("a".to_string()), "a".to_string())],
+                }),
+            }),
+            limit: Some(2),
+            offset: None,
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_select_order_by() -> Result<()> {
+        let stmt = Parser::new(" select a from users order by a desc, b;").parse()?;
+
+        // 排序可引用未投影的列, 投影在最外层
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Projection {
+            source: Box::new(Node::Sort {
+                source: Box::new(Node::Scan {
+                    table_name: "users".to_string(),
+                }),
+                keys: vec![
+                    (Expression::Column("a".to_string()), true),
+                    (Expression::Column("b".to_string()), false),
+                ],
+            }),
+            exprs: vec![(Expression::Column("a".to_string()), "a".to_string())],
+        }));
+
+        // 别名换为对应的表达式, 聚合之后的键换为结果列
+        let keys = |sql: &str| -> Result<Vec<(Expression, bool)>> {
+            fn find(node: Node) -> Vec<(Expression, bool)> {
+                match node {
+                    Node::Sort { keys, .. } => keys,
+                    Node::Projection { source, .. } => find(*source),
+                    node => panic!("no sort node: {:?}", node),
+                }
+            }
+            Ok(find(Plan::build(Parser::new(sql).parse()?)?.0))
+        };
+        assert_eq!(keys(" select a + 1 as x from users order by x desc;")?, vec![
+            (Expression::binary(Expression::Column("a".to_string()), Operator::Add, Const::Integer(1).into()), true),
+        ]);
+        assert_eq!(keys(" select a, count(*) as n from users group by a order by count(*), a;")?, vec![
+            (Expression::Column("n".to_string()), false),
+            (Expression::Column("a".to_string()), false),
+        ]);
+
+        // 聚合查询的排序键须在结果列中
+        let err = |sql: &str| -> Result<String> { Ok(Plan::build(Parser::new(sql).parse()?).unwrap_err().to_string()) };
+        assert_eq!(err(" select count(*) from users group by a order by a;")?, "ORDER BY key a must appear in the select list of an aggregate query");
+        assert_eq!(err(" select a from users group by a order by max(b);")?, "ORDER BY key MAX(b) must appear in the select list of an aggregate query");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_select_limit() -> Result<()> {
+        let stmt = Parser::new(" select * from users order by a limit 10 offset 5;").parse()?;
+
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Limit {
+            source: Box::new(Node::Sort {
+                source: Box::new(Node::Scan {
+                    table_name: "users".to_string(),
+                }),
+                keys: vec![(Expression::Column("a".to_string()), false)],
+            }),
+            limit: Some(10),
+            offset: Some(5),
+        }));
+
+        let stmt = Parser::new(" select * from users offset 5;").parse()?;
+        assert!(matches!(Plan::build(stmt)?, Plan(Node::Limit { limit: None, offset: Some(5), .. })));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_update() -> Result<()> {
+        let sql = " update users set a = 1 where id = 2;";
+        let stmt = Parser::new(sql).parse()?;
+        let plan = Plan::build(stmt)?;
+
+        assert_eq!(plan, Plan(Node::Update {
+            table_name: "users".to_string(),
+            assignments: vec![("a".to_string(), Const::Integer(1).into())],
+            filter: Some(Expression::binary(Expression::Column("id".to_string()), Operator::Equal, Const::Integer(2).into())),
+            returning: None,
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_delete() -> Result<()> {
+        let mut sql = " delete from users;";
+        let mut stmt = Parser::new(sql).parse()?;
+        let mut plan = Plan::build(stmt)?;
+
+        assert_eq!(plan, Plan(Node::Delete {
+            table_name: "users".to_string(),
+            filter: None,
+            returning: None,
+        }));
+
+        sql = " delete from users where id = 1;";
+        stmt = Parser::new(sql).parse()?;
+        plan = Plan::build(stmt)?;
+
+        assert_eq!(plan, Plan(Node::Delete {
+            table_name: "users".to_string(),
+            filter: Some(Expression::binary(Expression::Column("id".to_string()), Operator::Equal, Const::Integer(1).into())),
+            returning: None,
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_null_comparison() -> Result<()> {
+        let strict = |sql: &str| Planner::new(None).with_strict_null(true).build(Parser::new(sql).parse()?);
+
+        for (sql, expr) in [
+            (" select * from t where a = null;", "a = NULL"),
+            (" select * from t where b > 1 and null <> a;", "NULL <> a"),
+            (" select a = null from t;", "a = NULL"),
+            (" select * from t join u on t.id = null;", "t.id = NULL"),
+            (" update t set a = 1 where not (a = null);", "a = NULL"),
+            (" delete from t where a = null or b = 1;", "a = NULL"),
+            (" explain select * from t where a = null;", "a = NULL"),
+        ] {
+            assert_eq!(
+                strict(sql).unwrap_err().to_string(),
+                format!("Comparison with NULL always yields NULL, use IS [NOT] NULL instead: {}", expr),
+            );
+        }
+
+        for sql in [" select * from t where a is null;", " select * from t where a is not null;", " update t set a = null;", " insert into t values (null);"] {
+            strict(sql)?;
+        }
+
+        // 默认不检查, 比较照常求值为 Null
+        let plan = Plan::build(Parser::new(" select * from t where a = null;").parse()?)?;
+        assert_eq!(plan, Plan(Node::Filter {
+            source: Box::new(Node::Scan { table_name: "t".to_string() }),
+            predicate: Expression::binary(Expression::Column("a".to_string()), Operator::Equal, Const::Null.into()),
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_warnings() -> Result<()> {
+        let warnings_of = |sql: &str| -> Result<Vec<Warning>> {
+            let mut parser = Parser::new(sql);
+            let stmt = parser.parse()?;
+            Ok(Planner::new(None).with_positions(parser.positions()).build_with_warnings(stmt)?.1)
+        };
+        let message = |expr: &str| format!("Comparison with NULL always yields NULL, use IS [NOT] NULL instead: {}", expr);
+
+        assert_eq!(warnings_of(" select * from t where a = null;")?, vec![Warning {
+            message: message("a = NULL"),
+            position: Some(Position { line: 1, column: 24 }),
+        }]);
+        assert_eq!(warnings_of(" select * from t where a is null and b = 1;")?, vec![]);
+        assert_eq!(warnings_of(" update t set a = null;")?, vec![]);
+
+        // 警告不影响计划; 未给出解析位置时不带位置
+        let (plan, warnings) = Planner::new(None).build_with_warnings(Parser::new(" select * from t where a = null;").parse()?)?;
+        assert_eq!(plan, Plan::build(Parser::new(" select * from t where a = null;").parse()?)?);
+        assert_eq!(warnings, vec![Warning { message: message("a = NULL"), position: None }]);
+        assert_eq!(warnings[0].to_string(), message("a = NULL"));
+
+        // 每处比较各一条, 相同的表达式按出现顺序对应各自的位置
+        let found = warnings_of(" select * from t\n where a = null or (b <> null and c = (select max(d) from u where d = null))\n   or a = null;")?;
+        assert_eq!(found.iter().map(ToString::to_string).collect::<Vec<_>>(), vec![
+            format!("{} at line 2, column 8", message("a = NULL")),
+            format!("{} at line 2, column 21", message("b <> NULL")),
+            format!("{} at line 2, column 67", message("d = NULL")),
+            format!("{} at line 3, column 7", message("a = NULL")),
+        ]);
+
+        // 严格模式下仍为错误
+        let mut parser = Parser::new(" select * from t where a = null;");
+        let stmt = parser.parse()?;
+        assert_eq!(
+            Planner::new(None).with_strict_null(true).with_positions(parser.positions()).build_with_warnings(stmt).unwrap_err().to_string(),
+            format!("{} at line 1, column 24", message("a = NULL")),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_drop_table() -> Result<()> {
+        let mut stmt = Parser::new(" drop table users;").parse()?;
+
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Drop {
+            table_name: "users".to_string(),
+            if_exists: false,
+        }));
+
+        stmt = Parser::new(" drop table if exists users;").parse()?;
+
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Drop {
+            table_name: "users".to_string(),
+            if_exists: true,
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_truncate_table() -> Result<()> {
+        let stmt = Parser::new(" truncate table users;").parse()?;
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Truncate { table_name: "users".to_string() }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_union() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+        let project = |table: &str, exprs: Vec<(Expression, String)>| Node::Projection {
+            source: Box::new(Node::Scan { table_name: table.to_string() }),
+            exprs,
+        };
+
+        let stmt = Parser::new(" select a from t union all select b as a from u;").parse()?;
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Union {
+            left: Box::new(project("t", vec![(col("a"), "a".to_string())])),
+            right: Box::new(project("u", vec![(col("b"), "a".to_string())])),
+            all: true,
+        }));
+
+        let stmt = Parser::new(" explain select a from t union select b from u;").parse()?;
+        assert_eq!(Plan::build(stmt)?.0.to_string(), "\
+Explain
+  Union
+    Projection: a
+      Scan: t
+    Projection: b
+      Scan: u
+");
+
+        let plan = |sql: &str| Plan::build(Parser::new(sql).parse()?);
+        assert_eq!(
+            plan(" select a, b from t union select a from u;").unwrap_err().to_string(),
+            "Each UNION query must have the same number of columns, got 2 and 1",
+        );
+        assert_eq!(
+            plan(" select a from t union select a from u union all select a, count(*) from v group by a;").unwrap_err().to_string(),
+            "Each UNION query must have the same number of columns, got 1 and 2",
+        );
+        // 无目录时 SELECT * 的列数未知
+        assert!(plan(" select * from t union select a from u;").is_ok());
+
+        let catalog = catalog(&[" create table t (a int, b varchar, c date);", " create table u (x float, y varchar null);"])?;
+        let plan = |sql: &str| Plan::build_with_catalog(Parser::new(sql).parse()?, &catalog);
+
+        assert!(plan(" select a, b from t union select x, y from u;").is_ok());
+        assert!(plan(" select a, null from t union select x + 1, y from u;").is_ok());
+        assert!(plan(" select c from t union select cast(y as timestamp) from u;").is_ok());
+        assert_eq!(plan(" select * from t union select x, y from u;").unwrap_err().to_string(), "Each UNION query must have the same number of columns, got 3 and 2");
+        assert_eq!(plan(" select a, b from t union select y, x from u;").unwrap_err().to_string(), "UNION column 1 has incompatible types INTEGER and VARCHAR");
+        assert_eq!(plan(" select null, c from t union all select y, x from u;").unwrap_err().to_string(), "UNION column 2 has incompatible types DATE and FLOAT");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_derived_table() -> Result<()> {
+        let col = |c: &str| Expression::Column(c.to_string());
+
+        let stmt = Parser::new(" select b from (select a as b from t where a > 1) as sub;").parse()?;
+        assert_eq!(Plan::build(stmt)?, Plan(Node::Projection {
+            source: Box::new(Node::Derived {
+                source: Box::new(Node::Projection {
+                    source: Box::new(Node::Filter {
+                        source: Box::new(Node::Scan { table_name: "t".to_string() }),
+                        predicate: Expression::binary(col("a"), Operator::GreaterThan, Const::Integer(1).into()),
+                    }),
+                    exprs: vec![(col("a"), "b".to_string())],
+                }),
+                alias: "sub".to_string(),
+            }),
+            exprs: vec![(col("b"), "b".to_string())],
+        }));
+
+        let stmt = Parser::new(" explain select * from (select a from t union all select a from u) as s, v;").parse()?;
+        assert_eq!(Plan::build(stmt)?.0.to_string(), "\
+Explain
+  NestedLoopJoin: true
+    Derived: s
+      Union All
+        Projection: a
+          Scan: t
+        Projection: a
+          Scan: u
+    Scan: v
+");
+
+        let catalog = catalog(&[" create table t (a int, b varchar);"])?;
+        let plan = |sql: &str| Plan::build_with_catalog(Parser::new(sql).parse()?, &catalog);
+
+        // 派生表的列为内部查询的结果列, 以别名限定
+        assert!(plan(" select sub.x, b from (select a + 1 as x, t.b from t) as sub;").is_ok());
+        assert!(plan(" select s.a, t.b from (select a from t) as s join t on s.a = t.a;").is_ok());
+        assert!(plan(" select n from (select count(*) as n from (select * from t) as s1) as s2;").is_ok());
+        assert_eq!(plan(" select a from (select a + 1 as x from t) as sub;").unwrap_err().to_string(), "Column a does not exist in table sub");
+        assert_eq!(plan(" select t.x from (select a as x from t) as sub;").unwrap_err().to_string(), "Column t.x does not exist in table sub");
+        assert_eq!(plan(" select x from (select c as x from t) as sub;").unwrap_err().to_string(), "Column c does not exist in table t");
+        assert_eq!(plan(" select x from (select a as x from missing) as sub;").unwrap_err().to_string(), "Table missing does not exist");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_output_schema() -> Result<()> {
+        let catalog = catalog(&[" create table users (id int, name varchar, score decimal(5, 2));", " create table orders (id int, user_id int, amount float);"])?;
+        let schema = |sql: &str| Plan::build_with_catalog(Parser::new(sql).parse()?, &catalog)?.output_schema(&catalog);
+        let columns = |columns: &[(&str, DataType)]| columns.iter().map(|(name, data_type)| (name.to_string(), data_type.clone())).collect::<Vec<_>>();
+
+        assert_eq!(schema(" select * from users where id > 1 order by name limit 2;")?, columns(&[
+            ("id", DataType::Integer),
+            ("name", DataType::String),
+            ("score", DataType::Decimal { precision: 5, scale: 2 }),
+        ]));
+        assert_eq!(schema(" select name, id + 0.5 as x, null from users;")?, columns(&[
+            ("name", DataType::String),
+            ("x", DataType::Float),
+            ("expr2", DataType::String),
+        ]));
+        assert_eq!(schema(" select name, count(*), avg(score), max(score) as top from users group by name;")?, columns(&[
+            ("name", DataType::String),
+            ("expr1", DataType::Integer),
+            ("expr2", DataType::Float),
+            ("top", DataType::Decimal { precision: 5, scale: 2 }),
+        ]));
+
+        // 连接结果的列名限定为 table.column
+        assert_eq!(schema(" select * from users join orders on users.id = orders.user_id;")?.into_iter().map(|(name, _)| name).collect::<Vec<_>>(), vec![
+            "users.id", "users.name", "users.score", "orders.id", "orders.user_id", "orders.amount",
+        ]);
+        assert_eq!(schema(" select * from (select name as n, score * 2 as s from users) as u, orders where orders.id > 1;")?, columns(&[
+            ("u.n", DataType::String),
+            ("u.s", DataType::Decimal { precision: 38, scale: 2 }),
+            ("orders.id", DataType::Integer),
+            ("orders.user_id", DataType::Integer),
+            ("orders.amount", DataType::Float),
+        ]));
+        assert_eq!(schema(" select amount from orders union select score from users;")?, columns(&[("amount", DataType::Float)]));
+
+        assert_eq!(schema(" explain select * from users;")?, columns(&[("plan", DataType::String)]));
+        assert_eq!(schema(" delete from users;")?, vec![]);
+
+        let plan = Plan::build(Parser::new(" select * from missing;").parse()?)?;
+        assert_eq!(plan.output_schema(&catalog).unwrap_err().to_string(), "Cannot determine the output columns of Scan: missing");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_with_catalog() -> Result<()> {
+        let catalog = catalog(&[" create table users (id int, name varchar);"])?;
+
+        let plan = |sql: &str| Plan::build_with_catalog(Parser::new(sql).parse()?, &catalog);
+
+        assert!(plan(" insert into users (id, name) values (1, 'a');").is_ok());
+        assert!(plan(" select id from users where id = 1;").is_ok());
+        assert!(plan(" select id, name from users;").is_ok());
+        assert!(plan(" update users set name = 'b';").is_ok());
+        assert!(plan(" create table if not exists users (id int);").is_ok());
+
+        assert_eq!(plan(" select * from orders;").unwrap_err().to_string(), "Table orders does not exist");
+        assert_eq!(plan(" insert into orders values (1);").unwrap_err().to_string(), "Table orders does not exist");
+        assert_eq!(plan(" delete from orders;").unwrap_err().to_string(), "Table orders does not exist");
+        assert_eq!(plan(" drop table orders;").unwrap_err().to_string(), "Table orders does not exist");
+        assert_eq!(plan(" create table users (id int);").unwrap_err().to_string(), "Table users already exists");
+
+        assert_eq!(plan(" insert into users (id, age) values (1, 2);").unwrap_err().to_string(), "Column age does not exist in table users");
+        assert_eq!(plan(" select id, age from users;").unwrap_err().to_string(), "Column age does not exist in table users");
+        assert_eq!(plan(" update users set age = 1;").unwrap_err().to_string(), "Column age does not exist in table users");
+
+        // 无目录时不校验
+        assert!(Plan::build(Parser::new(" insert into orders (a) values (1);").parse()?).is_ok());
+        assert!(Plan::build(Parser::new(" drop table orders;").parse()?).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_insert_defaults() -> Result<()> {
+        let catalog = catalog(&[" create table users (id int not null, name varchar null, age int default 18);"])?;
+
+        let plan = |sql: &str| Plan::build_with_catalog(Parser::new(sql).parse()?, &catalog);
+
+        assert_eq!(plan(" insert into users (age, id) values (20, 1), (30, 2);")?, Plan(Node::Insert {
+            table_name: "users".to_string(),
+            columns: vec![],
+            values: rows(vec![
+                vec![Value::Integer(1), Value::Null, Value::Integer(20)],
+                vec![Value::Integer(2), Value::Null, Value::Integer(30)],
+            ]),
+            returning: None,
+        }));
+
+        assert_eq!(plan(" insert into users (id) values (1);")?, Plan(Node::Insert {
+            table_name: "users".to_string(),
+            columns: vec![],
+            values: rows(vec![vec![Value::Integer(1), Value::Null, Value::Integer(18)]]),
+            returning: None,
+        }));
+
+        assert_eq!(plan(" insert into users values (1, 'a', 2);")?, Plan(Node::Insert {
+            table_name: "users".to_string(),
+            columns: vec![],
+            values: rows(vec![vec![Value::Integer(1), Value::String("a".to_string()), Value::Integer(2)]]),
+            returning: None,
+        }));
+
+        assert_eq!(plan(" insert into users (age, id, name) values (default, 1, 'a'), (20, 2, default);")?, Plan(Node::Insert {
+            table_name: "users".to_string(),
+            columns: vec![],
+            values: rows(vec![
+                vec![Value::Integer(1), Value::String("a".to_string()), Value::Integer(18)],
+                vec![Value::Integer(2), Value::Null, Value::Integer(20)],
+            ]),
+            returning: None,
+        }));
+        assert_eq!(plan(" insert into users values (1, default, default);")?, Plan(Node::Insert {
+            table_name: "users".to_string(),
+            columns: vec![],
+            values: rows(vec![vec![Value::Integer(1), Value::Null, Value::Integer(18)]]),
+            returning: None,
+        }));
+        assert_eq!(plan(" insert into users (id) values (default);").unwrap_err().to_string(), "Column id has no default value");
+        assert_eq!(
+            Plan::build(Parser::new(" insert into users values (default);").parse()?).unwrap_err().to_string(),
+            "DEFAULT requires the schema of table users",
+        );
+
+        assert_eq!(plan(" insert into users (name) values ('a');").unwrap_err().to_string(), "Column id is not nullable and has no default value");
+        assert_eq!(plan(" insert into users values (1);").unwrap_err().to_string(), "Insert row 0 has 1 values, expected 3");
+        assert_eq!(plan(" insert into users (id, id) values (1, 2);").unwrap_err().to_string(), "Column id specified more than once at position 1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_insert_batch() -> Result<()> {
+        let catalog = catalog(&[" create table t (a int, b varchar null, c float default 0.5);"])?;
+
+        let tuples = (0..1000).map(|i| format!("('{}', {})", i, i)).collect::<Vec<_>>().join(", ");
+        let Plan(Node::Insert { values, .. }) = Plan::build_with_catalog(Parser::new(&format!(" insert into t (b, a) values {};", tuples)).parse()?, &catalog)? else {
+            panic!("not an insert plan")
+        };
+
+        assert_eq!(values, rows((0..1000).map(|i| vec![Value::Integer(i), Value::String(i.to_string()), Value::Float(0.5)]).collect()));
+
+        // 按行数和列数一次分配
+        assert_eq!(values.capacity(), 1000);
+        assert!(values.iter().all(|row| row.capacity() == 3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_insert_types() -> Result<()> {
+        let catalog = catalog(&[" create table t (a int not null, b float null, c varchar null);"])?;
+
+        let plan = |sql: &str| Plan::build_with_catalog(Parser::new(sql).parse()?, &catalog);
+
+        assert_eq!(plan(" insert into t values (1, 2, 'x'), (2, 2.5, null);")?, Plan(Node::Insert {
+            table_name: "t".to_string(),
+            columns: vec![],
+            values: rows(vec![
+                vec![Value::Integer(1), Value::Float(2.0), Value::String("x".to_string())],
+                vec![Value::Integer(2), Value::Float(2.5), Value::Null],
+            ]),
+            returning: None,
+        }));
+
+        assert_eq!(plan(" insert into t values (1, 2, 'x'), ('x', 2, 'x');").unwrap_err().to_string(), r#"Column a in row 1: Type mismatch: expected Integer, got String("x")"#);
+        assert_eq!(plan(" insert into t (a, c) values (1, 2);").unwrap_err().to_string(), "Column c in row 0: Type mismatch: expected String, got Integer(2)");
+        assert_eq!(plan(" insert into t (a) values (null);").unwrap_err().to_string(), "Column a in row 0 cannot be null");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_insert_coercion() -> Result<()> {
+        let catalog = catalog(&[" create table t (a int null, b bool null, c float null, d decimal(4, 1) null);"])?;
+
+        let plan = |sql: &str| Plan::build_with_catalog(Parser::new(sql).parse()?, &catalog);
+
+        // 整数可转为浮点和定点数
+        assert_eq!(plan(" insert into t values (1, true, 2, 3), (null, false, 1.5, 2.25);")?, Plan(Node::Insert {
+            table_name: "t".to_string(),
+            columns: vec![],
+            values: rows(vec![
+                vec![Value::Integer(1), Value::Boolean(true), Value::Float(2.0), Value::Decimal("3.0".parse()?)],
+                vec![Value::Null, Value::Boolean(false), Value::Float(1.5), Value::Decimal("2.3".parse()?)],
+            ]),
+            returning: None,
+        }));
+
+        // 布尔与整数不互相转换
+        assert_eq!(plan(" insert into t (a) values (true);").unwrap_err().to_string(), "Column a in row 0: Type mismatch: expected Integer, got Boolean(true)");
+        assert_eq!(plan(" insert into t (b) values (1);").unwrap_err().to_string(), "Column b in row 0: Type mismatch: expected Boolean, got Integer(1)");
+        assert_eq!(plan(" insert into t (a) values (1.5);").unwrap_err().to_string(), "Column a in row 0: Type mismatch: expected Integer, got Float(1.5)");
+        assert_eq!(plan(" insert into t (d) values (true);").unwrap_err().to_string(), "Column d in row 0: Type mismatch: expected Decimal { precision: 4, scale: 1 }, got Boolean(true)");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_plan_serde() -> Result<()> {
+        let catalog = catalog(&[" create table t (a int primary key, b float null default 1.5);"])?;
+
+        let sqls = [
+            " create table u (a int primary key, b float null default 1.5);",
+            " insert into t values (1, 0.1), (2, null);",
+            " select distinct a, count(*) as n from t where b between -0.5 and 2.5 group by a having count(*) > 1 order by a desc limit 1;",
+            " update t set b = b * 2 where a in (1, 2);",
+            " explain delete from t where b is not null;",
+            " alter table t add column c varchar null;",
+        ];
+        for sql in sqls {
+            let plan = Plan::build_with_catalog(Parser::new(sql).parse()?, &catalog)?;
+            let json = serde_json::to_string(&plan)?;
+            assert_eq!(serde_json::from_str::<Plan>(&json)?, plan, "{}", json);
+        }
+
         Ok(())
     }
 }
@@ -1,30 +1,629 @@
+use anyhow::{anyhow, bail, Result};
 use derive_new::new;
-use common::ast::Statement;
-use common::schema::Table;
-use crate::{Node, Plan};
+use common::ast::{self, Const, Expression, FromItem, Operator, Position, Projection, Statement};
+use common::ops::{eval_binary, eval_unary};
+use common::schema::{system_table, Catalog, Column, ColumnDefault, Table};
+use common::types::{Collation, DataType, Value};
+use crate::{AlterOperation, Node, Plan, Warning};
+
+// 查询的结果列 (列名, 类型)
+type OutputColumns = Vec<(String, Option<DataType>)>;
 
 #[derive(Debug, new)]
-pub struct Planner;
+pub struct Planner<'a> {
+    // 无目录时不校验表和列
+    catalog: Option<&'a dyn Catalog>,
+    // 严格模式下拒绝 x = NULL / x <> NULL, 否则照常求值为 Null
+    #[new(default)]
+    strict_null: bool,
+    // 建表及加列时未指定 COLLATE 的列所用的比较规则
+    #[new(default)]
+    collation: Collation,
+    // 比较表达式在输入中的位置, 用于标明警告的位置
+    #[new(default)]
+    positions: &'a [(Expression, Position)],
+}
+
+impl<'a> Planner<'a> {
+    pub fn with_strict_null(mut self, strict_null: bool) -> Self {
+        self.strict_null = strict_null;
+        self
+    }
+
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = collation;
+        self
+    }
+
+    // 解析时记下的位置, 见 Parser::positions; 未给出时警告不带位置
+    pub fn with_positions(mut self, positions: &'a [(Expression, Position)]) -> Self {
+        self.positions = positions;
+        self
+    }
+
+    pub fn build(&mut self, stmt: Statement) -> Result<Plan> {
+        self.build_with_warnings(stmt).map(|(plan, _)| plan)
+    }
 
-impl Planner {
-    pub fn build(&mut self, stmt: Statement) -> Plan {
-        Plan(self.build_statement(stmt))
+    // 同 build, 另返回不影响执行的警告; 严格模式下与 NULL 的比较仍为错误
+    pub fn build_with_warnings(&mut self, stmt: Statement) -> Result<(Plan, Vec<Warning>)> {
+        // 相同的表达式依次对应其各次出现的位置
+        let mut positions = self.positions.iter().collect::<Vec<_>>();
+        let warnings = stmt.expressions().into_iter()
+            .flat_map(Self::null_comparisons)
+            .map(|expr| Warning {
+                message: format!("Comparison with NULL always yields NULL, use IS [NOT] NULL instead: {}", expr),
+                position: positions.iter().position(|(e, _)| e == expr).map(|i| positions.remove(i).1),
+            })
+            .collect::<Vec<_>>();
+        if let (true, Some(warning)) = (self.strict_null, warnings.first()) {
+            bail!("{}", warning);
+        }
+
+        Ok((Plan(self.build_statement(stmt)?), warnings))
     }
 
-    fn build_statement(&self, stmt: Statement) -> Node {
-        match stmt {
-            Statement::Create { table_name, columns } => Node::Create {
-                schema: Table {
-                    name: table_name,
-                    columns: columns.into_iter().map(Into::into).collect(),
+    fn build_statement(&self, stmt: Statement) -> Result<Node> {
+        // 系统表只能查询
+        if let Statement::Create { table_name, .. } | Statement::Insert { table_name, .. } | Statement::Update { table_name, .. }
+        | Statement::Delete { table_name, .. } | Statement::Drop { table_name, .. } | Statement::Truncate { table_name }
+        | Statement::AlterTable { table_name, .. } = &stmt {
+            if system_table(table_name).is_some() {
+                bail!("Table {} is read-only", table_name);
+            }
+        }
+
+        // 标量子查询在此只做校验, 由执行器求值
+        for query in stmt.expressions().into_iter().flat_map(Expression::subqueries) {
+            let node = self.build_statement(query.clone())?;
+            if let Some(columns) = self.output_columns(&node)? {
+                if columns.len() != 1 {
+                    bail!("Subquery must return exactly one column, got {}", columns.len());
+                }
+            }
+        }
+
+        Ok(match stmt {
+            Statement::Create { table_name, columns, if_not_exists, primary_key, comment } => {
+                if !if_not_exists && self.get_table(&table_name).is_some() {
+                    bail!("Table {} already exists", table_name);
+                }
+
+                // 自增列须为整数, 每表至多一个
+                let mut auto_increment = columns.iter().filter(|col| col.auto_increment);
+                if let Some(col) = auto_increment.clone().find(|col| col.data_type != DataType::Integer) {
+                    bail!("AUTOINCREMENT column {} must be INTEGER", col.name);
+                }
+                if auto_increment.nth(1).is_some() {
+                    bail!("Table {} has more than one AUTOINCREMENT column", table_name);
+                }
+
+                // CHECK 只能引用本表的列, 不能含聚合
+                for check in columns.iter().flat_map(|col| &col.check) {
+                    if let Some(name) = check.columns().into_iter().find(|&name| !columns.iter().any(|col| &col.name == name)) {
+                        bail!("Column {} does not exist in table {}", name, table_name);
+                    }
+                    if check.contains_aggregate() {
+                        bail!("Aggregate functions are not allowed in CHECK constraints");
+                    }
+                }
+
+                Node::Create {
+                    schema: Table {
+                        name: table_name,
+                        // 表级主键合并到列上
+                        columns: columns.into_iter().map(|mut col| {
+                            col.primary_key |= primary_key.contains(&col.name);
+                            self.build_column(col)
+                        }).collect::<Result<_>>()?,
+                        comment,
+                    },
+                    if_not_exists,
+                }
+            }
+            Statement::Insert { table_name, columns, values, returning } => {
+                self.check_columns(&[&table_name], columns.iter().flatten())?;
+                let returning = self.build_returning(&table_name, returning)?;
+
+                let table = self.get_table(&table_name);
+
+                // 每行值的个数须与指定的列数一致, 未指定列时与表的列数一致
+                let expected = match (&columns, table) {
+                    (Some(columns), _) => Some(columns.len()),
+                    (None, Some(table)) => Some(table.columns.len()),
+                    (None, None) => None,
+                };
+                if let Some(expected) = expected {
+                    for (i, row) in values.iter().enumerate() {
+                        if row.len() != expected {
+                            bail!("Insert row {} has {} values, expected {}", i, row.len(), expected);
+                        }
+                    }
+                }
+
+                // VALUES 中每个位置对应的列, 用于取 DEFAULT 的值
+                let targets = match (table, &columns) {
+                    (Some(table), Some(columns)) => columns.iter().filter_map(|name| table.get_column(name)).collect(),
+                    (Some(table), None) => table.columns.iter().collect(),
+                    (None, _) => vec![],
+                };
+
+                let mut rows = Vec::with_capacity(values.len());
+                for row in values {
+                    rows.push(row.into_iter().enumerate().map(|(i, expr)| match (expr, targets.get(i)) {
+                        (Expression::Default, Some(col)) => Ok(col.default.as_ref().ok_or(anyhow!("Column {} has no default value", col.name))?.to_expression()),
+                        (Expression::Default, None) => bail!("DEFAULT requires the schema of table {}", table_name),
+                        (expr, _) => Ok(expr),
+                    }).collect::<Result<Vec<_>>>()?);
+                }
+
+                // 有目录时按表的列顺序展开, columns 置空表示所有列
+                let (rows, columns) = match (table, columns) {
+                    (Some(table), Some(columns)) => (Self::fill_defaults(table, &columns, rows)?, vec![]),
+                    (_, columns) => (rows, columns.unwrap_or_default()),
+                };
+
+                // 含占位符时绑定参数后才能求值, 含子查询(如动态默认值)时在执行时求值, 由执行器校验类型
+                if rows.iter().flatten().any(|expr| !expr.placeholders().is_empty() || !expr.subqueries().is_empty()) {
+                    return Ok(Node::Insert { table_name, columns, values: rows, returning });
+                }
+
+                let values = rows.iter()
+                    .map(|row| row.iter().map(Self::fold_constant).collect::<Result<Vec<_>>>())
+                    .collect::<Result<Vec<_>>>()?;
+
+                Node::Insert {
+                    table_name,
+                    columns,
+                    values: Self::const_rows(match table {
+                        Some(table) => Self::check_types(table, values)?,
+                        None => values,
+                    }),
+                    returning,
                 }
+            }
+            Statement::Select { distinct, projection, from, filter, group_by, having, order_by, limit, offset } => {
+                let mut exprs = Self::projection_exprs(projection);
+                let conditions = from.conditions().into_iter().flat_map(Expression::columns).cloned().collect::<Vec<_>>();
+                let mut node = self.build_from(from)?;
+
+                // 含聚合函数或分组时由聚合节点计算结果列, 不再投影
+                let aggregated = !group_by.is_empty() || having.is_some() || exprs.iter().any(|(expr, _)| expr.contains_aggregate());
+                let order_by = order_by.into_iter().map(|(key, desc)| (Self::order_key(key, &exprs, aggregated), desc)).collect::<Vec<_>>();
+
+                // 聚合之后的排序键可引用结果列, 其余列均须属于来源表
+                let outputs = exprs.iter().filter(|_| aggregated).map(|(_, name)| name).collect::<Vec<_>>();
+                self.check_scope(&node, exprs.iter().flat_map(|(expr, _)| expr.columns())
+                    .chain(&conditions)
+                    .chain(filter.iter().chain(&group_by).chain(&having).flat_map(Expression::columns))
+                    .chain(order_by.iter().flat_map(|(key, _)| key.columns()).filter(|col| !outputs.contains(col))))?;
+
+                if let Some(predicate) = filter {
+                    node = Node::Filter { source: Box::new(node), predicate };
+                }
+
+                if aggregated {
+                    if exprs.is_empty() {
+                        bail!("SELECT * cannot be used with aggregation");
+                    }
+
+                    let bare_column = exprs.iter().map(|(expr, _)| expr).chain(&having)
+                        .flat_map(|expr| Self::bare_columns(expr, &group_by))
+                        .next();
+                    if let Some(col) = bare_column {
+                        bail!("Column {} must appear in GROUP BY or be used in an aggregate function", col);
+                    }
+                    // 排序在聚合之后, 只能引用结果列
+                    if let Some((key, _)) = order_by.iter().find(|(key, _)| key.contains_aggregate() || key.columns().iter().any(|col| !outputs.contains(col))) {
+                        bail!("ORDER BY key {} must appear in the select list of an aggregate query", key);
+                    }
+
+                    node = Node::Aggregate {
+                        source: Box::new(node),
+                        group_keys: group_by,
+                        aggregates: std::mem::take(&mut exprs),
+                        having,
+                    };
+                }
+
+                if !order_by.is_empty() {
+                    node = Node::Sort { source: Box::new(node), keys: order_by };
+                }
+
+                // select * 不需要投影
+                if !exprs.is_empty() {
+                    node = Node::Projection { source: Box::new(node), exprs };
+                }
+
+                if distinct {
+                    node = Node::Distinct { source: Box::new(node) };
+                }
+
+                // 去重之后再截取
+                if limit.is_some() || offset.is_some() {
+                    node = Node::Limit { source: Box::new(node), limit, offset };
+                }
+
+                node
+            }
+            Statement::Update { table_name, assignments, filter, returning } => {
+                if let Some((i, (col, _))) = assignments.iter().enumerate().find(|(i, (col, _))| assignments[..*i].iter().any(|(c, _)| c == col)) {
+                    bail!("Column {} assigned more than once at position {}", col, i);
+                }
+                self.check_columns(
+                    &[&table_name],
+                    assignments.iter().flat_map(|(col, expr)| std::iter::once(col).chain(expr.columns())).chain(filter.iter().flat_map(Expression::columns)),
+                )?;
+
+                let returning = self.build_returning(&table_name, returning)?;
+
+                Node::Update { table_name, assignments, filter, returning }
+            }
+            Statement::Delete { table_name, filter, returning } => {
+                self.check_columns(&[&table_name], filter.iter().flat_map(Expression::columns))?;
+                let returning = self.build_returning(&table_name, returning)?;
+
+                Node::Delete { table_name, filter, returning }
+            }
+            Statement::Drop { table_name, if_exists } => {
+                if !if_exists {
+                    self.check_columns(&[&table_name], [])?;
+                }
+
+                Node::Drop { table_name, if_exists }
+            }
+            Statement::Truncate { table_name } => {
+                self.check_columns(&[&table_name], [])?;
+
+                Node::Truncate { table_name }
+            }
+            Statement::AlterTable { table_name, op } => {
+                self.check_columns(&[&table_name], [])?;
+
+                let op = match op {
+                    ast::AlterOperation::AddColumn(column) => {
+                        let column = self.build_column(column)?;
+
+                        // 已有的行无法满足主键约束
+                        if column.primary_key {
+                            bail!("Cannot add primary key column {}", column.name);
+                        }
+                        if self.get_table(&table_name).is_some_and(|table| table.get_column(&column.name).is_some()) {
+                            bail!("Column {} already exists in table {}", column.name, table_name);
+                        }
+
+                        AlterOperation::AddColumn(column)
+                    }
+                    ast::AlterOperation::DropColumn(column) => {
+                        self.check_columns(&[&table_name], [&column])?;
+
+                        if self.get_table(&table_name).and_then(|table| table.get_column(&column)).is_some_and(|col| col.primary_key) {
+                            bail!("Cannot drop primary key column {}", column);
+                        }
+
+                        AlterOperation::DropColumn(column)
+                    }
+                };
+
+                Node::AlterTable { table_name, op }
+            }
+            Statement::Union { left, right, all } => {
+                let (left, right) = (self.build_statement(*left)?, self.build_statement(*right)?);
+
+                // 列数须一致, 对应列的类型须能合并, 见 DataType::common_type
+                if let (Some(left), Some(right)) = (self.output_columns(&left)?, self.output_columns(&right)?) {
+                    if left.len() != right.len() {
+                        bail!("Each UNION query must have the same number of columns, got {} and {}", left.len(), right.len());
+                    }
+                    for (i, ((_, a), (_, b))) in left.iter().zip(&right).enumerate() {
+                        if let (Some(a), Some(b)) = (a, b) {
+                            if a.common_type(b).is_none() {
+                                bail!("UNION column {} has incompatible types {} and {}", i + 1, a, b);
+                            }
+                        }
+                    }
+                }
+
+                Node::Union { left: Box::new(left), right: Box::new(right), all }
+            }
+            Statement::Explain(stmt) => Node::Explain { source: Box::new(self.build_statement(*stmt)?) },
+        })
+    }
+
+    // 结果列名: 别名, 其次列名, 其余表达式按位置生成; * 为空
+    fn projection_exprs(projection: Projection) -> Vec<(Expression, String)> {
+        match projection {
+            Projection::All => vec![],
+            Projection::Columns(cols) => cols.into_iter().enumerate().map(|(i, (expr, alias))| {
+                let name = match (alias, &expr) {
+                    (Some(alias), _) => alias,
+                    (None, Expression::Column(name)) => name.clone(),
+                    (None, _) => format!("expr{}", i),
+                };
+                (expr, name)
+            }).collect(),
+        }
+    }
+
+    // 排序在投影之前, 引用别名的键换为对应的表达式; 聚合之后只能按结果列排序, 与结果列相同的表达式换为该列
+    fn order_key(key: Expression, exprs: &[(Expression, String)], aggregated: bool) -> Expression {
+        let found = exprs.iter().find(|(expr, name)| match (&key, aggregated) {
+            (_, true) => *expr == key,
+            (Expression::Column(col), false) => col == name && *expr != key,
+            _ => false,
+        });
+
+        match (found, aggregated) {
+            (Some((_, name)), true) => Expression::Column(name.clone()),
+            (Some((expr, _)), false) => expr.clone(),
+            (None, _) => key,
+        }
+    }
+
+    // RETURNING 只能引用本表的列, 不能含聚合
+    fn build_returning(&self, table_name: &str, returning: Option<Projection>) -> Result<Option<Vec<(Expression, String)>>> {
+        let Some(projection) = returning else { return Ok(None) };
+
+        let exprs = Self::projection_exprs(projection);
+        self.check_columns(&[table_name], exprs.iter().flat_map(|(expr, _)| expr.columns()))?;
+        if exprs.iter().any(|(expr, _)| expr.contains_aggregate()) {
+            bail!("Aggregate functions are not allowed in RETURNING");
+        }
+
+        Ok(Some(exprs))
+    }
+
+    // 同执行器的结果集表头: 不返回行的语句为空, 结果恒为 Null 或类型未知的列按 VARCHAR
+    pub(crate) fn output_schema(&self, node: &Node) -> Result<Vec<(String, DataType)>> {
+        Ok(match node {
+            Node::Create { .. } | Node::Insert { .. } | Node::Update { .. } | Node::Delete { .. } | Node::Drop { .. }
+            | Node::Truncate { .. } | Node::AlterTable { .. } => vec![],
+            Node::Explain { .. } => vec![("plan".to_string(), DataType::String)],
+            node => match self.output_columns(node)? {
+                Some(columns) => columns.into_iter().map(|(name, data_type)| (name, data_type.unwrap_or(DataType::String))).collect(),
+                None => bail!("Cannot determine the output columns of {}", node.to_string().lines().next().unwrap_or_default()),
+            },
+        })
+    }
+
+    // 查询节点的结果列(名称, 类型), 列数未知(无目录时的 SELECT *)时为 None
+    // 无目录或结果恒为 Null 的列类型为 None
+    fn output_columns(&self, node: &Node) -> Result<Option<OutputColumns>> {
+        Ok(match node {
+            Node::Projection { source, exprs } | Node::Aggregate { source, aggregates: exprs, .. } => {
+                let table = self.source_schema(source)?;
+                Some(exprs.iter()
+                    .map(|(expr, name)| Ok((name.clone(), table.as_ref().map_or(Ok(None), |table| table.infer_type(expr))?)))
+                    .collect::<Result<_>>()?)
+            }
+            // 列名取左侧, 类型为两侧合并后的类型, 一侧为 Null 的列取另一侧的类型
+            Node::Union { left, right, .. } => match (self.output_columns(left)?, self.output_columns(right)?) {
+                (Some(left), Some(right)) => Some(left.into_iter().zip(right).map(|((name, a), (_, b))| {
+                    let data_type = match (a, b) {
+                        (Some(a), Some(b)) => a.common_type(&b),
+                        (a, b) => a.or(b),
+                    };
+
+                    (name, data_type)
+                }).collect()),
+                (left, right) => left.or(right),
             },
-            Statement::Insert { table_name, columns, values } => Node::Insert {
-                table_name,
-                columns: columns.unwrap_or_default(),
-                values: values.into_iter().map(|v| v.into_iter().map(Into::into).collect()).collect(),
+            Node::Filter { source, .. } | Node::Sort { source, .. } | Node::Distinct { source } | Node::Limit { source, .. } => {
+                self.output_columns(source)?
+            }
+            node => self.source_schema(node)?.map(|table| table.columns.into_iter().map(|col| (col.name, Some(col.data_type))).collect()),
+        })
+    }
+
+    // 扫描、派生表及连接节点输出行的表结构, 无目录时为 None
+    // 同执行器: 连接结果的列名限定为 table.column, 派生表的列名去掉限定
+    fn source_schema(&self, node: &Node) -> Result<Option<Table>> {
+        Ok(match node {
+            Node::Scan { table_name } => self.get_table(table_name).cloned(),
+            Node::Derived { source, alias } => self.catalog.and(self.output_columns(source)?).map(|columns| Table {
+                name: alias.clone(),
+                columns: columns.into_iter().map(|(name, data_type)| {
+                    let name = name.rsplit_once('.').map_or(name.clone(), |(_, column)| column.to_string());
+                    Column::output(name, data_type.unwrap_or(DataType::String))
+                }).collect(),
+                comment: None,
+            }),
+            Node::NestedLoopJoin { left, right, .. } => {
+                let qualify = |table: Table| table.columns.iter().map(|col| match col.name.contains('.') {
+                    true => col.clone(),
+                    false => Column { name: format!("{}.{}", table.name, col.name), ..col.clone() },
+                }).collect::<Vec<_>>();
+                let (Some(left), Some(right)) = (self.source_schema(left)?, self.source_schema(right)?) else { return Ok(None) };
+
+                Some(Table {
+                    name: format!("{}, {}", left.name, right.name),
+                    columns: [qualify(left), qualify(right)].concat(),
+                    comment: None,
+                })
+            }
+            Node::Filter { source, .. } | Node::Sort { source, .. } | Node::Limit { source, .. } => self.source_schema(source)?,
+            _ => None,
+        })
+    }
+
+    // 默认值先折叠为常量, 含子查询的保留为表达式, 子查询之外的部分须为常量
+    // 未指定比较规则的列使用默认规则
+    fn build_column(&self, mut column: ast::Column) -> Result<Column> {
+        column.collation.get_or_insert(self.collation);
+        match &mut column.default {
+            Some(default) if !default.subqueries().is_empty() => {
+                let mut folded = default.clone();
+                Plan::resolve_expression(&mut folded, &mut |_| Ok(Value::Null))?;
+                Self::fold_constant(&folded)?;
+            }
+            Some(default) => *default = Const::from(Self::fold_constant(default)?).into(),
+            None => {}
+        }
+
+        column.try_into()
+    }
+
+    // 求只含常量的表达式的值, 如 1 + 2、-(3 * 0.5)
+    pub fn fold_constant(expr: &Expression) -> Result<Value> {
+        Ok(match expr {
+            Expression::Const(c) => c.clone().into(),
+            Expression::UnaryOp { op, expr } => eval_unary(op, Self::fold_constant(expr)?)?,
+            Expression::BinaryOp { left, op, right } => eval_binary(op, Self::fold_constant(left)?, Self::fold_constant(right)?)?,
+            Expression::IsNull { expr, negated } => Value::Boolean((Self::fold_constant(expr)? == Value::Null) != *negated),
+            Expression::Cast { expr, target } => Self::fold_constant(expr)?.cast_to(target)?,
+            Expression::Column(name) => bail!("Column {} is not allowed in a constant expression", name),
+            Expression::Placeholder(n) => bail!("Placeholder ${} is not bound", n),
+            expr => bail!("Not a constant expression: {}", expr),
+        })
+    }
+
+    fn const_rows(values: Vec<Vec<Value>>) -> Vec<Vec<Expression>> {
+        values.into_iter().map(|row| row.into_iter().map(|v| Expression::Const(v.into())).collect()).collect()
+    }
+
+    // 无目录时返回 None
+    fn get_table(&self, table_name: &str) -> Option<&Table> {
+        self.catalog.and_then(|catalog| catalog.get_table(table_name).or(system_table(table_name)))
+    }
+
+    // 按表的列顺序重排每行, 未指定的列取默认值
+    // 列与值的对应关系只计算一次, 各行按固定容量分配
+    pub fn fill_defaults(table: &Table, columns: &[String], values: Vec<Vec<Expression>>) -> Result<Vec<Vec<Expression>>> {
+        if let Some((i, col)) = columns.iter().enumerate().find(|(i, col)| columns[..*i].contains(col)) {
+            bail!("Column {} specified more than once at position {}", col, i);
+        }
+
+        // 表中每列在插入列中的位置
+        let indexes = table.columns.iter()
+            .map(|col| columns.iter().position(|name| name == &col.name))
+            .collect::<Vec<_>>();
+
+        if let Some((col, _)) = table.columns.iter().zip(&indexes).find(|(col, index)| index.is_none() && col.default.is_none() && !col.auto_increment) {
+            bail!("Column {} is not nullable and has no default value", col.name);
+        }
+
+        let mut rows = Vec::with_capacity(values.len());
+        for mut row in values {
+            let mut filled = Vec::with_capacity(table.columns.len());
+            for (col, index) in table.columns.iter().zip(&indexes) {
+                filled.push(match (index, &col.default) {
+                    (Some(i), _) => std::mem::replace(&mut row[*i], Expression::Const(Const::Null)),
+                    // 缺少默认值的列已在上面检查, 自增列先置为 Null
+                    (None, default) => default.as_ref().map_or(Expression::Const(Const::Null), ColumnDefault::to_expression),
+                });
+            }
+            rows.push(filled);
+        }
+
+        Ok(rows)
+    }
+
+    // 校验每个值可赋给对应列并转换为列类型, Null 仅允许可空列及待分配序号的自增列
+    pub fn check_types(table: &Table, values: Vec<Vec<Value>>) -> Result<Vec<Vec<Value>>> {
+        let mut rows = Vec::with_capacity(values.len());
+        for (i, row) in values.into_iter().enumerate() {
+            if row.len() != table.columns.len() {
+                bail!("Insert row {} has {} values, expected {}", i, row.len(), table.columns.len());
+            }
+
+            let mut checked = Vec::with_capacity(row.len());
+            for (col, value) in table.columns.iter().zip(row) {
+                checked.push(match (value, &col.data_type) {
+                    (Value::Null, _) if col.nullable || col.auto_increment => Value::Null,
+                    (Value::Null, _) => bail!("Column {} in row {} cannot be null", col.name, i),
+                    (value, data_type) => value.coerce_to(data_type).map_err(|e| anyhow!("Column {} in row {}: {}", col.name, i, e))?,
+                });
+            }
+            rows.push(checked);
+        }
+
+        Ok(rows)
+    }
+
+    // 聚合函数及分组表达式之外引用的列
+    fn bare_columns<'e>(expr: &'e Expression, group_keys: &[Expression]) -> Vec<&'e String> {
+        match expr {
+            expr if group_keys.contains(expr) => vec![],
+            Expression::Column(name) => vec![name],
+            Expression::Aggregate { .. } => vec![],
+            expr => expr.children().into_iter().flat_map(|child| Self::bare_columns(child, group_keys)).collect(),
+        }
+    }
+
+    // 与 NULL 常量做 = 或 <> 比较的子表达式, 其结果恒为 Null
+    fn null_comparisons(expr: &Expression) -> Vec<&Expression> {
+        match expr {
+            Expression::BinaryOp { left, op: Operator::Equal | Operator::NotEqual, right }
+                if [left, right].iter().any(|side| matches!(side.as_ref(), Expression::Const(Const::Null))) => vec![expr],
+            Expression::Subquery(query) => query.expressions().into_iter().flat_map(Self::null_comparisons).collect(),
+            expr => expr.children().into_iter().flat_map(Self::null_comparisons).collect(),
+        }
+    }
+
+    // 连接构建为左深的嵌套循环连接
+    // 派生表的查询在此规划
+    fn build_from(&self, from: FromItem) -> Result<Node> {
+        Ok(match from {
+            FromItem::Table(table_name) => Node::Scan { table_name },
+            FromItem::Subquery { query, alias } => Node::Derived { source: Box::new(self.build_statement(*query)?), alias },
+            FromItem::Join { left, right, on } => Node::NestedLoopJoin {
+                left: Box::new(self.build_from(*left)?),
+                right: Box::new(self.build_from(*right)?),
+                // 交叉连接的条件恒为真
+                predicate: on.unwrap_or(Const::Boolean(true).into()),
             },
-            Statement::Select { table_name } => Node::Scan { table_name },
+        })
+    }
+
+    // 有目录时校验表及列存在, 限定列名 table.column 须属于对应的表
+    fn check_columns<'c>(&self, table_names: &[&str], columns: impl IntoIterator<Item=&'c String>) -> Result<()> {
+        let Some(catalog) = self.catalog else { return Ok(()) };
+
+        let mut tables = vec![];
+        for table_name in table_names {
+            let Some(table) = catalog.get_table(table_name).or(system_table(table_name)) else {
+                bail!("Table {} does not exist", table_name);
+            };
+            tables.push(table);
         }
+
+        Self::check_columns_in(&tables, columns)
+    }
+
+    // 查询来源为扫描、派生表及其连接, 列须属于其中的表
+    fn check_scope<'c>(&self, node: &Node, columns: impl IntoIterator<Item=&'c String>) -> Result<()> {
+        if self.catalog.is_none() {
+            return Ok(());
+        }
+
+        let tables = self.scope(node)?;
+        Self::check_columns_in(&tables.iter().collect::<Vec<_>>(), columns)
+    }
+
+    fn scope(&self, node: &Node) -> Result<Vec<Table>> {
+        Ok(match node {
+            Node::Scan { table_name } => vec![self.get_table(table_name).cloned().ok_or(anyhow!("Table {} does not exist", table_name))?],
+            Node::NestedLoopJoin { left, right, .. } => [self.scope(left)?, self.scope(right)?].concat(),
+            node => self.source_schema(node)?.into_iter().collect(),
+        })
+    }
+
+    fn check_columns_in<'c>(tables: &[&Table], columns: impl IntoIterator<Item=&'c String>) -> Result<()> {
+        let table_names = tables.iter().map(|table| table.name.as_str()).collect::<Vec<_>>();
+
+        for col in columns {
+            let found = match col.split_once('.') {
+                Some((table_name, column)) => tables.iter().filter(|table| table.name == table_name && table.get_column(column).is_some()).count(),
+                None => tables.iter().filter(|table| table.get_column(col).is_some()).count(),
+            };
+
+            match found {
+                0 => bail!("Column {} does not exist in table {}", col, table_names.join(", ")),
+                1 => {}
+                _ => bail!("Ambiguous column {}", col),
+            }
+        }
+
+        Ok(())
     }
-}
\ No newline at end of file
+}
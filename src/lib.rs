@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+use anyhow::Result;
+use executor::{Executor, FileStorage, MemoryStorage, Storage};
+use parser::Parser;
+use planner::{Node, Planner};
+
+pub use common::types::{Collation, DataType, Value};
+pub use executor::{ExecResult, ResultSet};
+
+// 数据库入口, 依次完成解析、规划(使用当前目录)和执行
+#[derive(Debug)]
+pub struct Database {
+    storage: Box<dyn Storage>,
+    // 新建的列未指定 COLLATE 时的比较规则
+    collation: Collation,
+}
+
+impl Database {
+    // 文件不存在时新建空库
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            storage: Box::new(FileStorage::open(path)?),
+            collation: Collation::default(),
+        })
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            storage: Box::new(MemoryStorage::default()),
+            collation: Collation::default(),
+        }
+    }
+
+    // 只影响之后建表或加列时未指定 COLLATE 的列, 已有的列不变
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = collation;
+        self
+    }
+
+    // 修改数据的语句执行后立即写入存储, 带 RETURNING 的也返回结果集
+    pub fn execute(&mut self, sql: &str) -> Result<ExecResult> {
+        let plan = Planner::new(Some(self.storage.as_ref())).with_collation(self.collation).build(Parser::new(sql).parse()?)?;
+        let returning = matches!(plan.0, Node::Insert { returning: Some(_), .. } | Node::Update { returning: Some(_), .. } | Node::Delete { returning: Some(_), .. });
+        let result = Executor::new(self.storage.as_mut()).execute(plan)?;
+        if returning || matches!(result, ExecResult::Affected(_)) {
+            self.storage.flush()?;
+        }
+
+        Ok(result)
+    }
+
+    pub fn close(mut self) -> Result<()> {
+        self.storage.flush()
+    }
+}
@@ -0,0 +1,244 @@
+use std::fs;
+use anyhow::Result;
+use rsqldb::{Collation, DataType, Database, ExecResult, ResultSet, Value};
+
+fn query(db: &mut Database, sql: &str) -> Result<ResultSet> {
+    match db.execute(sql)? {
+        ExecResult::Rows(result) => Ok(result),
+        result => panic!("not a query result: {:?}", result),
+    }
+}
+
+#[test]
+fn test_database_flow() -> Result<()> {
+    let mut db = Database::in_memory();
+
+    db.execute("create table users (id int primary key, name varchar null, score float default 0);")?;
+    assert_eq!(db.execute("insert into users values (1, 'a', 1.5), (2, 'b', 2), (3, null, 3.5);")?, ExecResult::Affected(3));
+
+    assert_eq!(query(&mut db, "select * from users where score > 1.5")?, ResultSet {
+        columns: vec!["id".to_string(), "name".to_string(), "score".to_string()],
+        column_types: vec![DataType::Integer, DataType::String, DataType::Float],
+        rows: vec![
+            vec![Value::Integer(2), Value::String("b".to_string()), Value::Float(2.0)],
+            vec![Value::Integer(3), Value::Null, Value::Float(3.5)],
+        ],
+    });
+
+    assert_eq!(db.execute("update users set score = score * 2, name = 'c' where name is null;")?, ExecResult::Affected(1));
+    assert_eq!(query(&mut db, "select name, score from users where id = 3;")?.rows, vec![
+        vec![Value::String("c".to_string()), Value::Float(7.0)],
+    ]);
+
+    assert_eq!(db.execute("delete from users where score < 5;")?, ExecResult::Affected(2));
+    assert_eq!(query(&mut db, "select id from users;")?.rows, vec![vec![Value::Integer(3)]]);
+
+    assert_eq!(db.execute("delete from users;")?, ExecResult::Affected(1));
+    assert_eq!(query(&mut db, "select count(*) from users;")?.rows, vec![vec![Value::Integer(0)]]);
+
+    assert_eq!(query(&mut db, "select * from missing;").unwrap_err().to_string(), "Table missing does not exist");
+
+    Ok(())
+}
+
+#[test]
+fn test_database_update_type_error() -> Result<()> {
+    let mut db = Database::in_memory();
+
+    db.execute("create table t (a int, b varchar null);")?;
+    db.execute("insert into t values (1, 'x');")?;
+
+    // 更新失败时不修改任何行
+    assert_eq!(db.execute("update t set b = a;").unwrap_err().to_string(), "Column b in row 0: Type mismatch: expected String, got Integer(1)");
+    assert_eq!(query(&mut db, "select b from t;")?.rows, vec![vec![Value::String("x".to_string())]]);
+
+    Ok(())
+}
+
+#[test]
+fn test_database_open() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("rsqldb_database_{}.db", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut db = Database::open(&path)?;
+    db.execute("create table t (a int, b varchar null);")?;
+    db.execute("insert into t values (1, 'x'), (2, null);")?;
+    db.close()?;
+
+    let mut db = Database::open(&path)?;
+    db.execute("update t set b = 'y' where a = 2;")?;
+
+    // 修改语句执行后即写入文件
+    let mut reopened = Database::open(&path)?;
+    assert_eq!(query(&mut reopened, "select * from t;")?.rows, vec![
+        vec![Value::Integer(1), Value::String("x".to_string())],
+        vec![Value::Integer(2), Value::String("y".to_string())],
+    ]);
+
+    // 带 RETURNING 的修改同样写入
+    assert_eq!(query(&mut db, "delete from t where a = 1 returning b;")?.rows, vec![vec![Value::String("x".to_string())]]);
+    let mut reopened = Database::open(&path)?;
+    assert_eq!(query(&mut reopened, "select a from t;")?.rows, vec![vec![Value::Integer(2)]]);
+    drop(db);
+
+    fs::remove_file(&path)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_database_ident_case() -> Result<()> {
+    let mut db = Database::in_memory();
+
+    db.execute("create table users (Id int);")?;
+    db.execute("insert into USERS (ID) values (1);")?;
+    assert_eq!(query(&mut db, "select ID from Users;")?, ResultSet {
+        columns: vec!["id".to_string()],
+        column_types: vec![DataType::Integer],
+        rows: vec![vec![Value::Integer(1)]],
+    });
+
+    // 加引号的标识符保留大小写
+    assert_eq!(query(&mut db, r#"select * from "Users";"#).unwrap_err().to_string(), "Table Users does not exist");
+    db.execute(r#"create table "Users" ("Id" int);"#)?;
+    db.execute(r#"insert into "Users" values (2);"#)?;
+    assert_eq!(query(&mut db, r#"select "Id" from "Users";"#)?.rows, vec![vec![Value::Integer(2)]]);
+    assert_eq!(query(&mut db, r#"select id from "Users";"#).unwrap_err().to_string(), "Column id does not exist in table Users");
+    assert_eq!(query(&mut db, "select * from users;")?.rows, vec![vec![Value::Integer(1)]]);
+
+    Ok(())
+}
+
+#[test]
+fn test_database_keyword_ident() -> Result<()> {
+    let mut db = Database::in_memory();
+
+    db.execute(r#"create table "table" ("default" int, `key` varchar null);"#)?;
+    db.execute(r#"insert into "table" ("default", `key`) values (1, 'a');"#)?;
+    assert_eq!(query(&mut db, r#"select `key` from "table" where "default" = 1;"#)?.rows, vec![vec![Value::String("a".to_string())]]);
+
+    assert!(query(&mut db, "select key from \"table\";").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_database_decimal() -> Result<()> {
+    let mut db = Database::in_memory();
+
+    db.execute("create table prices (id int, price decimal(6, 2) default 1);")?;
+    db.execute("insert into prices values (1, 12.345), (2, 3), (3, -0.004);")?;
+    db.execute("insert into prices (id) values (4);")?;
+
+    let result = query(&mut db, "select price, price * 2, price + 1.5 from prices;")?;
+    let rendered = result.rows.iter()
+        .map(|row| row.iter().map(ToString::to_string).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(rendered, vec![
+        vec!["12.35", "24.70", "13.85"],
+        vec!["3.00", "6.00", "4.5"],
+        vec!["0.00", "0.00", "1.5"],
+        vec!["1.00", "2.00", "2.5"],
+    ]);
+
+    assert_eq!(query(&mut db, "select sum(price) from prices where price > 2;")?.rows[0][0].to_string(), "15.35");
+
+    assert_eq!(
+        db.execute("insert into prices values (5, 10000);").unwrap_err().to_string(),
+        "Column price in row 0: Value 10000 out of range for DECIMAL(6, 2)",
+    );
+
+    // 乘积的小数位数不超过 38, 多出的位四舍五入
+    db.execute("create table ratios (r decimal(38, 38));")?;
+    db.execute("insert into ratios values (0.5), (0.123), (0.00000000000000000008);")?;
+    let result = query(&mut db, "select r * r, cast(r * r as int) from ratios;")?;
+    assert_eq!(result.column_types, vec![DataType::Decimal { precision: 38, scale: 38 }, DataType::Integer]);
+    assert_eq!(result.rows.iter().map(|row| row[0].to_string()).collect::<Vec<_>>(), vec![
+        format!("0.25{}", "0".repeat(36)),
+        format!("0.015129{}", "0".repeat(32)),
+        format!("0.{}1", "0".repeat(37)),
+    ]);
+    assert_eq!(result.rows[1][1], Value::Integer(0));
+
+    Ok(())
+}
+
+#[test]
+fn test_database_date() -> Result<()> {
+    let mut db = Database::in_memory();
+
+    db.execute("create table events (d date, ts timestamp default '2024-01-01');")?;
+    db.execute("insert into events values ('2024-01-02', '2024-01-02 03:04:05'), ('2023-12-31', '2024-01-01 23:59:59.5');")?;
+    db.execute("insert into events (d) values ('2024-02-29');")?;
+
+    let rendered = query(&mut db, "select d, ts from events where d >= '2024-01-01';")?.rows.iter()
+        .map(|row| row.iter().map(ToString::to_string).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(rendered, vec![
+        vec!["2024-01-02", "2024-01-02 03:04:05"],
+        vec!["2024-02-29", "2024-01-01 00:00:00"],
+    ]);
+
+    assert_eq!(
+        db.execute("insert into events values ('2023-02-29', '2024-01-01');").unwrap_err().to_string(),
+        "Column d in row 0: Invalid DATE: 2023-02-29",
+    );
+    assert_eq!(
+        db.execute("insert into events values ('2024-01-01', '2024-01-01 25:00:00');").unwrap_err().to_string(),
+        "Column ts in row 0: Invalid TIMESTAMP: 2024-01-01 25:00:00",
+    );
+    assert_eq!(
+        db.execute("insert into events values (20240101, '2024-01-01');").unwrap_err().to_string(),
+        "Column d in row 0: Type mismatch: expected Date, got Integer(20240101)",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_database_collation() -> Result<()> {
+    let mut db = Database::in_memory();
+
+    db.execute("create table users (name varchar, nick varchar collate nocase);")?;
+    db.execute("insert into users values ('Alice', 'Alice'), ('bob', 'Bob');")?;
+    assert_eq!(query(&mut db, "select name from users where name = 'alice';")?.rows, Vec::<Vec<Value>>::new());
+    assert_eq!(query(&mut db, "select name from users where nick = 'alice';")?.rows, vec![vec![Value::String("Alice".to_string())]]);
+    assert_eq!(query(&mut db, "select count(*) from users where nick in ('ALICE', 'BOB');")?.rows, vec![vec![Value::Integer(2)]]);
+    assert_eq!(query(&mut db, "select name from users where 'BOB' in (nick);")?.rows, vec![vec![Value::String("bob".to_string())]]);
+    assert_eq!(query(&mut db, "select name from users where nick like 'a%';")?.rows, vec![vec![Value::String("Alice".to_string())]]);
+    assert_eq!(query(&mut db, "select count(*) from users where name like 'a%';")?.rows, vec![vec![Value::Integer(0)]]);
+
+    // 数据库的默认规则只作用于未指定 COLLATE 的新列
+    let mut db = Database::in_memory().with_collation(Collation::NoCase);
+    db.execute("create table users (name varchar, code varchar collate binary);")?;
+    db.execute("insert into users values ('Alice', 'A1');")?;
+    assert_eq!(query(&mut db, "select count(*) from users where name = 'ALICE';")?.rows, vec![vec![Value::Integer(1)]]);
+    assert_eq!(query(&mut db, "select count(*) from users where code = 'a1';")?.rows, vec![vec![Value::Integer(0)]]);
+
+    Ok(())
+}
+
+#[test]
+fn test_database_order_by() -> Result<()> {
+    let mut db = Database::in_memory();
+
+    db.execute("create table scores (name varchar, score int null);")?;
+    db.execute("insert into scores values ('b', 80), ('a', null), ('c', 95), ('d', 80);")?;
+
+    assert_eq!(query(&mut db, "select name, score from scores order by score desc, name;")?.rows, vec![
+        vec![Value::String("c".to_string()), Value::Integer(95)],
+        vec![Value::String("b".to_string()), Value::Integer(80)],
+        vec![Value::String("d".to_string()), Value::Integer(80)],
+        vec![Value::String("a".to_string()), Value::Null],
+    ]);
+    assert_eq!(query(&mut db, "select name from scores order by score limit 2;")?.rows, vec![
+        vec![Value::String("a".to_string())],
+        vec![Value::String("b".to_string())],
+    ]);
+    assert_eq!(query(&mut db, "select score, count(*) as n from scores where score is not null group by score order by n desc, score;")?.rows, vec![
+        vec![Value::Integer(80), Value::Integer(2)],
+        vec![Value::Integer(95), Value::Integer(1)],
+    ]);
+
+    Ok(())
+}